@@ -0,0 +1,216 @@
+use crate::error::ApiError;
+use actix_web::HttpRequest;
+use codec::Encode;
+use provotum_sdk::keystore::Keystore;
+use provotum_sdk::rpc::get_sealers;
+use sp_keyring::sr25519::sr25519::Pair;
+use std::{
+    collections::HashMap,
+    convert::TryInto,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use substrate_subxt::{
+    sp_core::{crypto::Ss58Codec, hashing::blake2_256, sr25519, Pair as KeyPairGenerator},
+    system::System,
+    Client, NodeTemplateRuntime,
+};
+
+pub type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+/// the sealer's account (ss58)
+pub const ACCOUNT_HEADER: &str = "X-Sealer-Account";
+
+/// the unix timestamp (seconds) at which the request was signed
+pub const TIMESTAMP_HEADER: &str = "X-Sealer-Timestamp";
+
+/// the hex encoded sr25519 signature of the request
+pub const SIGNATURE_HEADER: &str = "X-Sealer-Signature";
+
+/// the context prefix of the message signed by a sealer
+pub const SEALER_SIGNING_CONTEXT: &[u8] = b"provotum-api-sealer";
+
+/// the maximum difference (seconds) between the request's timestamp and the server time
+const MAX_CLOCK_SKEW: u64 = 5 * 60;
+
+/// Creates the message a sealer signs: the method, path, timestamp and the hash of the body.
+pub fn request_message(method: &str, path: &str, timestamp: u64, body: &[u8]) -> Vec<u8> {
+    (
+        SEALER_SIGNING_CONTEXT,
+        method.as_bytes(),
+        path.as_bytes(),
+        timestamp,
+        blake2_256(body),
+    )
+        .encode()
+}
+
+/// Authenticates sr25519 signed requests.
+///
+/// Each signature is accepted only once within the allowed clock skew to prevent replays.
+pub struct SealerAuth {
+    seen: Mutex<HashMap<Vec<u8>, u64>>,
+}
+
+impl SealerAuth {
+    pub fn new() -> Self {
+        SealerAuth {
+            seen: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Verifies the signature headers of the request, returns the authenticated account.
+    pub fn authenticate(
+        &self,
+        req: &HttpRequest,
+        body: &[u8],
+        now: u64,
+    ) -> Result<AccountId, ApiError> {
+        let account = header(req, ACCOUNT_HEADER)?;
+        let account = AccountId::from_ss58check(account)
+            .map_err(|_| ApiError::Unauthenticated("invalid account"))?;
+        let timestamp: u64 = header(req, TIMESTAMP_HEADER)?
+            .parse()
+            .map_err(|_| ApiError::Unauthenticated("invalid timestamp"))?;
+        let signature = hex::decode(header(req, SIGNATURE_HEADER)?)
+            .map_err(|_| ApiError::Unauthenticated("invalid signature"))?;
+
+        if now.abs_diff(timestamp) > MAX_CLOCK_SKEW {
+            return Err(ApiError::Unauthenticated("expired timestamp"));
+        }
+
+        let raw: [u8; 64] = signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| ApiError::Unauthenticated("invalid signature"))?;
+        let message = request_message(req.method().as_str(), req.path(), timestamp, body);
+        let public = sr25519::Public::from_raw(account.clone().into());
+        if !sr25519::Pair::verify(&sr25519::Signature::from_raw(raw), &message, &public) {
+            return Err(ApiError::Unauthenticated("invalid signature"));
+        }
+
+        // forget signatures which would be refused anyway, reject replays
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, seen_at| *seen_at + 2 * MAX_CLOCK_SKEW > now);
+        if seen.insert(signature, now).is_some() {
+            return Err(ApiError::Unauthenticated("replayed request"));
+        }
+        Ok(account)
+    }
+}
+
+/// Ties the route parameter `{sealer}` to the authenticated account.
+///
+/// The account must be the sealer's key in the keystore and a registered sealer on chain.
+pub async fn authorize(
+    req: &HttpRequest,
+    body: &[u8],
+    sealer: &str,
+    keystore: &Keystore,
+    auth: &SealerAuth,
+    client: &Client<NodeTemplateRuntime>,
+) -> Result<Pair, ApiError> {
+    let account = auth.authenticate(req, body, now())?;
+    let pair = ensure_sealer(&account, sealer, keystore)?;
+    let sealers = get_sealers(client).await?;
+    if !sealers.contains(&account) {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(pair)
+}
+
+fn ensure_sealer(account: &AccountId, sealer: &str, keystore: &Keystore) -> Result<Pair, ApiError> {
+    let pair = keystore.get_sealer(sealer)?;
+    if AccountId::from(pair.public()) != *account {
+        return Err(ApiError::Forbidden);
+    }
+    Ok(pair)
+}
+
+fn header<'a>(req: &'a HttpRequest, name: &'static str) -> Result<&'a str, ApiError> {
+    req.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(ApiError::Unauthenticated("missing signature headers"))
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time went backwards")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use sp_keyring::AccountKeyring;
+
+    const NOW: u64 = 1_600_000_000;
+
+    fn signed_request(keyring: AccountKeyring, timestamp: u64, body: &[u8]) -> HttpRequest {
+        let path = "/keygen/vote/bob";
+        let message = request_message("POST", path, timestamp, body);
+        let signature = keyring.pair().sign(&message);
+        TestRequest::post()
+            .uri(path)
+            .header(ACCOUNT_HEADER, keyring.to_account_id().to_ss58check())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, hex::encode(signature.0))
+            .to_http_request()
+    }
+
+    #[test]
+    fn it_should_authenticate_a_signed_request() {
+        let auth = SealerAuth::new();
+        let req = signed_request(AccountKeyring::Bob, NOW, b"body");
+        let account = auth.authenticate(&req, b"body", NOW).unwrap();
+        assert_eq!(account, AccountKeyring::Bob.to_account_id());
+
+        // the same request cannot be replayed
+        let result = auth.authenticate(&req, b"body", NOW);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+    }
+
+    #[test]
+    fn it_should_refuse_a_modified_body() {
+        let auth = SealerAuth::new();
+        let req = signed_request(AccountKeyring::Bob, NOW, b"body");
+        let result = auth.authenticate(&req, b"other body", NOW);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+    }
+
+    #[test]
+    fn it_should_refuse_an_expired_request() {
+        let auth = SealerAuth::new();
+        let req = signed_request(AccountKeyring::Bob, NOW - MAX_CLOCK_SKEW - 1, b"body");
+        let result = auth.authenticate(&req, b"body", NOW);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+    }
+
+    #[test]
+    fn it_should_refuse_missing_headers() {
+        let auth = SealerAuth::new();
+        let req = TestRequest::post()
+            .uri("/keygen/vote/bob")
+            .to_http_request();
+        let result = auth.authenticate(&req, b"body", NOW);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+    }
+
+    #[test]
+    fn it_should_tie_the_sealer_to_the_account() {
+        let keystore = Keystore::from_pairs(vec![
+            ("bob".to_string(), AccountKeyring::Bob.pair()),
+            ("charlie".to_string(), AccountKeyring::Charlie.pair()),
+        ]);
+        let bob = AccountKeyring::Bob.to_account_id();
+        assert!(ensure_sealer(&bob, "bob", &keystore).is_ok());
+
+        let result = ensure_sealer(&bob, "charlie", &keystore);
+        assert!(matches!(result, Err(ApiError::Forbidden)));
+        let result = ensure_sealer(&bob, "dave", &keystore);
+        assert!(matches!(result, Err(ApiError::UnknownSealer(_))));
+    }
+}
@@ -0,0 +1,271 @@
+use crate::{error::ApiError, relay::EligibleVoters};
+use actix_web::{post, web};
+use crypto::blind::{BlindPrivateKey, BlindSignature};
+use num_bigint::BigUint;
+use pallet_mixnet::types::VoteId;
+use provotum_sdk::auth::credential_request_message;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use substrate_subxt::{
+    sp_core::{crypto::Ss58Codec, sr25519, Pair as KeyPairGenerator},
+    system::System,
+    NodeTemplateRuntime,
+};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CredentialRequestBody {
+    // the voter's identity (ss58), checked against the eligible voters
+    pub voter: String,
+    // the blinded message (hex, big-endian)
+    pub blinded: String,
+    // the voter's signature of the request (hex)
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct CredentialResponseBody {
+    pub vote: String,
+    // the blind signature (hex, big-endian), unblinded by the voter it becomes the voter token
+    pub blind_signature: String,
+}
+
+/// A single line of the issuance log.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+struct IssuedCredential {
+    vote: String,
+    voter: String,
+}
+
+/// Blind-signs the voter tokens of eligible voters, each voter receives one per vote.
+///
+/// The private key of each vote is read from `<dir>/<vote>.json`.
+/// The issued credentials are appended to a log to survive restarts.
+pub struct CredentialIssuer {
+    dir: PathBuf,
+    issued: Mutex<HashSet<(VoteId, AccountId)>>,
+    log: Mutex<File>,
+}
+
+impl CredentialIssuer {
+    pub fn open(dir: &Path, log: &Path) -> io::Result<Self> {
+        let mut issued = HashSet::new();
+        if log.exists() {
+            for line in BufReader::new(File::open(log)?).lines() {
+                let entry: IssuedCredential = serde_json::from_str(&line?)?;
+                let voter = AccountId::from_ss58check(&entry.voter)
+                    .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid voter"))?;
+                issued.insert((entry.vote.into_bytes(), voter));
+            }
+        }
+        let file = OpenOptions::new().create(true).append(true).open(log)?;
+        Ok(CredentialIssuer {
+            dir: dir.to_path_buf(),
+            issued: Mutex::new(issued),
+            log: Mutex::new(file),
+        })
+    }
+
+    fn key(&self, vote: &str) -> Result<BlindPrivateKey, ApiError> {
+        // the vote is part of the path, it must not leave the directory
+        if vote.is_empty()
+            || !vote
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(ApiError::CredentialKeyDoesNotExist);
+        }
+        let path = self.dir.join(format!("{}.json", vote));
+        let content = fs::read_to_string(path).map_err(|_| ApiError::CredentialKeyDoesNotExist)?;
+        serde_json::from_str(&content).map_err(|_| ApiError::CredentialKeyDoesNotExist)
+    }
+
+    /// Blind-signs the request of an eligible voter which hasn't received a credential yet.
+    pub fn issue(
+        &self,
+        vote: &str,
+        body: &CredentialRequestBody,
+        eligible_voters: &EligibleVoters,
+    ) -> Result<BigUint, ApiError> {
+        let vote_id = vote.as_bytes().to_vec();
+        let voter = AccountId::from_ss58check(&body.voter)
+            .map_err(|_| ApiError::Unauthenticated("invalid voter"))?;
+        let blinded = hex::decode(&body.blinded)
+            .map_err(|_| ApiError::InvalidCredentialRequest("invalid blinded message"))?;
+        verify_request(&vote_id, &voter, &blinded, &body.signature)?;
+        if !eligible_voters.is_eligible(&vote_id, &voter) {
+            return Err(ApiError::NotEligible);
+        }
+
+        let key = self.key(vote)?;
+        let blinded = BigUint::from_bytes_be(&blinded);
+        if blinded >= key.public.n {
+            return Err(ApiError::InvalidCredentialRequest(
+                "blinded message out of range",
+            ));
+        }
+
+        // a second credential would allow a second ballot
+        let mut issued = self.issued.lock().unwrap();
+        if issued.contains(&(vote_id.clone(), voter.clone())) {
+            return Err(ApiError::CredentialAlreadyIssued);
+        }
+        self.record(vote, &voter)?;
+        issued.insert((vote_id, voter));
+        Ok(BlindSignature::sign(&key, &blinded))
+    }
+
+    fn record(&self, vote: &str, voter: &AccountId) -> Result<(), ApiError> {
+        let entry = IssuedCredential {
+            vote: vote.to_string(),
+            voter: voter.to_ss58check(),
+        };
+        let mut line = serde_json::to_vec(&entry).map_err(ApiError::InvalidBody)?;
+        line.push(b'\n');
+        let mut file = self.log.lock().unwrap();
+        file.write_all(&line)
+            .and_then(|_| file.flush())
+            .map_err(|_| ApiError::CredentialLogUnavailable)
+    }
+}
+
+fn verify_request(
+    vote_id: &VoteId,
+    voter: &AccountId,
+    blinded: &[u8],
+    signature: &str,
+) -> Result<(), ApiError> {
+    let signature = hex::decode(signature)
+        .ok()
+        .and_then(|signature| signature.as_slice().try_into().ok())
+        .map(sr25519::Signature::from_raw)
+        .ok_or(ApiError::Unauthenticated("invalid signature"))?;
+    let public = sr25519::Public::from_raw(voter.clone().into());
+    let message = credential_request_message(vote_id, blinded);
+    if !sr25519::Pair::verify(&signature, &message, &public) {
+        return Err(ApiError::Unauthenticated("invalid signature"));
+    }
+    Ok(())
+}
+
+/// Issues the blind signature of a voter token.
+#[post("/credentials/{vote}")]
+async fn issue_credential(
+    web::Path(vote): web::Path<String>,
+    body: web::Json<CredentialRequestBody>,
+    issuer: web::Data<CredentialIssuer>,
+    eligible_voters: web::Data<EligibleVoters>,
+) -> Result<web::Json<CredentialResponseBody>, ApiError> {
+    let blind_signature = issuer.issue(&vote, &body, &eligible_voters)?;
+    Ok(web::Json(CredentialResponseBody {
+        vote,
+        blind_signature: hex::encode(blind_signature.to_bytes_be()),
+    }))
+}
+
+/// Registers the credential issuance.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(issue_credential);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallet_mixnet::types::credential_message;
+    use sp_keyring::AccountKeyring;
+    use std::{collections::HashMap, env};
+
+    fn setup(name: &str) -> (CredentialIssuer, EligibleVoters, BlindPrivateKey) {
+        let dir = env::temp_dir().join(format!("provotum-credentials-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // two Mersenne primes: 2^61 - 1, 2^31 - 1
+        let p = BigUint::from(2_305_843_009_213_693_951u64);
+        let q = BigUint::from(2_147_483_647u64);
+        let key = BlindSignature::key_from_primes(&p, &q).unwrap();
+        fs::write(dir.join("vote.json"), serde_json::to_string(&key).unwrap()).unwrap();
+
+        let mut voters = HashMap::new();
+        let mut eligible = HashSet::new();
+        eligible.insert(AccountKeyring::Dave.to_account_id());
+        voters.insert(b"vote".to_vec(), eligible.clone());
+        voters.insert(b"other".to_vec(), eligible);
+
+        let issuer = CredentialIssuer::open(&dir, &dir.join("issued.log")).unwrap();
+        (issuer, EligibleVoters::new(voters), key)
+    }
+
+    fn request(vote: &str, voter: AccountKeyring, blinded: &BigUint) -> CredentialRequestBody {
+        let blinded = blinded.to_bytes_be();
+        let signature = voter.pair().sign(&credential_request_message(
+            &vote.as_bytes().to_vec(),
+            &blinded,
+        ));
+        CredentialRequestBody {
+            voter: voter.to_account_id().to_ss58check(),
+            blinded: hex::encode(blinded),
+            signature: hex::encode(signature.0),
+        }
+    }
+
+    #[test]
+    fn it_should_issue_a_valid_token_once() {
+        let (issuer, voters, key) = setup("once");
+        let one_time_account = AccountKeyring::Ferdie.to_account_id();
+        let message = credential_message(&b"vote".to_vec(), &one_time_account);
+        let r = BigUint::from(12_345u32);
+        let blinded = BlindSignature::blind(&key.public, &message, &r);
+
+        let body = request("vote", AccountKeyring::Dave, &blinded);
+        let blind_signature = issuer.issue("vote", &body, &voters).unwrap();
+        let token = BlindSignature::unblind(&key.public, &blind_signature, &r).unwrap();
+        assert!(BlindSignature::verify(&key.public, &message, &token));
+
+        let result = issuer.issue("vote", &body, &voters);
+        assert!(matches!(result, Err(ApiError::CredentialAlreadyIssued)));
+
+        // the issued credentials survive a restart
+        let issuer = CredentialIssuer::open(&issuer.dir, &issuer.dir.join("issued.log")).unwrap();
+        let result = issuer.issue("vote", &body, &voters);
+        assert!(matches!(result, Err(ApiError::CredentialAlreadyIssued)));
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_requests() {
+        let (issuer, voters, _) = setup("invalid");
+        let blinded = BigUint::from(42u32);
+
+        let body = request("vote", AccountKeyring::Eve, &blinded);
+        let result = issuer.issue("vote", &body, &voters);
+        assert!(matches!(result, Err(ApiError::NotEligible)));
+
+        let mut body = request("vote", AccountKeyring::Dave, &blinded);
+        body.blinded = hex::encode(BigUint::from(43u32).to_bytes_be());
+        let result = issuer.issue("vote", &body, &voters);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+
+        // the voter signed the request for another vote
+        let body = request("vote", AccountKeyring::Dave, &blinded);
+        let result = issuer.issue("other", &body, &voters);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+
+        // no key exists for the vote
+        let body = request("other", AccountKeyring::Dave, &blinded);
+        let result = issuer.issue("other", &body, &voters);
+        assert!(matches!(result, Err(ApiError::CredentialKeyDoesNotExist)));
+
+        // the key is larger than the blinded message
+        let body = request("vote", AccountKeyring::Dave, &(BigUint::from(1u32) << 100));
+        let result = issuer.issue("vote", &body, &voters);
+        assert!(matches!(result, Err(ApiError::InvalidCredentialRequest(_))));
+    }
+}
@@ -0,0 +1,130 @@
+use actix_web::{http::StatusCode, ResponseError};
+use provotum_sdk::keystore::KeystoreError;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// the node could not be reached, the storage query or the extrinsic failed
+    Chain(substrate_subxt::Error),
+
+    /// the signature headers are missing, malformed, expired, replayed or don't verify
+    Unauthenticated(&'static str),
+
+    /// the authenticated account is not allowed to act as the requested sealer
+    Forbidden,
+
+    /// the sealer is not part of the keystore
+    UnknownSealer(String),
+
+    /// the request body could not be parsed
+    InvalidBody(serde_json::Error),
+
+    /// the requested vote does not exist on chain
+    VoteDoesNotExist,
+
+    /// the requested topic does not exist for the vote
+    TopicDoesNotExist,
+
+    /// the topic has not been tallied yet
+    TallyDoesNotExist,
+
+    /// the events of a block could not be fetched or decoded
+    Events(String),
+
+    /// the requested replay exceeds the maximum # of blocks
+    ReplayTooLong(u32),
+
+    /// the voter is not eligible to vote
+    NotEligible,
+
+    /// the relayed ballot is malformed or its randomizer signatures don't verify
+    InvalidBallot(&'static str),
+
+    /// the vote is not in the voting phase
+    WrongVotePhase,
+
+    /// no relayer key is part of the keystore
+    RelayerUnavailable,
+
+    /// the blinded message of the credential request is malformed
+    InvalidCredentialRequest(&'static str),
+
+    /// no key to blind-sign voter tokens exists for the vote
+    CredentialKeyDoesNotExist,
+
+    /// the voter already received a credential for the vote
+    CredentialAlreadyIssued,
+
+    /// the issued credential could not be recorded
+    CredentialLogUnavailable,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Chain(err) => write!(f, "failed to query the chain: {}", err),
+            ApiError::Unauthenticated(reason) => write!(f, "unauthenticated: {}", reason),
+            ApiError::Forbidden => write!(f, "not authorized to act as the sealer!"),
+            ApiError::UnknownSealer(name) => write!(f, "unknown sealer: {}", name),
+            ApiError::InvalidBody(err) => write!(f, "invalid request body: {}", err),
+            ApiError::VoteDoesNotExist => write!(f, "vote does not exist!"),
+            ApiError::TopicDoesNotExist => write!(f, "topic does not exist!"),
+            ApiError::TallyDoesNotExist => write!(f, "topic has not been tallied yet!"),
+            ApiError::Events(reason) => write!(f, "failed to read the events: {}", reason),
+            ApiError::ReplayTooLong(max) => {
+                write!(f, "cannot replay more than {} blocks!", max)
+            }
+            ApiError::NotEligible => write!(f, "voter is not eligible!"),
+            ApiError::InvalidBallot(reason) => write!(f, "invalid ballot: {}", reason),
+            ApiError::WrongVotePhase => write!(f, "vote is not in the voting phase!"),
+            ApiError::RelayerUnavailable => write!(f, "ballots cannot be relayed!"),
+            ApiError::InvalidCredentialRequest(reason) => {
+                write!(f, "invalid credential request: {}", reason)
+            }
+            ApiError::CredentialKeyDoesNotExist => write!(f, "no credential key for the vote!"),
+            ApiError::CredentialAlreadyIssued => write!(f, "credential has already been issued!"),
+            ApiError::CredentialLogUnavailable => write!(f, "failed to record the credential!"),
+        }
+    }
+}
+
+impl From<substrate_subxt::Error> for ApiError {
+    fn from(err: substrate_subxt::Error) -> Self {
+        ApiError::Chain(err)
+    }
+}
+
+impl From<KeystoreError> for ApiError {
+    fn from(err: KeystoreError) -> Self {
+        match err {
+            KeystoreError::UnknownSealer(name) => ApiError::UnknownSealer(name),
+            // the keystore is decrypted at startup, only unknown sealers can occur
+            _ => ApiError::Forbidden,
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::Chain(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Unauthenticated(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden => StatusCode::FORBIDDEN,
+            ApiError::UnknownSealer(_) => StatusCode::NOT_FOUND,
+            ApiError::InvalidBody(_) => StatusCode::BAD_REQUEST,
+            ApiError::VoteDoesNotExist => StatusCode::NOT_FOUND,
+            ApiError::TopicDoesNotExist => StatusCode::NOT_FOUND,
+            ApiError::TallyDoesNotExist => StatusCode::NOT_FOUND,
+            ApiError::Events(_) => StatusCode::BAD_GATEWAY,
+            ApiError::ReplayTooLong(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotEligible => StatusCode::FORBIDDEN,
+            ApiError::InvalidBallot(_) => StatusCode::BAD_REQUEST,
+            ApiError::WrongVotePhase => StatusCode::CONFLICT,
+            ApiError::RelayerUnavailable => StatusCode::SERVICE_UNAVAILABLE,
+            ApiError::InvalidCredentialRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::CredentialKeyDoesNotExist => StatusCode::NOT_FOUND,
+            ApiError::CredentialAlreadyIssued => StatusCode::CONFLICT,
+            ApiError::CredentialLogUnavailable => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
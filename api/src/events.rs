@@ -0,0 +1,517 @@
+use crate::{error::ApiError, node::NodeClient, queries::tally_entries, NODE_URL};
+use actix_web::{get, rt, web, web::Bytes, HttpRequest, HttpResponse};
+use codec::Decode;
+use frame_metadata::RuntimeMetadataPrefixed;
+use futures::{
+    channel::mpsc::{channel, Receiver, Sender},
+    future,
+    lock::Mutex as AsyncMutex,
+    stream, StreamExt,
+};
+use jsonrpsee::common::Params;
+use pallet_mixnet::types::{TopicAnswer, TopicId, VoteId};
+use provotum_sdk::{
+    events::{self, decoder::EventDecoder, MixnetEvent},
+    rpc::{get_answers, get_topics, get_vote_ids},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+use substrate_subxt::{
+    sp_core::{crypto::Ss58Codec, twox_128, Bytes as RpcBytes},
+    Client, NodeTemplateRuntime,
+};
+
+/// the maximum number of blocks which are replayed for a single request
+const MAX_REPLAY_BLOCKS: u32 = 1_000;
+
+/// the number of events buffered per subscriber, lagging subscribers are disconnected
+const SUBSCRIBER_BUFFER: usize = 256;
+
+/// the time to wait before reconnecting to the node
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// An event of the mixnet pallet, re-published to the subscribers of the vote
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChainEvent {
+    pub block: u32,
+    // the position of the event in the block
+    pub index: u32,
+    pub vote: String,
+    pub event: String,
+    pub data: Value,
+}
+
+impl ChainEvent {
+    /// Formats the event as Server-Sent Event, the id is used to resume a stream.
+    pub fn to_sse(&self) -> String {
+        format!(
+            "id: {}-{}\nevent: {}\ndata: {}\n\n",
+            self.block,
+            self.index,
+            self.event,
+            serde_json::to_string(self).expect("events are serializable")
+        )
+    }
+}
+
+/// Some events only reference the topic, the vote is looked up.
+enum VoteRef {
+    Vote(VoteId),
+    Topic(TopicId),
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Converts the re-published events of the mixnet pallet, all other events are ignored.
+/// The results of a tallied topic are labelled with the topic's answers.
+fn mixnet_event(event: MixnetEvent, answers: &[TopicAnswer]) -> Option<(VoteRef, Value)> {
+    let event = match event {
+        MixnetEvent::BallotSubmitted(voter, vote_id, _ballot) => (
+            VoteRef::Vote(vote_id),
+            json!({ "voter": voter.to_ss58check() }),
+        ),
+        MixnetEvent::VotePhaseChanged(vote_id, phase) => (
+            VoteRef::Vote(vote_id),
+            json!({ "phase": format!("{:?}", phase) }),
+        ),
+        MixnetEvent::ShuffleProofSubmitted(topic_id, sealer)
+        | MixnetEvent::DecryptedShareSubmitted(topic_id, sealer) => {
+            let data = json!({ "topic": label(&topic_id), "sealer": sealer.to_ss58check() });
+            (VoteRef::Topic(topic_id), data)
+        }
+        MixnetEvent::TopicTallied(vote_id, topic_id, result) => {
+            let results = tally_entries(result, answers);
+            let data = json!({ "topic": label(&topic_id), "results": results });
+            (VoteRef::Vote(vote_id), data)
+        }
+        _ => return None,
+    };
+    Some(event)
+}
+
+/// Reads the events of finalized blocks from the node.
+pub struct EventSource {
+    client: Client<NodeTemplateRuntime>,
+    rpc: jsonrpsee::Client,
+    decoder: EventDecoder,
+    // topics never move between votes
+    topic_votes: HashMap<TopicId, VoteId>,
+}
+
+impl EventSource {
+    /// Uses the client to query blocks, the events are read with a separate rpc connection.
+    pub async fn connect(client: Client<NodeTemplateRuntime>) -> Result<Self, ApiError> {
+        let rpc = jsonrpsee::ws_client(NODE_URL)
+            .await
+            .map_err(|err| ApiError::Events(err.to_string()))?;
+
+        // the metadata describes the arguments of all events
+        let metadata: RpcBytes = rpc
+            .request("state_getMetadata", Params::None)
+            .await
+            .map_err(|err| ApiError::Events(err.to_string()))?;
+        let metadata = RuntimeMetadataPrefixed::decode(&mut &metadata.0[..])
+            .map_err(|err| ApiError::Events(err.to_string()))?;
+        let decoder =
+            EventDecoder::new(metadata).map_err(|err| ApiError::Events(err.to_string()))?;
+        Ok(EventSource {
+            client,
+            rpc,
+            decoder,
+            topic_votes: HashMap::new(),
+        })
+    }
+
+    pub async fn finalized_number(&self) -> Result<u32, ApiError> {
+        let hash = self.client.finalized_head().await?;
+        let header = self
+            .client
+            .header(Some(hash))
+            .await?
+            .ok_or_else(|| ApiError::Events("finalized header not found".to_string()))?;
+        Ok(header.number)
+    }
+
+    /// Returns all mixnet events of the block.
+    pub async fn block_events(&mut self, number: u32) -> Result<Vec<ChainEvent>, ApiError> {
+        let hash = self
+            .client
+            .block_hash(Some(number.into()))
+            .await?
+            .ok_or_else(|| ApiError::Events(format!("block {} not found", number)))?;
+
+        let mut key = twox_128(b"System").to_vec();
+        key.extend(twox_128(b"Events").iter());
+        let params = Params::Array(vec![json!(RpcBytes(key)), json!(hash)]);
+        let encoded: Option<RpcBytes> = self
+            .rpc
+            .request("state_getStorage", params)
+            .await
+            .map_err(|err| ApiError::Events(err.to_string()))?;
+        let encoded = match encoded {
+            Some(encoded) => encoded,
+            None => return Ok(Vec::new()),
+        };
+        let raw_events = self
+            .decoder
+            .decode_events(&mut &encoded.0[..])
+            .map_err(|err| ApiError::Events(err.to_string()))?;
+
+        let mut events = Vec::new();
+        for raw in raw_events.iter() {
+            let event = match events::decode(&raw.module, &raw.variant, &raw.data) {
+                Ok(Some(event)) => event,
+                Ok(None) => continue,
+                Err(err) => return Err(ApiError::Events(err.to_string())),
+            };
+            let answers = match &event {
+                MixnetEvent::TopicTallied(_, topic_id, _) => {
+                    get_answers(&self.client, topic_id.clone()).await?
+                }
+                _ => Vec::new(),
+            };
+            let (vote, data) = match mixnet_event(event, &answers) {
+                Some(event) => event,
+                None => continue,
+            };
+            let vote_id = match vote {
+                VoteRef::Vote(vote_id) => vote_id,
+                VoteRef::Topic(topic_id) => self.vote_of_topic(topic_id).await?,
+            };
+            events.push(ChainEvent {
+                block: number,
+                index: raw.index,
+                vote: label(&vote_id),
+                event: raw.variant.clone(),
+                data,
+            });
+        }
+        Ok(events)
+    }
+
+    async fn vote_of_topic(&mut self, topic_id: TopicId) -> Result<VoteId, ApiError> {
+        if !self.topic_votes.contains_key(&topic_id) {
+            for vote_id in get_vote_ids(&self.client).await? {
+                for (id, _) in get_topics(&self.client, vote_id.clone()).await? {
+                    self.topic_votes.insert(id, vote_id.clone());
+                }
+            }
+        }
+        self.topic_votes
+            .get(&topic_id)
+            .cloned()
+            .ok_or(ApiError::TopicDoesNotExist)
+    }
+}
+
+struct Subscriber {
+    // only events of this vote are sent, all if none
+    vote: Option<String>,
+    sender: Sender<ChainEvent>,
+}
+
+/// Distributes the events of finalized blocks to all subscribers.
+pub struct EventHub {
+    subscribers: Mutex<Vec<Subscriber>>,
+    // shared by all replays, replays are served one at a time
+    replay: AsyncMutex<Option<EventSource>>,
+}
+
+impl EventHub {
+    pub fn new() -> Self {
+        EventHub {
+            subscribers: Mutex::new(Vec::new()),
+            replay: AsyncMutex::new(None),
+        }
+    }
+
+    pub fn subscribe(&self, vote: Option<String>) -> Receiver<ChainEvent> {
+        let (sender, receiver) = channel(SUBSCRIBER_BUFFER);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.push(Subscriber { vote, sender });
+        receiver
+    }
+
+    /// Sends the event to all subscribers of the vote.
+    /// Closed streams and subscribers which don't keep up (full buffer) are removed.
+    pub fn publish(&self, event: &ChainEvent) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain_mut(|subscriber| match &subscriber.vote {
+            Some(vote) if *vote != event.vote => !subscriber.sender.is_closed(),
+            _ => subscriber.sender.try_send(event.clone()).is_ok(),
+        });
+    }
+
+    /// Returns the events of the blocks `from..=head` using the shared replay source.
+    /// The source is connected on first use and dropped on failure, the next replay reconnects.
+    async fn replay(
+        &self,
+        node: &NodeClient,
+        from: u32,
+    ) -> Result<(u32, Vec<ChainEvent>), ApiError> {
+        let mut replay = self.replay.lock().await;
+        if replay.is_none() {
+            *replay = Some(EventSource::connect(node.client().await?).await?);
+        }
+        let source = replay.as_mut().expect("replay source is connected");
+        let result = replay_blocks(source, from).await;
+        if result.is_err() {
+            *replay = None;
+        }
+        result
+    }
+}
+
+async fn replay_blocks(
+    source: &mut EventSource,
+    from: u32,
+) -> Result<(u32, Vec<ChainEvent>), ApiError> {
+    let head = source.finalized_number().await?;
+    if head.saturating_sub(from) > MAX_REPLAY_BLOCKS {
+        return Err(ApiError::ReplayTooLong(MAX_REPLAY_BLOCKS));
+    }
+    let mut events = Vec::new();
+    for number in from..=head {
+        events.extend(source.block_events(number).await?);
+    }
+    Ok((head, events))
+}
+
+/// Follows the finalized blocks and publishes their mixnet events, reconnects on failure.
+pub async fn publish_events(hub: web::Data<EventHub>, node: web::Data<NodeClient>) {
+    let mut last_block = None;
+    loop {
+        if let Err(err) = follow_finalized_blocks(&hub, &node, &mut last_block).await {
+            println!("event stream failed: {}, reconnecting...", err);
+        }
+        rt::time::delay_for(RECONNECT_DELAY).await;
+    }
+}
+
+async fn follow_finalized_blocks(
+    hub: &EventHub,
+    node: &NodeClient,
+    last_block: &mut Option<u32>,
+) -> Result<(), ApiError> {
+    let mut source = EventSource::connect(node.client().await?).await?;
+    let mut headers = source.client.subscribe_finalized_blocks().await?;
+    loop {
+        let header = headers.next().await;
+
+        // several blocks can be finalized at once
+        let from = last_block.map(|block| block + 1).unwrap_or(header.number);
+        for number in from..=header.number {
+            for event in source.block_events(number).await? {
+                hub.publish(&event);
+            }
+            *last_block = Some(number);
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EventsQuery {
+    // replay all events starting at this block
+    pub from: Option<u32>,
+}
+
+/// Parses the `Last-Event-ID` header (`<block>-<index>`) sent by reconnecting clients.
+fn last_event_id(req: &HttpRequest) -> Option<(u32, u32)> {
+    let id = req.headers().get("Last-Event-ID")?.to_str().ok()?;
+    let mut parts = id.splitn(2, '-');
+    let block = parts.next()?.parse().ok()?;
+    let index = parts.next()?.parse().ok()?;
+    Some((block, index))
+}
+
+async fn stream_events(
+    req: HttpRequest,
+    vote: Option<String>,
+    from: Option<u32>,
+    hub: web::Data<EventHub>,
+    node: web::Data<NodeClient>,
+) -> Result<HttpResponse, ApiError> {
+    // subscribe first, no event between the replay and the live events is lost
+    let live = hub.subscribe(vote.clone());
+
+    // resuming after the last received event takes precedence
+    let start = match last_event_id(&req) {
+        Some((block, index)) => Some((block, Some(index))),
+        None => from.map(|block| (block, None)),
+    };
+
+    let mut replayed = Vec::new();
+    let mut replayed_until = None;
+    if let Some((from_block, after_index)) = start {
+        let (head, events) = hub.replay(&node, from_block).await?;
+        for event in events {
+            let is_new = match after_index {
+                Some(index) => event.block > from_block || event.index > index,
+                None => true,
+            };
+            let is_selected = vote.as_ref().map(|vote| *vote == event.vote);
+            if is_new && is_selected.unwrap_or(true) {
+                replayed.push(event);
+            }
+        }
+        replayed_until = Some(head);
+    }
+
+    // the replayed blocks are skipped in the live stream
+    let live = live.filter(move |event| {
+        let replayed = matches!(replayed_until, Some(head) if event.block <= head);
+        future::ready(!replayed)
+    });
+    let events = stream::iter(replayed)
+        .chain(live)
+        .map(|event| Ok::<_, ApiError>(Bytes::from(event.to_sse())));
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .streaming(events))
+}
+
+#[get("/events")]
+async fn all_events(
+    req: HttpRequest,
+    query: web::Query<EventsQuery>,
+    hub: web::Data<EventHub>,
+    node: web::Data<NodeClient>,
+) -> Result<HttpResponse, ApiError> {
+    stream_events(req, None, query.from, hub, node).await
+}
+
+#[get("/votes/{vote}/events")]
+async fn vote_events(
+    req: HttpRequest,
+    web::Path(vote): web::Path<String>,
+    query: web::Query<EventsQuery>,
+    hub: web::Data<EventHub>,
+    node: web::Data<NodeClient>,
+) -> Result<HttpResponse, ApiError> {
+    stream_events(req, Some(vote), query.from, hub, node).await
+}
+
+/// Registers the Server-Sent Event streams.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(all_events).service(vote_events);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use codec::Encode;
+    use pallet_mixnet::types::{PublicKeyShare, TopicResult, VotePhase};
+    use sp_keyring::AccountKeyring;
+
+    fn decode(variant: &str, data: Vec<u8>) -> Option<MixnetEvent> {
+        events::decode(events::MODULE, variant, &data).unwrap()
+    }
+
+    fn chain_event(vote: &str, block: u32) -> ChainEvent {
+        ChainEvent {
+            block,
+            index: 0,
+            vote: vote.to_string(),
+            event: "VotePhaseChanged".to_string(),
+            data: json!({ "phase": "Voting" }),
+        }
+    }
+
+    #[test]
+    fn it_should_decode_mixnet_events() {
+        let data = (b"vote".to_vec(), VotePhase::Voting).encode();
+        let event = decode("VotePhaseChanged", data).unwrap();
+        let (vote, data) = mixnet_event(event, &[]).unwrap();
+        assert!(matches!(vote, VoteRef::Vote(vote_id) if vote_id == b"vote".to_vec()));
+        assert_eq!(data, json!({ "phase": "Voting" }));
+
+        let sealer = AccountKeyring::Bob.to_account_id();
+        let data = (b"topic".to_vec(), sealer.clone()).encode();
+        let event = decode("ShuffleProofSubmitted", data).unwrap();
+        let (vote, data) = mixnet_event(event, &[]).unwrap();
+        assert!(matches!(vote, VoteRef::Topic(topic_id) if topic_id == b"topic".to_vec()));
+        assert_eq!(
+            data,
+            json!({ "topic": "topic", "sealer": sealer.to_ss58check() })
+        );
+
+        let mut result = TopicResult::new();
+        result.insert(vec![1u8], vec![3u8]);
+        let data = (b"vote".to_vec(), b"topic".to_vec(), result).encode();
+        let event = decode("TopicTallied", data).unwrap();
+        let answers = vec![(1, b"yes".to_vec())];
+        let (vote, data) = mixnet_event(event, &answers).unwrap();
+        assert!(matches!(vote, VoteRef::Vote(vote_id) if vote_id == b"vote".to_vec()));
+        assert_eq!(
+            data,
+            json!({ "topic": "topic", "results": [{ "option": "1", "answer": "yes", "count": 3 }] })
+        );
+
+        let data = PublicKeyShare::default().encode();
+        let event = decode("PublicKeyShareSubmitted", data).unwrap();
+        assert!(mixnet_event(event, &[]).is_none());
+    }
+
+    #[test]
+    fn it_should_publish_per_vote() {
+        let hub = EventHub::new();
+        let mut vote_a = hub.subscribe(Some("a".to_string()));
+        let mut all = hub.subscribe(None);
+
+        hub.publish(&chain_event("a", 1));
+        hub.publish(&chain_event("b", 2));
+
+        assert_eq!(vote_a.try_next().unwrap(), Some(chain_event("a", 1)));
+        assert!(vote_a.try_next().is_err());
+        assert_eq!(all.try_next().unwrap(), Some(chain_event("a", 1)));
+        assert_eq!(all.try_next().unwrap(), Some(chain_event("b", 2)));
+
+        // closed streams are removed
+        drop(vote_a);
+        hub.publish(&chain_event("a", 3));
+        assert_eq!(hub.subscribers.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn it_should_disconnect_lagging_subscribers() {
+        let hub = EventHub::new();
+        let mut lagging = hub.subscribe(None);
+        let mut other_vote = hub.subscribe(Some("b".to_string()));
+
+        // the buffer of the subscriber holds at least SUBSCRIBER_BUFFER events
+        for block in 0..=SUBSCRIBER_BUFFER as u32 + 1 {
+            hub.publish(&chain_event("a", block));
+        }
+
+        // the lagging subscriber is removed, its stream ends after the buffered events
+        assert_eq!(hub.subscribers.lock().unwrap().len(), 1);
+        let buffered = std::iter::from_fn(|| lagging.try_next().unwrap()).count();
+        assert!(buffered > SUBSCRIBER_BUFFER && buffered <= SUBSCRIBER_BUFFER + 2);
+        assert!(other_vote.try_next().is_err());
+    }
+
+    #[test]
+    fn it_should_parse_the_last_event_id() {
+        let req = TestRequest::get()
+            .header("Last-Event-ID", "42-3")
+            .to_http_request();
+        assert_eq!(last_event_id(&req), Some((42, 3)));
+
+        let req = TestRequest::get()
+            .header("Last-Event-ID", "invalid")
+            .to_http_request();
+        assert_eq!(last_event_id(&req), None);
+    }
+
+    #[test]
+    fn it_should_format_server_sent_events() {
+        let sse = chain_event("a", 7).to_sse();
+        assert!(sse.starts_with("id: 7-0\nevent: VotePhaseChanged\ndata: {"));
+        assert!(sse.ends_with("}\n\n"));
+    }
+}
@@ -1,11 +1,25 @@
-use actix_web::{web,get,  post, App, HttpResponse, HttpServer, Responder, Result};
-use substrate_subxt::{Client, PairSigner};
-use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime};
-use hex_literal::hex;
+use actix_web::{web,get,  post, App, HttpRequest, HttpResponse, HttpServer, Responder, Result};
+use substrate_subxt::PairSigner;
+use substrate_subxt::NodeTemplateRuntime;
+use clap::Clap;
 use pallet_mixnet::types::{Cipher, PublicKeyShare, Wrapper};
-use sp_keyring::{sr25519::sr25519::Pair, AccountKeyring};
-mod substrate;
-use substrate::rpc::{get_ciphers, store_public_key_share, submit_partial_decryptions};
+use sp_keyring::sr25519::sr25519::Pair;
+mod auth;
+mod credentials;
+mod error;
+mod events;
+mod node;
+mod queries;
+mod relay;
+use auth::{authorize, SealerAuth};
+use credentials::CredentialIssuer;
+use error::ApiError;
+use events::EventHub;
+use relay::{EligibleVoters, Relayer};
+use provotum_sdk::keystore::{Keystore, KeystoreEntry, DEFAULT_LOG_N};
+use node::NodeClient;
+use std::{collections::HashMap, env, path::PathBuf};
+use provotum_sdk::rpc::{get_ciphers, store_public_key_share, submit_partial_decryptions};
 use crypto::{
     proofs::decryption::{DecryptPostBody, HexDecryptionProof, DecryptionProof},
     types::Cipher as BigCipher,
@@ -13,102 +27,194 @@ use crypto::{
 use num_bigint::BigUint;
 use num_traits::Num;
 
-fn get_sealer(sealer: String) -> (Pair, [u8; 32]) {
-    // get the sealer and sealer_id
-    if sealer == "bob" {
-        return (
-            AccountKeyring::Bob.pair(),
-            hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48").into(),
-        );
-    } else {
-        return (
-            AccountKeyring::Charlie.pair(),
-            hex!("90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22").into(),
-        );
-    };
+/// The api service, signs sealer transactions with keys from an encrypted keystore
+#[derive(Clap, Debug)]
+#[clap(name = "provotum-api")]
+struct Opts {
+    #[clap(subcommand)]
+    subcmd: Option<SubCommand>,
 }
 
-async fn init() -> Result<Client<NodeTemplateRuntime>, Error> {
-    let url = "ws://127.0.0.1:9944";
-    let client = ClientBuilder::<NodeTemplateRuntime>::new()
-        .set_url(url)
-        .build()
-        .await?;
-    Ok(client)
+#[derive(Clap, Debug)]
+enum SubCommand {
+    #[clap(name = "add_sealer")]
+    AddSealer(AddSealer),
+}
+
+/// Encrypts a sealer's secret (read from stdin without echo) and adds it to the keystore
+#[derive(Clap, Debug)]
+struct AddSealer {
+    /// The name of the sealer
+    #[clap(short, long)]
+    name: String,
+}
+
+fn keystore_dir() -> PathBuf {
+    PathBuf::from(env::var("KEYSTORE_DIR").unwrap_or_else(|_| "keystore".to_string()))
+}
+
+fn keystore_password() -> std::io::Result<String> {
+    env::var("KEYSTORE_PASSWORD").map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "the env variable KEYSTORE_PASSWORD is required",
+        )
+    })
 }
 
+fn add_sealer(name: String) -> std::io::Result<()> {
+    let password = keystore_password()?;
+    // the secret is never echoed to the terminal
+    let prompt = format!("enter the secret (mnemonic or secret uri) of sealer: {}: ", name);
+    let secret = rpassword::prompt_password_stdout(&prompt)?;
+
+    let entry = KeystoreEntry::encrypt(&name, secret.trim(), &password, DEFAULT_LOG_N)
+        .and_then(|entry| entry.write(&keystore_dir()).map(|_| entry))
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    println!("added sealer: {} ({})", entry.name, entry.public);
+    Ok(())
+}
+
+/// the websocket url of the substrate node
+const NODE_URL: &str = "ws://127.0.0.1:9944";
+
 #[post("/keygen/{vote}/{sealer}")] // <- define path parameters
-async fn keygen(web::Path((vote, sealer)): web::Path<(String, String)>, pk_share: web::Json<PublicKeyShare>) -> impl Responder {
-    let client = init().await.unwrap();
-    let (sealer, _sealer_id): (Pair, [u8; 32]) = get_sealer(sealer.to_string()); 
-
-     let vote_id = vote.as_bytes().to_vec();
- 
-     // submit the public key share + proof
-     let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
-     let store_public_key_share_response =
-         store_public_key_share(&client, &signer, vote_id, pk_share.into_inner()).await.unwrap();
-     println!(
-         "store_public_key_share_response: {:?}",
-         store_public_key_share_response.events[0].variant
-     );
- 
-     HttpResponse::Ok().body("Successfully Stored Key Share!")
+async fn keygen(req: HttpRequest, web::Path((vote, sealer)): web::Path<(String, String)>, body: web::Bytes, keystore: web::Data<Keystore>, auth: web::Data<SealerAuth>, node: web::Data<NodeClient>) -> Result<HttpResponse, ApiError> {
+    node.run(|client| async move {
+        // only the sealer itself can submit its key share
+        let sealer = authorize(&req, &body, &sealer, &keystore, &auth, &client).await?;
+        let pk_share: PublicKeyShare = serde_json::from_slice(&body).map_err(ApiError::InvalidBody)?;
+
+        let vote_id = vote.as_bytes().to_vec();
+
+        // submit the public key share + proof
+        let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
+        let store_public_key_share_response =
+            store_public_key_share(&client, &signer, vote_id, pk_share).await?;
+        println!(
+            "store_public_key_share_response: {:?}",
+            store_public_key_share_response.events[0].variant
+        );
+
+        Ok(HttpResponse::Ok().body("Successfully Stored Key Share!"))
+    })
+    .await
 }
 
 
 #[get("/decrypt/{vote}/{question}")]
-async fn get_decrypt(web::Path((_vote, question)): web::Path<(String, String)>) -> Result<impl Responder> {
-    let client = init().await.unwrap();
-    let topic_id = question.as_bytes().to_vec();
-    let nr_of_shuffles = 3;
-    let raw_encryptions: Vec<Cipher> = get_ciphers(&client, topic_id.clone(), nr_of_shuffles).await.unwrap();
-    let encryptions: Vec<BigCipher> = Wrapper(raw_encryptions).into();
-
-    Ok(web::Json(encryptions))
+async fn get_decrypt(web::Path((_vote, question)): web::Path<(String, String)>, node: web::Data<NodeClient>) -> Result<impl Responder, ApiError> {
+    node.run(|client| async move {
+        let topic_id = question.as_bytes().to_vec();
+        let nr_of_shuffles = 3;
+        let raw_encryptions: Vec<Cipher> = get_ciphers(&client, topic_id.clone(), nr_of_shuffles).await?;
+        let encryptions: Vec<BigCipher> = Wrapper(raw_encryptions).into();
+
+        Ok(web::Json(encryptions))
+    })
+    .await
 }
 
 
 
 #[post("/decrypt/{vote}/{question}/{sealer}")] 
-async fn post_decrypt(web::Path((vote, question, sealer)): web::Path<(String, String, String)>, decrypt_post_body: web::Json<DecryptPostBody>) -> impl Responder {
-    // submit the partial decryption + proof
-    let client = init().await.unwrap();
-
-    let (sealer, _sealer_id): (Pair, [u8; 32]) = get_sealer(sealer);
-    let vote_id = vote.as_bytes().to_vec();
-    let topic_id = question.as_bytes().to_vec();
-    let nr_of_shuffles = 3;
-    let raw_decryption_proof: HexDecryptionProof = decrypt_post_body.decryption_proof.clone().into();
-    
-    let decryption_proof = DecryptionProof {
-        challenge: BigUint::from_str_radix(&raw_decryption_proof.challenge, 16).unwrap(), 
-        response: BigUint::from_str_radix(&raw_decryption_proof.response, 16).unwrap(), 
-    };
-
-    let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
-    let response = submit_partial_decryptions(
-        &client,
-        &signer,
-        vote_id,
-        topic_id,
-        decrypt_post_body.shares.clone(),
-        decryption_proof.into(),
-        nr_of_shuffles,
-    )
-    .await.unwrap();
-    println!("response: {:?}", response.events[0].variant);
-
-    HttpResponse::Ok().body("Successfully Submitted Partial Decryptions!")
+async fn post_decrypt(req: HttpRequest, web::Path((vote, question, sealer)): web::Path<(String, String, String)>, body: web::Bytes, keystore: web::Data<Keystore>, auth: web::Data<SealerAuth>, node: web::Data<NodeClient>) -> Result<HttpResponse, ApiError> {
+    node.run(|client| async move {
+        // only the sealer itself can submit its partial decryptions
+        let sealer = authorize(&req, &body, &sealer, &keystore, &auth, &client).await?;
+        let decrypt_post_body: DecryptPostBody = serde_json::from_slice(&body).map_err(ApiError::InvalidBody)?;
+
+        // submit the partial decryption + proof
+        let vote_id = vote.as_bytes().to_vec();
+        let topic_id = question.as_bytes().to_vec();
+        let nr_of_shuffles = 3;
+        let raw_decryption_proof: HexDecryptionProof = decrypt_post_body.decryption_proof.clone();
+
+        let decryption_proof = DecryptionProof {
+            challenge: parse_hex(&raw_decryption_proof.challenge)?,
+            response: parse_hex(&raw_decryption_proof.response)?,
+        };
+
+        let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
+        let response = submit_partial_decryptions(
+            &client,
+            &signer,
+            vote_id,
+            topic_id,
+            decrypt_post_body.shares.clone(),
+            decryption_proof.into(),
+            nr_of_shuffles,
+        )
+        .await?;
+        println!("response: {:?}", response.events[0].variant);
+
+        Ok(HttpResponse::Ok().body("Successfully Submitted Partial Decryptions!"))
+    })
+    .await
+}
+
+/// Parses a hex encoded number of the request body, malformed numbers are refused.
+fn parse_hex(value: &str) -> Result<BigUint, ApiError> {
+    BigUint::from_str_radix(value, 16)
+        .map_err(|err| ApiError::InvalidBody(serde::de::Error::custom(err)))
 }
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    HttpServer::new(|| {
+    let opts: Opts = Opts::parse();
+    if let Some(SubCommand::AddSealer(t)) = opts.subcmd {
+        return add_sealer(t.name);
+    }
+
+    // load the keys of all sealers, unknown sealers are refused
+    let password = keystore_password()?;
+    let keystore = Keystore::load(&keystore_dir(), &password)
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    println!("loaded sealers: {:?}", keystore.sealers());
+    let keystore = web::Data::new(keystore);
+    let auth = web::Data::new(SealerAuth::new());
+    let node = web::Data::new(NodeClient::new(NODE_URL));
+
+    // the relayer submits the ballots of eligible voters, its key is part of the keystore
+    let relayer_name = env::var("RELAYER").unwrap_or_else(|_| "relayer".to_string());
+    let relayer = keystore.get_sealer(&relayer_name).ok();
+    if relayer.is_none() {
+        println!("no relayer: {} in the keystore, ballots cannot be relayed!", relayer_name);
+    }
+    let relayer = web::Data::new(Relayer::new(relayer));
+    let path = env::var("ELIGIBLE_VOTERS").unwrap_or_else(|_| "eligible_voters.json".to_string());
+    let eligible_voters = EligibleVoters::from_file(&path).unwrap_or_else(|err| {
+        println!("failed to load eligible voters: {}, no voter is eligible!", err);
+        EligibleVoters::new(HashMap::new())
+    });
+    let eligible_voters = web::Data::new(eligible_voters);
+
+    // the voting authority's keys to blind-sign the voter tokens per vote
+    let dir = env::var("CREDENTIAL_KEYS_DIR").unwrap_or_else(|_| "credential_keys".to_string());
+    let log = env::var("ISSUED_CREDENTIALS").unwrap_or_else(|_| "issued_credentials.log".to_string());
+    let issuer = web::Data::new(CredentialIssuer::open(&PathBuf::from(dir), &PathBuf::from(log))?);
+
+    // re-publish the mixnet events of all finalized blocks
+    let hub = web::Data::new(EventHub::new());
+    actix_web::rt::spawn(events::publish_events(hub.clone(), node.clone()));
+
+    HttpServer::new(move || {
         App::new()
+        .app_data(keystore.clone())
+        .app_data(auth.clone())
+        .app_data(node.clone())
+        .app_data(hub.clone())
+        .app_data(relayer.clone())
+        .app_data(eligible_voters.clone())
+        .app_data(issuer.clone())
+        .app_data(web::PayloadConfig::new(1024 * 1024 * 50))
         .service(keygen)
         .service(get_decrypt)
         .service(post_decrypt)
+        .configure(queries::configure)
+        .configure(events::configure)
+        .configure(relay::configure)
+        .configure(credentials::configure)
         .data(web::JsonConfig::default().limit(1024 * 1024 * 50))
     })
     .bind(("0.0.0.0", 10008))?
@@ -0,0 +1,50 @@
+use crate::error::ApiError;
+use std::{future::Future, sync::Mutex};
+use substrate_subxt::{Client, ClientBuilder, NodeTemplateRuntime};
+
+/// The connection to the node shared by all requests, established on first use.
+pub struct NodeClient {
+    url: String,
+    client: Mutex<Option<Client<NodeTemplateRuntime>>>,
+}
+
+impl NodeClient {
+    pub fn new(url: &str) -> Self {
+        NodeClient {
+            url: url.to_string(),
+            client: Mutex::new(None),
+        }
+    }
+
+    /// Returns the shared client, connects to the node on first use.
+    pub async fn client(&self) -> Result<Client<NodeTemplateRuntime>, ApiError> {
+        if let Some(client) = self
+            .client
+            .lock()
+            .expect("client lock is poisoned!")
+            .as_ref()
+        {
+            return Ok(client.clone());
+        }
+        let client = ClientBuilder::<NodeTemplateRuntime>::new()
+            .set_url(&self.url)
+            .build()
+            .await?;
+        *self.client.lock().expect("client lock is poisoned!") = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Runs the request with the shared client.
+    /// If the node cannot be reached, the connection is dropped and the next request reconnects.
+    pub async fn run<T, F, Fut>(&self, request: F) -> Result<T, ApiError>
+    where
+        F: FnOnce(Client<NodeTemplateRuntime>) -> Fut,
+        Fut: Future<Output = Result<T, ApiError>>,
+    {
+        let result = request(self.client().await?).await;
+        if let Err(ApiError::Chain(substrate_subxt::Error::Rpc(_))) = result {
+            *self.client.lock().expect("client lock is poisoned!") = None;
+        }
+        result
+    }
+}
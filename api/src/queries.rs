@@ -0,0 +1,320 @@
+use crate::{error::ApiError, node::NodeClient};
+use actix_web::{get, web};
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use pallet_mixnet::types::{ShuffleState, Topic, TopicAnswer, TopicResult, Vote, VotePhase};
+use provotum_sdk::rpc::{
+    count_ballots, get_answers, get_shuffle_state, get_tally, get_topics, get_vote, get_vote_ids,
+};
+use serde::Serialize;
+use substrate_subxt::{sp_core::crypto::Ss58Codec, system::System, Client, NodeTemplateRuntime};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoteSummary {
+    pub id: String,
+    pub title: String,
+    pub phase: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TopicResponse {
+    pub id: String,
+    pub question: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoteResponse {
+    pub id: String,
+    pub title: String,
+    pub phase: String,
+    pub voting_authority: String,
+    pub topics: Vec<TopicResponse>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ShuffleProgressResponse {
+    pub topic: String,
+    // the # of completed shuffles
+    pub iteration: u8,
+    // the position of the next batch of ciphers to shuffle
+    pub start_position: u64,
+    pub batch_size: u64,
+    pub done: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct BallotCountResponse {
+    pub vote: String,
+    pub ballots: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TallyEntry {
+    // the voting option (decimal)
+    pub option: String,
+    // the label of the option stored on chain, if any
+    pub answer: Option<String>,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TallyResponse {
+    pub topic: String,
+    pub question: String,
+    pub results: Vec<TallyEntry>,
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn phase_label(phase: &VotePhase) -> String {
+    format!("{:?}", phase)
+}
+
+fn topic_response(topic: &Topic) -> TopicResponse {
+    TopicResponse {
+        id: label(&topic.0),
+        question: label(&topic.1),
+    }
+}
+
+fn vote_response(vote_id: &[u8], vote: Vote<AccountId>, topics: Vec<Topic>) -> VoteResponse {
+    VoteResponse {
+        id: label(vote_id),
+        title: label(&vote.title),
+        phase: phase_label(&vote.phase),
+        voting_authority: vote.voting_authority.to_ss58check(),
+        topics: topics.iter().map(topic_response).collect(),
+    }
+}
+
+fn shuffle_progress(topic: &[u8], state: ShuffleState) -> ShuffleProgressResponse {
+    ShuffleProgressResponse {
+        topic: label(topic),
+        iteration: state.iteration,
+        start_position: state.start_position,
+        batch_size: state.batch_size,
+        done: state.done,
+    }
+}
+
+/// Converts the big-endian encoded options and counts into decimals,
+/// each option is labelled with the matching answer stored on chain.
+pub(crate) fn tally_entries(result: TopicResult, answers: &[TopicAnswer]) -> Vec<TallyEntry> {
+    result
+        .iter()
+        .map(|(option, count)| {
+            let option = BigUint::from_bytes_be(option);
+            let answer = answers
+                .iter()
+                .find(|(value, _)| BigUint::from(*value) == option)
+                .map(|(_, answer)| label(answer));
+            TallyEntry {
+                option: option.to_str_radix(10),
+                answer,
+                count: BigUint::from_bytes_be(count).to_u64().unwrap_or(u64::MAX),
+            }
+        })
+        .collect()
+}
+
+/// Returns the topic of the vote or an error if it doesn't exist.
+async fn find_topic(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: &[u8],
+    topic_id: &[u8],
+) -> Result<Topic, ApiError> {
+    if get_vote(client, vote_id.to_vec()).await?.is_none() {
+        return Err(ApiError::VoteDoesNotExist);
+    }
+    let topics = get_topics(client, vote_id.to_vec()).await?;
+    topics
+        .into_iter()
+        .find(|topic| topic.0 == topic_id)
+        .ok_or(ApiError::TopicDoesNotExist)
+}
+
+#[get("/votes")]
+async fn list_votes(node: web::Data<NodeClient>) -> Result<web::Json<Vec<VoteSummary>>, ApiError> {
+    node.run(|client| async move {
+        let mut votes = Vec::new();
+        for vote_id in get_vote_ids(&client).await? {
+            if let Some(vote) = get_vote(&client, vote_id.clone()).await? {
+                votes.push(VoteSummary {
+                    id: label(&vote_id),
+                    title: label(&vote.title),
+                    phase: phase_label(&vote.phase),
+                });
+            }
+        }
+        Ok(web::Json(votes))
+    })
+    .await
+}
+
+#[get("/votes/{vote}")]
+async fn get_vote_details(
+    web::Path(vote): web::Path<String>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<VoteResponse>, ApiError> {
+    node.run(|client| async move {
+        let vote_id = vote.as_bytes().to_vec();
+        let details = get_vote(&client, vote_id.clone())
+            .await?
+            .ok_or(ApiError::VoteDoesNotExist)?;
+        let topics = get_topics(&client, vote_id.clone()).await?;
+        Ok(web::Json(vote_response(&vote_id, details, topics)))
+    })
+    .await
+}
+
+#[get("/votes/{vote}/topics")]
+async fn list_topics(
+    web::Path(vote): web::Path<String>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<Vec<TopicResponse>>, ApiError> {
+    node.run(|client| async move {
+        let vote_id = vote.as_bytes().to_vec();
+        if get_vote(&client, vote_id.clone()).await?.is_none() {
+            return Err(ApiError::VoteDoesNotExist);
+        }
+        let topics = get_topics(&client, vote_id).await?;
+        Ok(web::Json(topics.iter().map(topic_response).collect()))
+    })
+    .await
+}
+
+#[get("/votes/{vote}/ballots/count")]
+async fn get_ballot_count(
+    web::Path(vote): web::Path<String>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<BallotCountResponse>, ApiError> {
+    node.run(|client| async move {
+        let vote_id = vote.as_bytes().to_vec();
+        if get_vote(&client, vote_id.clone()).await?.is_none() {
+            return Err(ApiError::VoteDoesNotExist);
+        }
+        let ballots = count_ballots(&client, vote_id).await?;
+        Ok(web::Json(BallotCountResponse { vote, ballots }))
+    })
+    .await
+}
+
+#[get("/votes/{vote}/topics/{topic}/shuffle")]
+async fn get_shuffle_progress(
+    web::Path((vote, topic)): web::Path<(String, String)>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<ShuffleProgressResponse>, ApiError> {
+    node.run(|client| async move {
+        let (vote_id, topic_id) = (vote.as_bytes(), topic.as_bytes());
+        find_topic(&client, vote_id, topic_id).await?;
+
+        // no state exists before the first shuffle
+        let state = get_shuffle_state(&client, vote_id.to_vec(), topic_id.to_vec())
+            .await?
+            .unwrap_or_default();
+        Ok(web::Json(shuffle_progress(topic_id, state)))
+    })
+    .await
+}
+
+#[get("/votes/{vote}/topics/{topic}/tally")]
+async fn get_topic_tally(
+    web::Path((vote, topic)): web::Path<(String, String)>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<TallyResponse>, ApiError> {
+    node.run(|client| async move {
+        let (vote_id, topic_id) = (vote.as_bytes(), topic.as_bytes());
+        let topic = find_topic(&client, vote_id, topic_id).await?;
+
+        let result = get_tally(&client, topic_id.to_vec())
+            .await?
+            .ok_or(ApiError::TallyDoesNotExist)?;
+        let answers = get_answers(&client, topic_id.to_vec()).await?;
+        Ok(web::Json(TallyResponse {
+            topic: label(&topic.0),
+            question: label(&topic.1),
+            results: tally_entries(result, &answers),
+        }))
+    })
+    .await
+}
+
+/// Registers all read-only election query endpoints.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(list_votes)
+        .service(get_vote_details)
+        .service(list_topics)
+        .service(get_ballot_count)
+        .service(get_shuffle_progress)
+        .service(get_topic_tally);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_keyring::AccountKeyring;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn it_should_label_the_tally() {
+        let mut result: TopicResult = BTreeMap::new();
+        result.insert(
+            BigUint::from(1u32).to_bytes_be(),
+            BigUint::from(300u32).to_bytes_be(),
+        );
+        result.insert(
+            BigUint::from(0u32).to_bytes_be(),
+            BigUint::from(2u32).to_bytes_be(),
+        );
+
+        // only the answers stored on chain are labelled
+        let answers = vec![(1, b"yes".to_vec())];
+        let entries = tally_entries(result, &answers);
+        assert_eq!(
+            entries,
+            vec![
+                TallyEntry {
+                    option: "0".to_string(),
+                    answer: None,
+                    count: 2
+                },
+                TallyEntry {
+                    option: "1".to_string(),
+                    answer: Some("yes".to_string()),
+                    count: 300
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn it_should_label_the_vote() {
+        let vote = Vote {
+            voting_authority: AccountKeyring::Alice.to_account_id(),
+            title: b"Title".to_vec(),
+            phase: VotePhase::Voting,
+            params: Default::default(),
+        };
+        let topics = vec![(b"topic".to_vec(), b"Question?".to_vec())];
+
+        let response = vote_response(b"vote", vote, topics);
+        assert_eq!(response.id, "vote");
+        assert_eq!(response.title, "Title");
+        assert_eq!(response.phase, "Voting");
+        assert_eq!(
+            response.voting_authority,
+            AccountKeyring::Alice.to_account_id().to_ss58check()
+        );
+        assert_eq!(
+            response.topics,
+            vec![TopicResponse {
+                id: "topic".to_string(),
+                question: "Question?".to_string(),
+            }]
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use crate::{error::ApiError, node::NodeClient};
+use actix_web::{post, web};
+use crypto::{
+    helper::Helper,
+    types::{Cipher as BigCipher, ElGamalParams},
+};
+use pallet_mixnet::types::{
+    randomizer_message, relay_message, Ballot, Cipher, RandomizerKey, RandomizerSignature, Topic,
+    VoteId, VotePhase, VoterCredential,
+};
+use provotum_sdk::rpc::{
+    get_ballot, get_randomizer_keys, get_topics, get_vote, is_randomizer_signature_required,
+    relay_ballot,
+};
+use serde::{Deserialize, Serialize};
+use sp_keyring::sr25519::sr25519::Pair;
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+};
+use substrate_subxt::{
+    sp_core::{crypto::Ss58Codec, sr25519, Pair as KeyPairGenerator},
+    system::System,
+    NodeTemplateRuntime, PairSigner,
+};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RelayAnswer {
+    pub topic: String,
+    pub cipher: BigCipher,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RelayRequestBody {
+    pub answers: Vec<RelayAnswer>,
+    // the randomizer's signature per answer, empty if the vote doesn't require them
+    #[serde(default)]
+    pub signatures: Vec<RandomizerSignature>,
+    pub credential: VoterCredential,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RelayResponseBody {
+    pub vote: String,
+    pub voter: String,
+    // the hash of the block which includes the ballot
+    pub block: String,
+}
+
+/// The voters allowed to have their ballots relayed per vote.
+pub struct EligibleVoters {
+    voters: HashMap<VoteId, HashSet<AccountId>>,
+}
+
+impl EligibleVoters {
+    pub fn new(voters: HashMap<VoteId, HashSet<AccountId>>) -> Self {
+        EligibleVoters { voters }
+    }
+
+    /// Loads the eligible voters from a JSON file: `{ "<vote_id>": ["<ss58 account>", ...] }`
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let voters: HashMap<String, HashSet<AccountId>> = serde_json::from_str(&content)?;
+        let voters = voters
+            .into_iter()
+            .map(|(vote_id, voters)| (vote_id.as_bytes().to_vec(), voters))
+            .collect();
+        Ok(EligibleVoters::new(voters))
+    }
+
+    pub fn is_eligible(&self, vote_id: &VoteId, voter: &AccountId) -> bool {
+        self.voters
+            .get(vote_id)
+            .map(|voters| voters.contains(voter))
+            .unwrap_or(false)
+    }
+}
+
+/// The key used to submit relayed ballots, must be registered as relayer on chain.
+pub struct Relayer {
+    pair: Option<Pair>,
+}
+
+impl Relayer {
+    pub fn new(pair: Option<Pair>) -> Self {
+        Relayer { pair }
+    }
+}
+
+/// Validates the ballot before it is submitted, returns the voter's account and the ballot.
+///
+/// The same checks are performed by the pallet, invalid ballots are refused without a transaction.
+fn validate_ballot(
+    vote_id: &VoteId,
+    body: RelayRequestBody,
+    eligible_voters: &EligibleVoters,
+    params: &ElGamalParams,
+    topics: &[Topic],
+    randomizer_keys: Option<&[RandomizerKey]>,
+) -> Result<(AccountId, Ballot), ApiError> {
+    let credential = body.credential;
+    let voter = AccountId::from(credential.voter.0);
+    if !eligible_voters.is_eligible(vote_id, &voter) {
+        return Err(ApiError::NotEligible);
+    }
+
+    // each topic of the vote can be answered once
+    if body.answers.is_empty() {
+        return Err(ApiError::InvalidBallot("the ballot contains no answers"));
+    }
+    let q = params.q();
+    let mut answers = Vec::with_capacity(body.answers.len());
+    for answer in body.answers {
+        let topic_id = answer.topic.as_bytes().to_vec();
+        if !topics.iter().any(|topic| topic.0 == topic_id) {
+            return Err(ApiError::TopicDoesNotExist);
+        }
+        if answers.iter().any(|(id, _)| *id == topic_id) {
+            return Err(ApiError::InvalidBallot("a topic is answered twice"));
+        }
+        if !Helper::is_valid_cipher(&params.p, &q, &answer.cipher) {
+            return Err(ApiError::InvalidBallot("a cipher is not in the group"));
+        }
+        let cipher: Cipher = answer.cipher.into();
+        answers.push((topic_id, cipher));
+    }
+    let ballot = Ballot { answers };
+
+    // the voter must have signed the ballot
+    let message = relay_message(vote_id, &ballot);
+    if !sr25519::Pair::verify(&credential.signature, &message, &credential.voter) {
+        return Err(ApiError::Unauthenticated("invalid voter credential"));
+    }
+
+    // each answer must have been re-encrypted by a registered randomizer for the voter
+    if let Some(keys) = randomizer_keys {
+        if body.signatures.len() != ballot.answers.len() {
+            return Err(ApiError::InvalidBallot("randomizer signature missing"));
+        }
+        for ((_, cipher), signature) in ballot.answers.iter().zip(body.signatures.iter()) {
+            let message = randomizer_message(vote_id, &voter, cipher);
+            if !keys.contains(&signature.signer)
+                || !sr25519::Pair::verify(&signature.signature, &message, &signature.signer)
+            {
+                return Err(ApiError::InvalidBallot("invalid randomizer signature"));
+            }
+        }
+    }
+    Ok((voter, ballot))
+}
+
+/// Casts a ballot on behalf of a voter without a chain account.
+/// The ballot is validated and submitted as transaction signed by the relayer.
+#[post("/relay/{vote}")]
+async fn relay(
+    web::Path(vote): web::Path<String>,
+    body: web::Json<RelayRequestBody>,
+    eligible_voters: web::Data<EligibleVoters>,
+    relayer: web::Data<Relayer>,
+    node: web::Data<NodeClient>,
+) -> Result<web::Json<RelayResponseBody>, ApiError> {
+    let pair = relayer.pair.clone().ok_or(ApiError::RelayerUnavailable)?;
+    node.run(|client| async move {
+        let vote_id = vote.as_bytes().to_vec();
+        let details = get_vote(&client, vote_id.clone())
+            .await?
+            .ok_or(ApiError::VoteDoesNotExist)?;
+        if details.phase != VotePhase::Voting {
+            return Err(ApiError::WrongVotePhase);
+        }
+        let params: ElGamalParams = details.params.into();
+        let topics = get_topics(&client, vote_id.clone()).await?;
+        let required = is_randomizer_signature_required(&client, vote_id.clone()).await?;
+        let randomizer_keys = match required {
+            true => Some(get_randomizer_keys(&client).await?),
+            false => None,
+        };
+
+        let signatures = body.signatures.clone();
+        let credential = body.credential.clone();
+        let (voter, ballot) = validate_ballot(
+            &vote_id,
+            body.into_inner(),
+            &eligible_voters,
+            &params,
+            &topics,
+            randomizer_keys.as_deref(),
+        )?;
+
+        // each voter's ballot can only be relayed once
+        if get_ballot(&client, vote_id.clone(), voter.clone())
+            .await?
+            .is_some()
+        {
+            return Err(ApiError::InvalidBallot(
+                "the ballot has already been relayed",
+            ));
+        }
+
+        let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(pair);
+        let response =
+            relay_ballot(&client, &signer, vote_id, ballot, signatures, credential).await?;
+        println!("relayed ballot of voter: {}", voter.to_ss58check());
+
+        Ok(web::Json(RelayResponseBody {
+            vote,
+            voter: voter.to_ss58check(),
+            block: format!("{:?}", response.block),
+        }))
+    })
+    .await
+}
+
+/// Registers the ballot relay.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(relay);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+    use num_traits::Zero;
+    use sp_keyring::AccountKeyring;
+
+    const VOTE: &[u8] = b"vote";
+
+    fn params() -> ElGamalParams {
+        let (params, _, _) = Helper::setup_tiny_system();
+        params
+    }
+
+    fn setup() -> (EligibleVoters, Vec<Topic>) {
+        let mut voters = HashMap::new();
+        let mut eligible = HashSet::new();
+        eligible.insert(AccountKeyring::Dave.to_account_id());
+        voters.insert(VOTE.to_vec(), eligible);
+        let topics = vec![(b"topic".to_vec(), b"Question?".to_vec())];
+        (EligibleVoters::new(voters), topics)
+    }
+
+    fn cipher() -> BigCipher {
+        // both components are elements of the subgroup of order q = 23
+        BigCipher {
+            a: BigUint::from(4u32),
+            b: BigUint::from(9u32),
+        }
+    }
+
+    fn request(voter: AccountKeyring, topics: &[&str]) -> RelayRequestBody {
+        let answers: Vec<RelayAnswer> = topics
+            .iter()
+            .map(|topic| RelayAnswer {
+                topic: topic.to_string(),
+                cipher: cipher(),
+            })
+            .collect();
+        let ballot = Ballot {
+            answers: answers
+                .iter()
+                .map(|answer| {
+                    (
+                        answer.topic.as_bytes().to_vec(),
+                        answer.cipher.clone().into(),
+                    )
+                })
+                .collect(),
+        };
+        let pair = voter.pair();
+        RelayRequestBody {
+            answers,
+            signatures: Vec::new(),
+            credential: VoterCredential {
+                voter: pair.public(),
+                signature: pair.sign(&relay_message(&VOTE.to_vec(), &ballot)),
+            },
+        }
+    }
+
+    fn sign(randomizer: &Pair, voter: AccountKeyring) -> RandomizerSignature {
+        let cipher: Cipher = cipher().into();
+        let message = randomizer_message(&VOTE.to_vec(), &voter.to_account_id(), &cipher);
+        RandomizerSignature {
+            signer: randomizer.public(),
+            signature: randomizer.sign(&message),
+        }
+    }
+
+    #[test]
+    fn it_should_validate_a_signed_ballot() {
+        let (voters, topics) = setup();
+        let body = request(AccountKeyring::Dave, &["topic"]);
+        let (voter, ballot) =
+            validate_ballot(&VOTE.to_vec(), body, &voters, &params(), &topics, None).unwrap();
+        assert_eq!(voter, AccountKeyring::Dave.to_account_id());
+        assert_eq!(ballot.answers, vec![(b"topic".to_vec(), cipher().into())]);
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_ballots() {
+        let (voters, topics) = setup();
+        let vote_id = VOTE.to_vec();
+
+        let body = request(AccountKeyring::Eve, &["topic"]);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::NotEligible)));
+
+        let body = request(AccountKeyring::Dave, &["unknown"]);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::TopicDoesNotExist)));
+
+        let body = request(AccountKeyring::Dave, &["topic", "topic"]);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::InvalidBallot(_))));
+
+        // the ciphers must be elements of the group
+        let mut body = request(AccountKeyring::Dave, &["topic"]);
+        body.answers[0].cipher.b = BigUint::from(5u32);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::InvalidBallot(_))));
+
+        let mut body = request(AccountKeyring::Dave, &["topic"]);
+        body.answers[0].cipher.a = BigUint::zero();
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::InvalidBallot(_))));
+
+        // the credential doesn't cover a modified ballot
+        let mut body = request(AccountKeyring::Dave, &["topic"]);
+        body.answers[0].cipher.b = BigUint::from(16u32);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, None);
+        assert!(matches!(result, Err(ApiError::Unauthenticated(_))));
+    }
+
+    #[test]
+    fn it_should_check_the_randomizer_signatures() {
+        let (voters, topics) = setup();
+        let vote_id = VOTE.to_vec();
+        let randomizer = AccountKeyring::Ferdie.pair();
+        let keys = vec![randomizer.public()];
+
+        let body = request(AccountKeyring::Dave, &["topic"]);
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, Some(&keys));
+        assert!(matches!(result, Err(ApiError::InvalidBallot(_))));
+
+        // the signature must be issued for the voter
+        let mut body = request(AccountKeyring::Dave, &["topic"]);
+        body.signatures = vec![sign(&randomizer, AccountKeyring::Eve)];
+        let result = validate_ballot(&vote_id, body, &voters, &params(), &topics, Some(&keys));
+        assert!(matches!(result, Err(ApiError::InvalidBallot(_))));
+
+        let mut body = request(AccountKeyring::Dave, &["topic"]);
+        body.signatures = vec![sign(&randomizer, AccountKeyring::Dave)];
+        assert!(validate_ballot(&vote_id, body, &voters, &params(), &topics, Some(&keys)).is_ok());
+    }
+}
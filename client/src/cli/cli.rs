@@ -1,3 +1,4 @@
+use crate::output::Output;
 use clap::Clap;
 
 /// The Provotum CLI to impersonate voters, the voting-authority and sealers
@@ -8,6 +9,27 @@ use clap::Clap;
     author = "Moritz Eck <moritz.eck@gmail.com>"
 )]
 pub struct Opts {
+    /// The config file containing the profiles [default: provotum.toml]
+    #[clap(long, global = true, env = "PROVOTUM_CONFIG")]
+    pub config: Option<String>,
+    /// The profile of the config file to use [default: local]
+    #[clap(long, global = true, env = "PROVOTUM_PROFILE")]
+    pub profile: Option<String>,
+    /// The websocket url of the node
+    #[clap(long, global = true, env = "PROVOTUM_NODE_URL")]
+    pub node_url: Option<String>,
+    /// The url of the randomizer
+    #[clap(long, global = true, env = "PROVOTUM_RANDOMIZER_URL")]
+    pub randomizer_url: Option<String>,
+    /// The url of the api issuing the voter credentials
+    #[clap(long, global = true, env = "PROVOTUM_API_URL")]
+    pub api_url: Option<String>,
+    /// The secret uri or mnemonic signing the voting authority's transactions
+    #[clap(long, global = true, env = "PROVOTUM_IDENTITY", hide_env_values = true)]
+    pub identity: Option<String>,
+    /// The format of the results [default: table, json for verify]
+    #[clap(long, global = true, env = "PROVOTUM_OUTPUT", possible_values = &["table", "json", "csv"])]
+    pub output: Option<Output>,
     #[clap(subcommand)]
     pub subcmd: SubCommand,
 }
@@ -20,11 +42,130 @@ pub enum SubCommand {
     VotingAuthority(VotingAuthority),
     #[clap(name = "sealer")]
     Sealer(Sealer),
+    #[clap(name = "watch")]
+    Watch(Watch),
+    #[clap(name = "verify")]
+    Verify(Verify),
+    #[clap(name = "export")]
+    Export(Export),
+    #[clap(name = "broadcast")]
+    Broadcast(Broadcast),
+}
+
+/// A subcommand to follow the progress of a vote live
+#[derive(Clap, Debug)]
+pub struct Watch {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+}
+
+/// A subcommand to re-verify the shuffles, the decryptions and the tally of a vote
+#[derive(Clap, Debug)]
+pub struct Verify {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+}
+
+/// A subcommand to export the public parameters of a vote to sign its extrinsics offline
+#[derive(Clap, Debug)]
+pub struct Export {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The account (ss58) whose nonce is exported, can be repeated
+    #[clap(
+        short,
+        long = "account",
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    pub accounts: Vec<String>,
+    /// The export file to create
+    #[clap(short, long)]
+    pub file: String,
+}
+
+/// A subcommand to submit an extrinsic signed offline
+#[derive(Clap, Debug)]
+pub struct Broadcast {
+    /// The file containing the signed extrinsic
+    #[clap(short, long)]
+    pub file: String,
 }
 
 /// A subcommand for controlling the Voter
 #[derive(Clap, Debug)]
 pub struct Voter {
+    /// The voter subcommands
+    #[clap(subcommand)]
+    pub subcmd: VoterSubCommand,
+}
+
+#[derive(Clap, Debug)]
+pub enum VoterSubCommand {
+    #[clap(name = "create_votes")]
+    CreateVotes(CreateVotes),
+    #[clap(name = "cast")]
+    Cast(Cast),
+    #[clap(name = "keystore")]
+    Keystore(Keystore),
+    #[clap(name = "load")]
+    Load(Load),
+    #[clap(name = "sign")]
+    Sign(SignBallot),
+}
+
+/// A subcommand to encrypt and sign a ballot offline using an exported vote
+#[derive(Clap, Debug)]
+pub struct SignBallot {
+    /// The export file of the vote
+    #[clap(short, long)]
+    pub export: String,
+    /// The answer to a question: <question>=<label>, can be repeated
+    #[clap(
+        short,
+        long = "answer",
+        required = true,
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    pub answers: Vec<String>,
+    /// The keystore file of the voter, the mnemonic is read from the env or stdin if omitted
+    #[clap(short, long)]
+    pub keystore: Option<String>,
+    /// The nonce of the voter's account, required if it is not exported
+    #[clap(short, long)]
+    pub nonce: Option<u32>,
+    /// The file the signed extrinsic is written to
+    #[clap(short, long)]
+    pub file: String,
+}
+
+/// A subcommand to cast the ballots of many development voters (//0, //1, ...) concurrently
+#[derive(Clap, Debug)]
+pub struct Load {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The number of voters
+    #[clap(long)]
+    pub voters: usize,
+    /// The maximum number of ballots cast at the same time
+    #[clap(long, default_value = "10")]
+    pub concurrency: usize,
+    /// The index of the first voter, the voters //<offset>, //<offset + 1>, ... are used
+    #[clap(long, default_value = "0")]
+    pub offset: usize,
+    /// The number of retries of each step of casting a ballot
+    #[clap(long, default_value = "3")]
+    pub retries: u32,
+}
+
+/// A subcommand to cast random ballots of development voters (//0, //1, ...)
+#[derive(Clap, Debug)]
+pub struct CreateVotes {
     /// The id of the vote
     #[clap(short, long)]
     pub vote: String,
@@ -39,6 +180,41 @@ pub struct Voter {
     pub votes: Vec<u32>,
 }
 
+/// A subcommand to cast a ballot answering one or more questions
+#[derive(Clap, Debug)]
+pub struct Cast {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The answer to a question: <question>=<label>, can be repeated
+    #[clap(
+        short,
+        long = "answer",
+        required = true,
+        multiple_occurrences = true,
+        number_of_values = 1
+    )]
+    pub answers: Vec<String>,
+    /// The keystore file of the voter, the mnemonic is read from the env or stdin if omitted
+    #[clap(short, long)]
+    pub keystore: Option<String>,
+    /// Cast an anonymous ballot without randomizing it: the randomizer cannot link the one-time
+    /// account to the voter, but the voter can prove the vote (only votes with a credential key)
+    #[clap(long)]
+    pub skip_randomizer: bool,
+}
+
+/// A subcommand to encrypt the voter's mnemonic into a keystore file
+#[derive(Clap, Debug)]
+pub struct Keystore {
+    /// The name of the voter
+    #[clap(short, long)]
+    pub name: String,
+    /// The keystore file to create
+    #[clap(short, long)]
+    pub file: String,
+}
+
 /// A subcommand for controlling the Voting Authority
 #[derive(Clap, Debug)]
 pub struct VotingAuthority {
@@ -49,10 +225,14 @@ pub struct VotingAuthority {
 
 #[derive(Clap, Debug)]
 pub enum VASubCommand {
+    #[clap(name = "create")]
+    CreateElection(CreateElection),
     #[clap(name = "setup")]
     SetupVote(SetupVote),
     #[clap(name = "store_question")]
     StoreQuestion(StoreQuestion),
+    #[clap(name = "store_answers")]
+    StoreAnswers(StoreAnswers),
     #[clap(name = "set_phase")]
     SetVotePhase(SetVotePhase),
     #[clap(name = "combine_pk_shares")]
@@ -61,6 +241,25 @@ pub enum VASubCommand {
     TallyQuestion(TallyQuestion),
     #[clap(name = "result")]
     GetResult(GetResult),
+    #[clap(name = "store_randomizer_key")]
+    StoreRandomizerKey(StoreRandomizerKey),
+    #[clap(name = "require_randomizer_signature")]
+    RequireRandomizerSignature(RequireRandomizerSignature),
+    #[clap(name = "store_relayer")]
+    StoreRelayer(StoreRelayer),
+    #[clap(name = "create_credential_key")]
+    CreateCredentialKey(CreateCredentialKey),
+}
+
+/// A subcommand for setting up a vote using an election file, it can be repeated safely
+#[derive(Clap, Debug)]
+pub struct CreateElection {
+    /// The election file, see election.example.toml
+    #[clap(short, long)]
+    pub file: String,
+    /// The eligible voters file of the randomizer and the api, the roll is added to it
+    #[clap(short, long, default_value = "eligible_voters.json")]
+    pub eligible_voters: String,
 }
 
 /// A subcommand for setting up the vote
@@ -85,6 +284,20 @@ pub struct StoreQuestion {
     pub question: String,
 }
 
+/// A subcommand to store the allowed answers of a question
+#[derive(Clap, Debug)]
+pub struct StoreAnswers {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The id of the question
+    #[clap(short, long)]
+    pub question: String,
+    /// The labels of the answers, encrypted as 0, 1, ... in the given order
+    #[clap(short, long, required = true, min_values = 1)]
+    pub answers: Vec<String>,
+}
+
 /// A subcommand for changing the vote phase
 #[derive(Clap, Debug)]
 pub struct SetVotePhase {
@@ -113,16 +326,63 @@ pub struct TallyQuestion {
     /// The id of the question
     #[clap(short, long)]
     pub question: String,
+    /// Whether the answers are encoded (g^m), required for ballots cast using `voter cast`
+    #[clap(short, long)]
+    pub encoded: bool,
 }
 
 /// A subcommand to fetch result for a question
 #[derive(Clap, Debug)]
 pub struct GetResult {
+    /// The id of the vote, all votes are searched for the question if not given
+    #[clap(short, long)]
+    pub vote: Option<String>,
     /// The id of the question
     #[clap(short, long)]
     pub question: String,
 }
 
+/// A subcommand to register the key of a randomizer
+#[derive(Clap, Debug)]
+pub struct StoreRandomizerKey {
+    /// The randomizer's public key (ss58)
+    #[clap(short, long)]
+    pub key: String,
+}
+
+/// A subcommand to require a randomizer signature on all ballots of a vote
+#[derive(Clap, Debug)]
+pub struct RequireRandomizerSignature {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// Whether a randomizer signature is required
+    #[clap(short, long, possible_values = &["true", "false"])]
+    pub required: String,
+}
+
+/// A subcommand to register an account which relays the ballots of voters
+#[derive(Clap, Debug)]
+pub struct StoreRelayer {
+    /// The relayer's account (ss58)
+    #[clap(short, long)]
+    pub account: String,
+}
+
+/// A subcommand to create the key which blind-signs the voter tokens of a vote
+#[derive(Clap, Debug)]
+pub struct CreateCredentialKey {
+    /// The id of the vote
+    #[clap(short, long)]
+    pub vote: String,
+    /// The bit size of the RSA modulus
+    #[clap(short, long, default_value = "2048")]
+    pub bits: u64,
+    /// The directory the private key is written to, read by the api
+    #[clap(short, long, default_value = "credential_keys")]
+    pub dir: String,
+}
+
 /// A subcommand for controlling the Sealer
 #[derive(Clap, Debug)]
 pub struct Sealer {
@@ -137,6 +397,36 @@ pub enum SealerSubCommand {
     KeyGeneration(KeyGeneration),
     #[clap(name = "decrypt")]
     PartialDecryption(PartialDecryption),
+    #[clap(name = "sign")]
+    Sign(SignDecryption),
+    #[clap(name = "daemon")]
+    Daemon(Daemon),
+    #[clap(name = "add")]
+    Add(AddSealer),
+}
+
+/// A subcommand to add a sealer's account to the keystore: <keystore>/<who>.json
+///
+/// The secret uri (or mnemonic) of the account is read from PROVOTUM_SEALER_SECRET or stdin.
+#[derive(Clap, Debug)]
+pub struct AddSealer {
+    /// The name of the sealer
+    #[clap(short, long, required = true)]
+    pub who: String,
+    /// The directory containing the sealers' keystores
+    #[clap(short, long, default_value = "sealer_keys")]
+    pub keystore: String,
+}
+
+/// A subcommand to submit the key shares and partial decryptions of all votes automatically
+#[derive(Clap, Debug)]
+pub struct Daemon {
+    /// The name of the sealer to use
+    #[clap(short, long, required = true)]
+    pub who: String,
+    /// The directory containing the sealers' keystores and progress: <keystore>/<who>.progress.json
+    #[clap(short, long, default_value = "sealer_keys")]
+    pub keystore: String,
 }
 
 /// A subcommand for controlling the key generation
@@ -145,12 +435,12 @@ pub struct KeyGeneration {
     /// The id of the vote
     #[clap(short, long)]
     pub vote: String,
-    /// The private key as string
-    #[clap(short, long)]
-    pub sk: String,
     /// The name of the sealer to use
-    #[clap(short, long, required = true, possible_values = &["bob", "charlie"])]
+    #[clap(short, long, required = true)]
     pub who: String,
+    /// The directory containing the sealers' keystores: <keystore>/<who>/<vote>.json
+    #[clap(short, long, default_value = "sealer_keys")]
+    pub keystore: String,
 }
 
 /// A subcommand for controlling the partial decryption
@@ -162,10 +452,33 @@ pub struct PartialDecryption {
     /// The id of the question
     #[clap(short, long)]
     pub question: String,
-    /// The private key as string
+    /// The name of the sealer to use
+    #[clap(short, long, required = true)]
+    pub who: String,
+    /// The directory containing the sealers' keystores: <keystore>/<who>/<vote>.json
+    #[clap(short, long, default_value = "sealer_keys")]
+    pub keystore: String,
+}
+
+/// A subcommand to partially decrypt a question and sign the submission offline
+#[derive(Clap, Debug)]
+pub struct SignDecryption {
+    /// The export file of the vote
+    #[clap(short, long)]
+    pub export: String,
+    /// The id of the question
     #[clap(short, long)]
-    pub sk: String,
+    pub question: String,
     /// The name of the sealer to use
-    #[clap(short, long, required = true, possible_values = &["bob", "charlie"])]
+    #[clap(short, long, required = true)]
     pub who: String,
+    /// The directory containing the sealers' keystores: <keystore>/<who>/<vote>.json
+    #[clap(short, long, default_value = "sealer_keys")]
+    pub keystore: String,
+    /// The nonce of the sealer's account, required if it is not exported
+    #[clap(short, long)]
+    pub nonce: Option<u32>,
+    /// The file the signed extrinsic is written to
+    #[clap(short, long)]
+    pub file: String,
 }
@@ -0,0 +1,257 @@
+use serde::Deserialize;
+use sp_keyring::sr25519::sr25519::Pair;
+use std::{collections::HashMap, fmt, fs, path::Path};
+use substrate_subxt::{sp_core::Pair as KeyPairGenerator, Client, ClientBuilder};
+use substrate_subxt::{Error, NodeTemplateRuntime, PairSigner};
+
+/// the profile used if neither the cli nor the config file select one
+pub const DEFAULT_PROFILE: &str = "local";
+
+/// the config file read from the working directory if no other file is specified
+pub const DEFAULT_CONFIG_FILE: &str = "provotum.toml";
+
+pub const DEFAULT_NODE_URL: &str = "ws://127.0.0.1:9944";
+pub const DEFAULT_RANDOMIZER_URL: &str = "http://0.0.0.0:8080";
+pub const DEFAULT_API_URL: &str = "http://0.0.0.0:10008";
+
+/// the identity of the voting authority on a development chain
+pub const DEFAULT_IDENTITY: &str = "//Alice";
+
+/// The settings of a single deployment, unset values fall back to the defaults.
+#[derive(Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub node_url: Option<String>,
+    pub randomizer_url: Option<String>,
+    // the api issuing the voter credentials
+    pub api_url: Option<String>,
+    // the secret uri or mnemonic used to sign the voting authority's transactions
+    pub identity: Option<String>,
+}
+
+/// The content of the config file:
+///
+/// ```toml
+/// default_profile = "staging"
+///
+/// [profiles.staging]
+/// node_url = "wss://staging.example.org:9944"
+/// randomizer_url = "https://randomizer.staging.example.org"
+/// ```
+#[derive(Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+}
+
+impl ConfigFile {
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        toml::from_str(content).map_err(|err| Error::Other(format!("invalid config file: {}", err)))
+    }
+
+    /// Loads the config file, the default file is optional while a specified file must exist.
+    pub fn load(path: Option<&str>) -> Result<Self, Error> {
+        let path = match path {
+            Some(path) => path,
+            None if Path::new(DEFAULT_CONFIG_FILE).exists() => DEFAULT_CONFIG_FILE,
+            None => return Ok(ConfigFile::default()),
+        };
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::Other(format!("failed to read config file {}: {}", path, err)))?;
+        Self::parse(&content)
+    }
+}
+
+/// The resolved configuration shared by all commands.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Config {
+    pub profile: String,
+    pub node_url: String,
+    pub randomizer_url: String,
+    pub api_url: String,
+    pub identity: Option<String>,
+}
+
+impl Config {
+    /// Resolves the configuration in the order: cli options / env variables, profile, defaults.
+    ///
+    /// The profile `local` can be used without being part of the config file.
+    /// The defaults only apply to the profile `local`, the urls of other profiles must be set.
+    pub fn resolve(
+        file: &ConfigFile,
+        profile: Option<String>,
+        overrides: Profile,
+    ) -> Result<Self, Error> {
+        let name = profile
+            .or_else(|| file.default_profile.clone())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+        let selected = match file.profiles.get(&name) {
+            Some(selected) => selected.clone(),
+            None if name == DEFAULT_PROFILE => Profile::default(),
+            None => return Err(Error::Other(format!("unknown profile: {}", name))),
+        };
+
+        // the development defaults must not be used against a real deployment
+        let local = name == DEFAULT_PROFILE;
+        let setting = |value: Option<String>, default: &str, key: &str| match value {
+            Some(value) => Ok(value),
+            None if local => Ok(default.to_string()),
+            None => Err(Error::Other(format!(
+                "{} is not set for profile: {}",
+                key, name
+            ))),
+        };
+
+        Ok(Config {
+            node_url: setting(
+                overrides.node_url.or(selected.node_url),
+                DEFAULT_NODE_URL,
+                "node_url",
+            )?,
+            randomizer_url: setting(
+                overrides.randomizer_url.or(selected.randomizer_url),
+                DEFAULT_RANDOMIZER_URL,
+                "randomizer_url",
+            )?,
+            api_url: setting(
+                overrides.api_url.or(selected.api_url),
+                DEFAULT_API_URL,
+                "api_url",
+            )?,
+            // only required by the commands signing the voting authority's transactions
+            identity: overrides
+                .identity
+                .or(selected.identity)
+                .or_else(|| local.then(|| DEFAULT_IDENTITY.to_string())),
+            profile: name,
+        })
+    }
+
+    /// Connects to the node of the profile.
+    pub async fn client(&self) -> Result<Client<NodeTemplateRuntime>, Error> {
+        let client = ClientBuilder::<NodeTemplateRuntime>::new()
+            .set_url(self.node_url.clone())
+            .build()
+            .await?;
+        Ok(client)
+    }
+
+    /// Creates the signer of the identity.
+    pub fn signer(&self) -> Result<PairSigner<NodeTemplateRuntime, Pair>, Error> {
+        let identity = self.identity.as_ref().ok_or_else(|| {
+            Error::Other(format!("identity is not set for profile: {}", self.profile))
+        })?;
+        // the error would contain the secret
+        let pair: Pair = KeyPairGenerator::from_string(identity, None)
+            .map_err(|_| Error::Other("invalid identity".to_string()))?;
+        Ok(PairSigner::new(pair))
+    }
+
+    /// Returns the url of an endpoint of the randomizer.
+    pub fn randomizer(&self, path: &str) -> String {
+        format!("{}{}", self.randomizer_url.trim_end_matches('/'), path)
+    }
+
+    /// Returns the url of an endpoint of the api.
+    pub fn api(&self, path: &str) -> String {
+        format!("{}{}", self.api_url.trim_end_matches('/'), path)
+    }
+}
+
+// the identity is a secret and must not end up in the output
+impl fmt::Debug for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("profile", &self.profile)
+            .field("node_url", &self.node_url)
+            .field("randomizer_url", &self.randomizer_url)
+            .field("api_url", &self.api_url)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        default_profile = "staging"
+
+        [profiles.staging]
+        node_url = "wss://staging.example.org:9944"
+        randomizer_url = "https://randomizer.staging.example.org/"
+        api_url = "https://api.staging.example.org"
+
+        [profiles.production]
+        node_url = "wss://example.org:9944"
+        randomizer_url = "https://randomizer.example.org"
+        api_url = "https://api.example.org"
+        identity = "//Production"
+
+        [profiles.incomplete]
+        node_url = "wss://incomplete.example.org:9944"
+        identity = "//Incomplete"
+    "#;
+
+    #[test]
+    fn it_should_resolve_the_default_profile() {
+        let config = Config::resolve(&ConfigFile::default(), None, Profile::default()).unwrap();
+        assert_eq!(config.profile, "local");
+        assert_eq!(config.node_url, DEFAULT_NODE_URL);
+        assert_eq!(config.randomizer_url, DEFAULT_RANDOMIZER_URL);
+        assert_eq!(config.api_url, DEFAULT_API_URL);
+        assert_eq!(config.identity.as_deref(), Some(DEFAULT_IDENTITY));
+
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let config = Config::resolve(&file, None, Profile::default()).unwrap();
+        assert_eq!(config.profile, "staging");
+        assert_eq!(config.node_url, "wss://staging.example.org:9944");
+        assert_eq!(
+            config.randomizer("/randomize"),
+            "https://randomizer.staging.example.org/randomize"
+        );
+        assert_eq!(config.identity, None);
+        assert!(config.signer().is_err());
+    }
+
+    #[test]
+    fn it_should_prefer_the_overrides() {
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let overrides = Profile {
+            node_url: Some("ws://10.0.0.1:9944".to_string()),
+            ..Profile::default()
+        };
+        let config = Config::resolve(&file, Some("production".to_string()), overrides).unwrap();
+        assert_eq!(config.profile, "production");
+        assert_eq!(config.node_url, "ws://10.0.0.1:9944");
+        assert_eq!(config.randomizer_url, "https://randomizer.example.org");
+        assert_eq!(config.identity.as_deref(), Some("//Production"));
+        assert!(!format!("{:?}", config).contains("Production"));
+    }
+
+    #[test]
+    fn it_should_not_apply_the_defaults_to_other_profiles() {
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let result = Config::resolve(&file, Some("incomplete".to_string()), Profile::default());
+        assert!(result.is_err());
+
+        let overrides = Profile {
+            randomizer_url: Some("https://randomizer.example.org".to_string()),
+            api_url: Some("https://api.example.org".to_string()),
+            ..Profile::default()
+        };
+        let config = Config::resolve(&file, Some("incomplete".to_string()), overrides).unwrap();
+        assert_eq!(config.randomizer_url, "https://randomizer.example.org");
+    }
+
+    #[test]
+    fn it_should_refuse_unknown_profiles_and_fields() {
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let result = Config::resolve(&file, Some("unknown".to_string()), Profile::default());
+        assert!(result.is_err());
+
+        assert!(ConfigFile::parse("[profiles.local]\nnode = \"ws://\"").is_err());
+    }
+}
@@ -0,0 +1,356 @@
+use chrono::{DateTime, Utc};
+use crypto::{groups::NamedGroup, helper::Helper};
+use pallet_mixnet::types::{PublicParameters, Topic, TopicAnswer, TopicId, VoteId};
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::{fs, path::Path};
+use substrate_subxt::{sp_core::crypto::Ss58Codec, system::System};
+use substrate_subxt::{Error, NodeTemplateRuntime};
+use toml::value::Datetime;
+
+/// the batch size of the shuffles if the election file does not specify one
+pub const DEFAULT_BATCH_SIZE: u64 = 75;
+
+/// The parameter sets of the crypto crate accepted by the runtime (`MinModulusBits = 2048`).
+/// The safe prime test of a custom modulus larger than 2048bit exceeds the maximum extrinsic weight.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ParameterSet {
+    // 2048bit
+    Lg,
+    // the named groups of RFC 3526 and RFC 7919
+    Modp2048,
+    Modp3072,
+    Ffdhe2048,
+    Ffdhe3072,
+}
+
+impl ParameterSet {
+    /// The public parameters, the named groups carry their name.
+    pub fn params(self) -> PublicParameters {
+        match self {
+            ParameterSet::Lg => Helper::setup_lg_system().0.into(),
+            ParameterSet::Modp2048 => NamedGroup::Modp2048.into(),
+            ParameterSet::Modp3072 => NamedGroup::Modp3072.into(),
+            ParameterSet::Ffdhe2048 => NamedGroup::Ffdhe2048.into(),
+            ParameterSet::Ffdhe3072 => NamedGroup::Ffdhe3072.into(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ScheduleFile {
+    voting_start: Datetime,
+    voting_end: Datetime,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct QuestionFile {
+    question: String,
+    answers: Vec<String>,
+}
+
+/// The content of the election file, see `election.example.toml`.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ElectionFile {
+    // the id of the vote, defaults to the title
+    vote: Option<String>,
+    title: String,
+    params: ParameterSet,
+    batch_size: Option<u64>,
+    schedule: ScheduleFile,
+    questions: Vec<QuestionFile>,
+    roll: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    pub voting_start: DateTime<Utc>,
+    pub voting_end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Question {
+    pub question: String,
+    // the answers are encrypted as 0, 1, ... in the given order
+    pub answers: Vec<String>,
+}
+
+impl Question {
+    pub fn topic_id(&self) -> TopicId {
+        self.question.as_bytes().to_vec()
+    }
+
+    pub fn topic(&self) -> Topic {
+        (self.topic_id(), self.question.as_bytes().to_vec())
+    }
+
+    pub fn topic_answers(&self) -> Vec<TopicAnswer> {
+        self.answers
+            .iter()
+            .enumerate()
+            .map(|(value, label)| (value as u32, label.as_bytes().to_vec()))
+            .collect()
+    }
+}
+
+/// A validated election definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Election {
+    pub vote: String,
+    pub title: String,
+    pub params: ParameterSet,
+    pub batch_size: u64,
+    pub schedule: Schedule,
+    pub questions: Vec<Question>,
+    // the eligible voters (ss58)
+    pub roll: BTreeSet<String>,
+}
+
+impl Election {
+    pub fn parse(content: &str) -> Result<Self, Error> {
+        let file: ElectionFile = toml::from_str(content)
+            .map_err(|err| Error::Other(format!("invalid election file: {}", err)))?;
+        let invalid = |reason: &str| Error::Other(format!("invalid election file: {}", reason));
+
+        let title = file.title.trim().to_string();
+        if title.is_empty() {
+            return Err(invalid("the title must not be empty"));
+        }
+        let vote = file.vote.unwrap_or_else(|| title.clone());
+        if vote.is_empty() {
+            return Err(invalid("the vote must not be empty"));
+        }
+        let batch_size = file.batch_size.unwrap_or(DEFAULT_BATCH_SIZE);
+        if batch_size == 0 {
+            return Err(invalid("the batch_size must be greater than zero"));
+        }
+
+        let schedule = Schedule {
+            voting_start: parse_datetime(&file.schedule.voting_start)?,
+            voting_end: parse_datetime(&file.schedule.voting_end)?,
+        };
+        if schedule.voting_start >= schedule.voting_end {
+            return Err(invalid("the voting must start before it ends"));
+        }
+
+        if file.questions.is_empty() {
+            return Err(invalid("at least one question is required"));
+        }
+        let mut questions = Vec::new();
+        let mut seen_questions = HashSet::new();
+        for question in file.questions {
+            if question.question.is_empty() || !seen_questions.insert(question.question.clone()) {
+                return Err(invalid("the questions must be unique and not empty"));
+            }
+            let labels: HashSet<&String> = question.answers.iter().collect();
+            if question.answers.len() < 2
+                || labels.len() != question.answers.len()
+                || question.answers.iter().any(|label| label.is_empty())
+            {
+                return Err(Error::Other(format!(
+                    "invalid election file: the question {:?} requires at least two unique answers",
+                    question.question
+                )));
+            }
+            questions.push(Question {
+                question: question.question,
+                answers: question.answers,
+            });
+        }
+
+        if file.roll.is_empty() {
+            return Err(invalid("the roll must contain at least one voter"));
+        }
+        let mut roll = BTreeSet::new();
+        for voter in file.roll {
+            let account = <NodeTemplateRuntime as System>::AccountId::from_ss58check(&voter)
+                .map_err(|_| Error::Other(format!("invalid voter in the roll: {}", voter)))?;
+            if !roll.insert(account.to_ss58check()) {
+                return Err(Error::Other(format!(
+                    "duplicate voter in the roll: {}",
+                    voter
+                )));
+            }
+        }
+
+        Ok(Election {
+            vote,
+            title,
+            params: file.params,
+            batch_size,
+            schedule,
+            questions,
+            roll,
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::Other(format!("failed to read {}: {}", path, err)))?;
+        Self::parse(&content)
+    }
+
+    pub fn vote_id(&self) -> VoteId {
+        self.vote.as_bytes().to_vec()
+    }
+
+    /// Adds the roll to the eligible voters file: `{ "<vote_id>": ["<ss58 account>", ...] }`
+    ///
+    /// Returns false if the file already contains the roll, a different roll is never replaced.
+    pub fn store_roll(&self, path: &Path) -> Result<bool, Error> {
+        let mut rolls: BTreeMap<String, BTreeSet<String>> = if path.exists() {
+            let content = fs::read_to_string(path).map_err(|err| {
+                Error::Other(format!("failed to read {}: {}", path.display(), err))
+            })?;
+            serde_json::from_str(&content).map_err(|_| {
+                Error::Other(format!("invalid eligible voters file: {}", path.display()))
+            })?
+        } else {
+            BTreeMap::new()
+        };
+
+        match rolls.get(&self.vote) {
+            Some(roll) if *roll == self.roll => return Ok(false),
+            Some(_) => {
+                return Err(Error::Other(format!(
+                    "{} contains a different roll for the vote: {}",
+                    path.display(),
+                    self.vote
+                )))
+            }
+            None => rolls.insert(self.vote.clone(), self.roll.clone()),
+        };
+
+        let content =
+            serde_json::to_string_pretty(&rolls).map_err(|err| Error::Other(err.to_string()))?;
+        fs::write(path, content)
+            .map_err(|err| Error::Other(format!("failed to write {}: {}", path.display(), err)))?;
+        Ok(true)
+    }
+}
+
+fn parse_datetime(datetime: &Datetime) -> Result<DateTime<Utc>, Error> {
+    DateTime::parse_from_rfc3339(&datetime.to_string())
+        .map(|datetime| datetime.with_timezone(&Utc))
+        .map_err(|_| {
+            Error::Other(format!(
+                "invalid election file: the schedule requires a date, time and offset: {}",
+                datetime
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_keyring::AccountKeyring;
+    use std::env;
+
+    const ELECTION: &str = r#"
+        title = "Election 2021"
+        params = "lg"
+        roll = ROLL
+
+        [schedule]
+        voting_start = 2021-03-01T08:00:00Z
+        voting_end = 2021-03-07T18:00:00+01:00
+
+        [[questions]]
+        question = "Should the law be accepted?"
+        answers = ["no", "yes"]
+
+        [[questions]]
+        question = "Should the other law be accepted?"
+        answers = ["no", "yes"]
+    "#;
+
+    fn election(roll: &[AccountKeyring]) -> String {
+        let roll: Vec<String> = roll
+            .iter()
+            .map(|voter| voter.to_account_id().to_ss58check())
+            .collect();
+        ELECTION.replace("ROLL", &format!("{:?}", roll))
+    }
+
+    #[test]
+    fn it_should_parse_an_election_file() {
+        let content = election(&[AccountKeyring::Dave, AccountKeyring::Eve]);
+        let election = Election::parse(&content).unwrap();
+        assert_eq!(election.vote, "Election 2021");
+        assert_eq!(election.params, ParameterSet::Lg);
+        assert_eq!(election.batch_size, DEFAULT_BATCH_SIZE);
+        assert_eq!(election.questions.len(), 2);
+        assert_eq!(
+            election.questions[0].topic_answers(),
+            vec![(0, b"no".to_vec()), (1, b"yes".to_vec())]
+        );
+        assert_eq!(election.roll.len(), 2);
+        assert_eq!(
+            election.schedule.voting_end.to_rfc3339(),
+            "2021-03-07T17:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn it_should_name_the_groups() {
+        let content = election(&[AccountKeyring::Dave]).replace("\"lg\"", "\"ffdhe3072\"");
+        let election = Election::parse(&content).unwrap();
+        assert_eq!(election.params, ParameterSet::Ffdhe3072);
+
+        let params = election.params.params();
+        assert_eq!(params.group, Some(b"ffdhe3072".to_vec()));
+        assert_eq!(params, NamedGroup::Ffdhe3072.into());
+        assert_eq!(ParameterSet::Lg.params().group, None);
+    }
+
+    #[test]
+    fn it_should_parse_the_example() {
+        let election = Election::parse(include_str!("../election.example.toml")).unwrap();
+        assert_eq!(election.vote, "Election2021");
+        assert!(election
+            .roll
+            .contains(&AccountKeyring::Dave.to_account_id().to_ss58check()));
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_election_files() {
+        let valid = election(&[AccountKeyring::Dave]);
+        let invalid = vec![
+            valid.replace("title = \"Election 2021\"", "title = \" \""),
+            valid.replace("\"lg\"", "\"tiny\""),
+            // the runtime rejects the 1024bit parameter set
+            valid.replace("\"lg\"", "\"md\""),
+            // the safe prime test of the 3072bit parameter set exceeds the maximum extrinsic weight
+            valid.replace("\"lg\"", "\"xl\""),
+            valid.replace("2021-03-01T08:00:00Z", "2021-03-08T08:00:00Z"),
+            valid.replace("2021-03-01T08:00:00Z", "2021-03-01T08:00:00"),
+            valid.replace("the other law", "the law"),
+            valid.replace("[\"no\", \"yes\"]", "[\"yes\"]"),
+            valid.replace("[\"no\", \"yes\"]", "[\"yes\", \"yes\"]"),
+            election(&[]),
+            election(&[AccountKeyring::Dave, AccountKeyring::Dave]),
+            ELECTION.replace("ROLL", "[\"not an account\"]"),
+            format!("{}\nunknown = 1", valid),
+        ];
+        for content in invalid {
+            assert!(Election::parse(&content).is_err(), "{}", content);
+        }
+    }
+
+    #[test]
+    fn it_should_never_replace_a_roll() {
+        let path = env::temp_dir().join("provotum-client-test-eligible-voters.json");
+        let _ = fs::remove_file(&path);
+
+        let first = Election::parse(&election(&[AccountKeyring::Dave])).unwrap();
+        assert!(first.store_roll(&path).unwrap());
+        assert!(!first.store_roll(&path).unwrap());
+
+        let other = Election::parse(&election(&[AccountKeyring::Eve])).unwrap();
+        assert!(other.store_roll(&path).is_err());
+    }
+}
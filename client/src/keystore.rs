@@ -0,0 +1,23 @@
+use std::{env, io};
+use substrate_subxt::Error;
+
+/// the env variable containing the password of the keystore
+pub const PASSWORD_ENV: &str = "PROVOTUM_KEYSTORE_PASSWORD";
+
+/// Reads the password of the keystore from the env.
+pub fn password() -> Result<String, Error> {
+    env::var(PASSWORD_ENV)
+        .map_err(|_| Error::Other(format!("the env variable {} is required", PASSWORD_ENV)))
+}
+
+/// Reads a secret from the env variable or, if unset, the first line of stdin.
+pub fn read_secret(name: &str) -> Result<String, Error> {
+    if let Ok(secret) = env::var(name) {
+        return Ok(secret);
+    }
+    let mut secret = String::new();
+    io::stdin()
+        .read_line(&mut secret)
+        .map_err(|err| Error::Other(err.to_string()))?;
+    Ok(secret.trim().to_string())
+}
@@ -1,38 +1,146 @@
 mod cli;
+mod config;
+mod election;
+mod keystore;
+mod output;
 mod voting;
 
 use async_std::task;
 use clap::Clap;
-use cli::cli::{Opts, SealerSubCommand, SubCommand, VASubCommand};
+use cli::cli::{Opts, SealerSubCommand, SubCommand, VASubCommand, VoterSubCommand};
+use config::{Config, ConfigFile, Profile};
+use output::Output;
 use voting::{
-    sealer::{decrypt, keygen},
-    va::{change_vote_phase, get_result, setup_question, setup_vote},
+    daemon::daemon,
+    offline::{broadcast, export},
+    sealer::{add_sealer, decrypt, keygen, sign_decryption},
+    va::{
+        change_vote_phase, create_credential_key, create_election, get_result,
+        register_randomizer, register_relayer, require_randomizer_signature, setup_answers,
+        setup_question, setup_vote,
+    },
 };
-use voting::{va::combine_public_key_shares, va::tally_question, voter::create_votes};
+use voting::{va::combine_public_key_shares, va::tally_question};
+use voting::load::load;
+use voting::voter::{cast, create_keystore, create_votes, sign_ballot};
+use voting::verify::verify;
+use voting::watch::watch;
 
 fn main() {
     let opts: Opts = Opts::parse();
+    env_logger::init();
+
+    // resolve the node, randomizer, api and identity of the selected profile
+    let overrides = Profile {
+        node_url: opts.node_url,
+        randomizer_url: opts.randomizer_url,
+        api_url: opts.api_url,
+        identity: opts.identity,
+    };
+    let profile = opts.profile;
+    let output = opts.output;
+    let config = match ConfigFile::load(opts.config.as_deref())
+        .and_then(|file| Config::resolve(&file, profile, overrides))
+    {
+        Ok(config) => config,
+        Err(err) => {
+            println!("failed to load config: {:?}", err);
+            std::process::exit(1);
+        }
+    };
 
     // You can handle information about subcommands by requesting their matches by name
     // (as below), requesting just the name used, or both at the same time
     match opts.subcmd {
-        SubCommand::Voter(t) => {
-            println!("Voter. Creating votes... {:?}", t);
-            task::block_on(async {
-                let result =
-                    task::spawn(create_votes(t.vote, t.question, t.nr_of_votes, t.votes)).await;
-                match result {
-                    Ok(_) =>  println!("successfully created {:?} votes.", t.nr_of_votes),
-
-                    Err(err) => println!("failed to create vote: {:?}", err),
+        SubCommand::Voter(t) => match t.subcmd {
+            VoterSubCommand::CreateVotes(t) => {
+                println!("Voter. Creating votes... {:?}", t);
+                task::block_on(async {
+                    let result = task::spawn(create_votes(
+                        config,
+                        t.vote,
+                        t.question,
+                        t.nr_of_votes,
+                        t.votes,
+                    ))
+                    .await;
+                    match result {
+                        Ok(_) => println!("successfully created {:?} votes.", t.nr_of_votes),
+                        Err(err) => println!("failed to create vote: {:?}", err),
+                    }
+                });
+            }
+            VoterSubCommand::Cast(t) => {
+                println!("Voter. Casting ballot... {:?}", t);
+                task::block_on(async {
+                    let result = task::spawn(cast(
+                        config,
+                        t.vote,
+                        t.answers,
+                        t.keystore,
+                        t.skip_randomizer,
+                    ))
+                    .await;
+                    match result {
+                        Ok(_) => println!("successfully cast ballot!"),
+                        Err(err) => println!("failed to cast ballot: {:?}", err),
+                    }
+                });
+            }
+            VoterSubCommand::Keystore(t) => {
+                println!("Voter. Creating keystore... {:?}", t);
+                match create_keystore(t.name, t.file) {
+                    Ok(_) => println!("successfully created keystore!"),
+                    Err(err) => println!("failed to create keystore: {:?}", err),
                 }
-            });
-        }
+            }
+            VoterSubCommand::Load(t) => {
+                eprintln!("Voter. Generating load... {:?}", t);
+                let output = output.unwrap_or(Output::Table);
+                task::block_on(async {
+                    let result = task::spawn(load(
+                        config,
+                        t.vote,
+                        t.voters,
+                        t.concurrency,
+                        t.offset,
+                        t.retries,
+                        output,
+                    ))
+                    .await;
+                    if let Err(err) = result {
+                        eprintln!("failed to generate load: {:?}", err);
+                    }
+                });
+            }
+            VoterSubCommand::Sign(t) => {
+                println!("Voter. Signing ballot offline... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        sign_ballot(t.export, t.answers, t.keystore, t.nonce, t.file).await;
+                    match result {
+                        Ok(_) => println!("successfully signed ballot!"),
+                        Err(err) => println!("failed to sign ballot: {:?}", err),
+                    }
+                });
+            }
+        },
         SubCommand::VotingAuthority(t) => match t.subcmd {
+            VASubCommand::CreateElection(t) => {
+                println!("VA. Creating election... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        task::spawn(create_election(config, t.file, t.eligible_voters)).await;
+                    match result {
+                        Ok(_) => println!("successfully created election!"),
+                        Err(err) => println!("failed to create election: {:?}", err),
+                    }
+                });
+            }
             VASubCommand::SetupVote(t) => {
                 println!("VA. Creating vote... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(setup_vote(t.vote, t.question)).await;
+                    let result = task::spawn(setup_vote(config, t.vote, t.question)).await;
                     match result {
                         Ok(_) => println!("successfully created vote!"),
                         Err(err) => println!("failed to create vote: {:?}", err),
@@ -42,17 +150,28 @@ fn main() {
             VASubCommand::StoreQuestion(t) => {
                 println!("VA. Store Question... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(setup_question(t.vote, t.question)).await;
+                    let result = task::spawn(setup_question(config, t.vote, t.question)).await;
                     match result {
                         Ok(_) => println!("successfully setup question!"),
                         Err(err) => println!("failed to setup question: {:?}", err),
                     }
                 });
             }
+            VASubCommand::StoreAnswers(t) => {
+                println!("VA. Store Answers... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        task::spawn(setup_answers(config, t.vote, t.question, t.answers)).await;
+                    match result {
+                        Ok(_) => println!("successfully stored answers!"),
+                        Err(err) => println!("failed to store answers: {:?}", err),
+                    }
+                });
+            }
             VASubCommand::SetVotePhase(t) => {
                 println!("VA. Changing Vote Phase... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(change_vote_phase(t.vote, t.phase)).await;
+                    let result = task::spawn(change_vote_phase(config, t.vote, t.phase)).await;
                     match result {
                         Ok(_) => println!("successfully updated vote phase!"),
                         Err(err) => println!("failed to set vote: {:?}", err),
@@ -62,30 +181,78 @@ fn main() {
             VASubCommand::CombinePublicKeyShares(t) => {
                 println!("VA. Combining Public Key Shares... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(combine_public_key_shares(t.vote)).await;
+                    let result = task::spawn(combine_public_key_shares(config, t.vote)).await;
                     match result {
                         Ok(_) => println!("successfully create public key!"),
                         Err(err) => println!("failed to create public key: {:?}", err),
                     }
                 });
             }
+            // the status is written to stderr, the result to stdout
             VASubCommand::TallyQuestion(t) => {
-                println!("VA. Tallying Question... {:?}", t);
+                eprintln!("VA. Tallying Question... {:?}", t);
+                let output = output.unwrap_or(Output::Table);
                 task::block_on(async {
-                    let result = task::spawn(tally_question(t.vote, t.question)).await;
+                    let result = task::spawn(tally_question(
+                        config, t.vote, t.question, t.encoded, output,
+                    ))
+                    .await;
                     match result {
-                        Ok(_) => println!("successfully tallied question!"),
-                        Err(err) => println!("failed to tally question: {:?}", err),
+                        Ok(_) => eprintln!("successfully tallied question!"),
+                        Err(err) => eprintln!("failed to tally question: {:?}", err),
                     }
                 });
             }
             VASubCommand::GetResult(t) => {
-                println!("VA. Get Result... {:?}", t);
+                eprintln!("VA. Get Result... {:?}", t);
+                let output = output.unwrap_or(Output::Table);
                 task::block_on(async {
-                    let result = task::spawn(get_result(t.question)).await;
+                    let result = task::spawn(get_result(config, t.vote, t.question, output)).await;
+                    if let Err(err) = result {
+                        eprintln!("failed to fetch result: {:?}", err);
+                    }
+                });
+            }
+            VASubCommand::StoreRandomizerKey(t) => {
+                println!("VA. Store Randomizer Key... {:?}", t);
+                task::block_on(async {
+                    let result = task::spawn(register_randomizer(config, t.key)).await;
                     match result {
-                        Ok(_) => (),
-                        Err(err) => println!("failed to fetch result: {:?}", err),
+                        Ok(_) => println!("successfully stored randomizer key!"),
+                        Err(err) => println!("failed to store randomizer key: {:?}", err),
+                    }
+                });
+            }
+            VASubCommand::RequireRandomizerSignature(t) => {
+                println!("VA. Require Randomizer Signature... {:?}", t);
+                let required = t.required == "true";
+                task::block_on(async {
+                    let result =
+                        task::spawn(require_randomizer_signature(config, t.vote, required)).await;
+                    match result {
+                        Ok(_) => println!("successfully updated randomizer signature requirement!"),
+                        Err(err) => println!("failed to update randomizer signature requirement: {:?}", err),
+                    }
+                });
+            }
+            VASubCommand::StoreRelayer(t) => {
+                println!("VA. Store Relayer... {:?}", t);
+                task::block_on(async {
+                    let result = task::spawn(register_relayer(config, t.account)).await;
+                    match result {
+                        Ok(_) => println!("successfully stored relayer!"),
+                        Err(err) => println!("failed to store relayer: {:?}", err),
+                    }
+                });
+            }
+            VASubCommand::CreateCredentialKey(t) => {
+                println!("VA. Create Credential Key... {:?}", t);
+                task::block_on(async {
+                    let result =
+                        task::spawn(create_credential_key(config, t.vote, t.bits, t.dir)).await;
+                    match result {
+                        Ok(_) => println!("successfully stored credential key!"),
+                        Err(err) => println!("failed to store credential key: {:?}", err),
                     }
                 });
             }
@@ -94,7 +261,7 @@ fn main() {
             SealerSubCommand::KeyGeneration(t) => {
                 println!("Printing sealer - key generation... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(keygen(t.vote, t.sk, t.who)).await;
+                    let result = task::spawn(keygen(config, t.vote, t.who, t.keystore)).await;
                     match result {
                         Ok(_) => println!("successfully submitted public key share!"),
                         Err(err) => println!("failed to submitted public key share: {:?}", err),
@@ -104,13 +271,82 @@ fn main() {
             SealerSubCommand::PartialDecryption(t) => {
                 println!("Printing sealer - partial decryption... {:?}", t);
                 task::block_on(async {
-                    let result = task::spawn(decrypt(t.vote, t.question, t.sk, t.who)).await;
+                    let result =
+                        task::spawn(decrypt(config, t.vote, t.question, t.who, t.keystore)).await;
                     match result {
                         Ok(_) => println!("successfully submitted partial decryption!"),
                         Err(err) => println!("failed to submit partial decryption: {:?}", err),
                     }
                 });
             }
+            SealerSubCommand::Daemon(t) => {
+                println!("Printing sealer - daemon... {:?}", t);
+                task::block_on(async {
+                    let result = task::spawn(daemon(config, t.who, t.keystore)).await;
+                    if let Err(err) = result {
+                        println!("failed to run sealer daemon: {:?}", err);
+                    }
+                });
+            }
+            SealerSubCommand::Sign(t) => {
+                println!("Printing sealer - signing partial decryption offline... {:?}", t);
+                task::block_on(async {
+                    let result = sign_decryption(
+                        t.export, t.question, t.who, t.keystore, t.nonce, t.file,
+                    )
+                    .await;
+                    match result {
+                        Ok(_) => println!("successfully signed partial decryption!"),
+                        Err(err) => println!("failed to sign partial decryption: {:?}", err),
+                    }
+                });
+            }
+            SealerSubCommand::Add(t) => match add_sealer(t.who, t.keystore) {
+                Ok(_) => println!("successfully added sealer!"),
+                Err(err) => println!("failed to add sealer: {:?}", err),
+            },
         },
+        SubCommand::Watch(t) => {
+            task::block_on(async {
+                let result = task::spawn(watch(config, t.vote)).await;
+                if let Err(err) = result {
+                    println!("failed to watch vote: {:?}", err);
+                }
+            });
+        }
+        SubCommand::Export(t) => {
+            println!("Exporting vote... {:?}", t);
+            task::block_on(async {
+                let result = task::spawn(export(config, t.vote, t.accounts, t.file)).await;
+                match result {
+                    Ok(_) => println!("successfully exported vote!"),
+                    Err(err) => println!("failed to export vote: {:?}", err),
+                }
+            });
+        }
+        SubCommand::Broadcast(t) => {
+            println!("Broadcasting signed extrinsic... {:?}", t);
+            task::block_on(async {
+                let result = task::spawn(broadcast(config, t.file)).await;
+                match result {
+                    Ok(_) => println!("successfully broadcast extrinsic!"),
+                    Err(err) => println!("failed to broadcast extrinsic: {:?}", err),
+                }
+            });
+        }
+        SubCommand::Verify(t) => {
+            task::block_on(async {
+                let output = output.unwrap_or(Output::Json);
+                let result = task::spawn(verify(config, t.vote, output)).await;
+                match result {
+                    Ok(true) => {}
+                    Ok(false) => std::process::exit(1),
+                    Err(err) => {
+                        eprintln!("failed to verify vote: {:?}", err);
+                        std::process::exit(1);
+                    }
+                }
+            });
+        }
     }
 }
@@ -0,0 +1,320 @@
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use pallet_mixnet::types::{Topic, TopicAnswer, TopicResult, Vote, VoteId};
+use serde::{Serialize, Serializer};
+use std::{fmt, str::FromStr};
+use substrate_subxt::Error;
+
+/// The format of the results printed by the CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    Table,
+    Json,
+    Csv,
+}
+
+impl FromStr for Output {
+    type Err = String;
+
+    fn from_str(output: &str) -> Result<Self, Self::Err> {
+        match output {
+            "table" => Ok(Output::Table),
+            "json" => Ok(Output::Json),
+            "csv" => Ok(Output::Csv),
+            _ => Err(format!("unknown output format: {}", output)),
+        }
+    }
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String, Error> {
+    serde_json::to_string_pretty(value).map_err(|err| Error::Other(err.to_string()))
+}
+
+/// Rows of columns rendered as an aligned table or as csv.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rows {
+    pub headers: Vec<&'static str>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Rows {
+    pub fn table(&self) -> String {
+        let mut widths: Vec<usize> = self.headers.iter().map(|header| header.len()).collect();
+        for row in self.rows.iter() {
+            for (width, column) in widths.iter_mut().zip(row.iter()) {
+                *width = (*width).max(column.chars().count());
+            }
+        }
+        let line = |columns: Vec<&str>| {
+            columns
+                .iter()
+                .zip(widths.iter())
+                .map(|(column, width)| format!("{:<width$}", column, width = width))
+                .collect::<Vec<String>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        };
+        let mut lines = vec![line(self.headers.to_vec())];
+        for row in self.rows.iter() {
+            lines.push(line(row.iter().map(|column| column.as_str()).collect()));
+        }
+        lines.join("\n")
+    }
+
+    pub fn csv(&self) -> String {
+        let line = |columns: Vec<&str>| {
+            columns
+                .iter()
+                .map(|column| csv_field(column))
+                .collect::<Vec<String>>()
+                .join(",")
+        };
+        let mut lines = vec![line(self.headers.to_vec())];
+        for row in self.rows.iter() {
+            lines.push(line(row.iter().map(|column| column.as_str()).collect()));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Quotes a field if it contains a separator, a quote or a line break (RFC 4180).
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// An integer stored as big-endian bytes on chain.
+/// Serialized as a number if it fits into an u64, otherwise as a decimal string.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Integer(pub BigUint);
+
+impl Integer {
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Integer(BigUint::from_bytes_be(bytes))
+    }
+}
+
+impl fmt::Display for Integer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl Serialize for Integer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0.to_u64() {
+            Some(value) => serializer.serialize_u64(value),
+            None => serializer.serialize_str(&self.0.to_str_radix(10)),
+        }
+    }
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct VoteMetadata {
+    pub id: String,
+    pub title: String,
+    pub phase: String,
+}
+
+impl VoteMetadata {
+    pub fn new<AccountId>(vote_id: &VoteId, vote: &Vote<AccountId>) -> Self {
+        VoteMetadata {
+            id: label(vote_id),
+            title: label(&vote.title),
+            phase: format!("{:?}", vote.phase),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct TopicMetadata {
+    pub id: String,
+    pub question: String,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AnswerCount {
+    // the decrypted vote
+    pub value: Integer,
+    // the label of the answer, if the question has answers
+    pub answer: Option<String>,
+    pub count: Integer,
+}
+
+/// The tally of a question including the metadata of its vote.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuestionResult {
+    pub vote: VoteMetadata,
+    pub topic: TopicMetadata,
+    pub total: Integer,
+    pub results: Vec<AnswerCount>,
+}
+
+impl QuestionResult {
+    pub fn new(
+        vote: VoteMetadata,
+        topic: &Topic,
+        answers: &[TopicAnswer],
+        tally: &TopicResult,
+    ) -> Self {
+        let results: Vec<AnswerCount> = tally
+            .iter()
+            .map(|(value, count)| {
+                let value = Integer::from_bytes(value);
+                let answer = answers
+                    .iter()
+                    .find(|(answer, _)| BigUint::from(*answer) == value.0)
+                    .map(|(_, answer)| label(answer));
+                AnswerCount {
+                    value,
+                    answer,
+                    count: Integer::from_bytes(count),
+                }
+            })
+            .collect();
+        let total = results.iter().map(|result| &result.count.0).sum();
+        QuestionResult {
+            vote,
+            topic: TopicMetadata {
+                id: label(&topic.0),
+                question: label(&topic.1),
+            },
+            total: Integer(total),
+            results,
+        }
+    }
+
+    pub fn render(&self, output: Output) -> Result<String, Error> {
+        match output {
+            Output::Json => to_json(self),
+            Output::Csv => {
+                let rows = Rows {
+                    headers: vec![
+                        "vote", "title", "phase", "topic", "question", "value", "answer", "count",
+                    ],
+                    rows: self
+                        .results
+                        .iter()
+                        .map(|result| {
+                            vec![
+                                self.vote.id.clone(),
+                                self.vote.title.clone(),
+                                self.vote.phase.clone(),
+                                self.topic.id.clone(),
+                                self.topic.question.clone(),
+                                result.value.to_string(),
+                                result.answer.clone().unwrap_or_default(),
+                                result.count.to_string(),
+                            ]
+                        })
+                        .collect(),
+                };
+                Ok(rows.csv())
+            }
+            Output::Table => {
+                let rows = Rows {
+                    headers: vec!["VALUE", "ANSWER", "COUNT"],
+                    rows: self
+                        .results
+                        .iter()
+                        .map(|result| {
+                            vec![
+                                result.value.to_string(),
+                                result.answer.clone().unwrap_or_default(),
+                                result.count.to_string(),
+                            ]
+                        })
+                        .collect(),
+                };
+                Ok(format!(
+                    "Vote: {} ({}), phase: {}\nQuestion: {}\n{}\nTotal: {}",
+                    self.vote.title,
+                    self.vote.id,
+                    self.vote.phase,
+                    self.topic.question,
+                    rows.table(),
+                    self.total
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pallet_mixnet::types::{PublicParameters, VotePhase};
+    use std::collections::BTreeMap;
+
+    fn result() -> QuestionResult {
+        let vote = Vote::<u64> {
+            voting_authority: 0,
+            title: b"Election, 2021".to_vec(),
+            phase: VotePhase::Tallying,
+            params: PublicParameters::default(),
+        };
+        let topic: Topic = (b"law".to_vec(), b"Should the \"law\" be accepted?".to_vec());
+        let answers: Vec<TopicAnswer> = vec![(0, b"no".to_vec()), (1, b"yes".to_vec())];
+        let mut tally: TopicResult = BTreeMap::new();
+        tally.insert(vec![0], vec![3]);
+        tally.insert(vec![1], vec![5]);
+        tally.insert(vec![1, 0, 0, 0, 0, 0, 0, 0, 0], vec![1]);
+        QuestionResult::new(
+            VoteMetadata::new(&b"2021".to_vec(), &vote),
+            &topic,
+            &answers,
+            &tally,
+        )
+    }
+
+    #[test]
+    fn it_should_decode_and_label_the_results() {
+        let result = result();
+        assert_eq!(result.total, Integer(BigUint::from(9u32)));
+        assert_eq!(result.results[1].answer, Some("yes".to_string()));
+        assert_eq!(result.results[2].answer, None);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&result.render(Output::Json).unwrap()).unwrap();
+        assert_eq!(json["vote"]["phase"], "Tallying");
+        assert_eq!(json["total"], 9);
+        assert_eq!(json["results"][1]["value"], 1);
+        assert_eq!(json["results"][1]["count"], 5);
+        assert_eq!(json["results"][2]["value"], "18446744073709551616");
+    }
+
+    #[test]
+    fn it_should_render_csv_and_tables() {
+        let result = result();
+        let csv = result.render(Output::Csv).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "vote,title,phase,topic,question,value,answer,count"
+        );
+        assert_eq!(
+            lines[1],
+            "2021,\"Election, 2021\",Tallying,law,\"Should the \"\"law\"\" be accepted?\",0,no,3"
+        );
+        assert_eq!(lines.len(), 4);
+
+        let table = result.render(Output::Table).unwrap();
+        assert!(table.contains("VALUE                 ANSWER  COUNT"));
+        assert!(table.contains("1                     yes     5"));
+        assert!(table.ends_with("Total: 9"));
+    }
+
+    #[test]
+    fn it_should_parse_output_formats() {
+        assert_eq!("csv".parse::<Output>(), Ok(Output::Csv));
+        assert!("xml".parse::<Output>().is_err());
+    }
+}
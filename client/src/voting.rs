@@ -1,5 +1,9 @@
+pub mod daemon;
+pub mod load;
+pub mod offline;
 pub mod sealer;
 pub mod va;
 pub mod voter;
+pub mod verify;
+pub mod watch;
 
-mod substrate;
@@ -0,0 +1,591 @@
+use crate::config::Config;
+use crate::keystore;
+use crate::voting::sealer::{
+    create_key, get_sealer, keystore_path, submit_decryption, submit_key_share, unseal_key,
+};
+use crypto::types::ElGamalParams;
+use pallet_mixnet::types::VotePhase;
+use provotum_sdk::keystore::KeystoreEntry;
+use provotum_sdk::stores::{
+    DecryptedSharesStore, PublicKeyShareBySealerStore, SealersStore, ShuffleStateStore,
+    TopicsStore, VoteStore, VotesStore,
+};
+use serde::{Deserialize, Serialize};
+use sp_keyring::sr25519::sr25519::Pair;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use substrate_subxt::{system::System, Client, Signer};
+use substrate_subxt::{Error, NodeTemplateRuntime, PairSigner};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+type Hash = <NodeTemplateRuntime as System>::Hash;
+
+/// the number of failed attempts after which a submission is given up
+const MAX_ATTEMPTS: u32 = 5;
+
+/// The steps completed by the sealer, persisted after each submission.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub votes: BTreeMap<String, VoteProgress>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct VoteProgress {
+    pub key_share: bool,
+    // the questions whose partial decryptions are submitted
+    pub decrypted: BTreeSet<String>,
+    // whether nothing is left to submit, the vote is not fetched anymore
+    #[serde(default)]
+    pub finished: bool,
+}
+
+impl Progress {
+    /// Reads the progress, nothing is completed if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        if !path.exists() {
+            return Ok(Progress::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|err| Error::Other(format!("failed to read {}: {}", path.display(), err)))?;
+        serde_json::from_str(&content)
+            .map_err(|_| Error::Other(format!("invalid progress file: {}", path.display())))
+    }
+
+    /// Replaces the file atomically, a crash never leaves a partially written file.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content =
+            serde_json::to_string_pretty(self).map_err(|err| Error::Other(err.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, content)
+            .and_then(|_| fs::rename(&tmp, path))
+            .map_err(|err| Error::Other(format!("failed to write {}: {}", path.display(), err)))
+    }
+
+    fn complete(&mut self, task: &Task) {
+        match task {
+            Task::KeyShare { vote } => {
+                self.votes.entry(vote.clone()).or_default().key_share = true;
+            }
+            Task::Decryption { vote, topic } => {
+                let progress = self.votes.entry(vote.clone()).or_default();
+                progress.decrypted.insert(topic.clone());
+            }
+        }
+    }
+
+    fn finish(&mut self, vote: &str) {
+        self.votes.entry(vote.to_string()).or_default().finished = true;
+    }
+
+    fn is_finished(&self, vote: &str) -> bool {
+        self.votes
+            .get(vote)
+            .map(|progress| progress.finished)
+            .unwrap_or(false)
+    }
+
+    fn is_completed(&self, task: &Task) -> bool {
+        match task {
+            Task::KeyShare { vote } => self
+                .votes
+                .get(vote)
+                .map(|progress| progress.key_share)
+                .unwrap_or(false),
+            Task::Decryption { vote, topic } => self
+                .votes
+                .get(vote)
+                .map(|progress| progress.decrypted.contains(topic))
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// The progress file of a sealer: `<dir>/<sealer>.progress.json`
+fn progress_path(dir: &str, sealer: &str) -> PathBuf {
+    Path::new(dir).join(format!("{}.progress.json", sealer))
+}
+
+/// A submission of the sealer.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Task {
+    KeyShare { vote: String },
+    Decryption { vote: String, topic: String },
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Task::KeyShare { vote } => write!(f, "public key share of vote: {}", vote),
+            Task::Decryption { vote, topic } => {
+                write!(
+                    f,
+                    "partial decryption of question: {} of vote: {}",
+                    topic, vote
+                )
+            }
+        }
+    }
+}
+
+/// A failed submission, retried once the block `retry_at` is finalized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Failure {
+    attempts: u32,
+    retry_at: u32,
+}
+
+/// The failed submissions, retried with an exponential backoff (2, 4, 8, ... blocks).
+/// A submission is given up after `MAX_ATTEMPTS` until the daemon is restarted.
+#[derive(Debug, Default)]
+struct Retries {
+    failures: BTreeMap<Task, Failure>,
+}
+
+impl Retries {
+    fn is_due(&self, task: &Task, block: u32) -> bool {
+        match self.failures.get(task) {
+            Some(failure) => failure.attempts < MAX_ATTEMPTS && failure.retry_at <= block,
+            None => true,
+        }
+    }
+
+    /// Records the failure, returns whether the submission is given up.
+    fn fail(&mut self, task: &Task, block: u32) -> bool {
+        let failure = self.failures.entry(task.clone()).or_insert(Failure {
+            attempts: 0,
+            retry_at: block,
+        });
+        failure.attempts += 1;
+        failure.retry_at = block.saturating_add(1 << failure.attempts);
+        failure.attempts >= MAX_ATTEMPTS
+    }
+
+    fn succeed(&mut self, task: &Task) {
+        self.failures.remove(task);
+    }
+}
+
+/// The state of a question on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TopicState {
+    topic: String,
+    // whether the last shuffle is completed
+    shuffled: bool,
+    // whether the decrypted shares of the sealer are stored
+    decrypted: bool,
+}
+
+/// The state of a vote on chain.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct VoteState {
+    vote: String,
+    phase: VotePhase,
+    // whether the public key share of the sealer is stored
+    key_share: bool,
+    topics: Vec<TopicState>,
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+/// Fetches the state of all unfinished votes, no votes are returned if the account is not a sealer.
+/// Only the state required by the vote's phase is fetched, completed submissions are not fetched again.
+async fn fetch_votes(
+    client: &Client<NodeTemplateRuntime>,
+    sealer: &AccountId,
+    progress: &Progress,
+    at: Option<Hash>,
+) -> Result<Vec<VoteState>, Error> {
+    let sealers = client.fetch_or_default(&SealersStore {}, at).await?;
+    if !sealers.contains(sealer) {
+        return Ok(Vec::new());
+    }
+
+    let mut votes = Vec::new();
+    for vote_id in client.fetch_or_default(&VotesStore {}, at).await? {
+        let name = label(&vote_id);
+        if progress.is_finished(&name) {
+            continue;
+        }
+        let store = VoteStore {
+            vote_id: vote_id.clone(),
+        };
+        let vote = match client.fetch(&store, at).await? {
+            Some(vote) => vote,
+            None => continue,
+        };
+        let key_share = progress.is_completed(&Task::KeyShare { vote: name.clone() }) || {
+            let store = PublicKeyShareBySealerStore {
+                vote_id: vote_id.clone(),
+                sealer: sealer.clone(),
+            };
+            client.fetch(&store, at).await?.is_some()
+        };
+
+        // the questions are only decrypted while tallying
+        let mut topics = Vec::new();
+        let store = TopicsStore {
+            vote_id: vote_id.clone(),
+        };
+        let topic_ids = match vote.phase {
+            VotePhase::Tallying => client.fetch_or_default(&store, at).await?,
+            _ => Vec::new(),
+        };
+        for (topic_id, _) in topic_ids {
+            let task = Task::Decryption {
+                vote: name.clone(),
+                topic: label(&topic_id),
+            };
+            if progress.is_completed(&task) {
+                topics.push(TopicState {
+                    topic: label(&topic_id),
+                    shuffled: true,
+                    decrypted: true,
+                });
+                continue;
+            }
+            let store = ShuffleStateStore {
+                vote_id: vote_id.clone(),
+                topic_id: topic_id.clone(),
+            };
+            let shuffled = client
+                .fetch(&store, at)
+                .await?
+                .map(|state| state.done)
+                .unwrap_or(false);
+            let store = DecryptedSharesStore {
+                topic_id: topic_id.clone(),
+                sealer: sealer.clone(),
+            };
+            let decrypted = !client.fetch_or_default(&store, at).await?.is_empty();
+            topics.push(TopicState {
+                topic: label(&topic_id),
+                shuffled,
+                decrypted,
+            });
+        }
+        votes.push(VoteState {
+            vote: name,
+            phase: vote.phase,
+            key_share,
+            topics,
+        });
+    }
+    Ok(votes)
+}
+
+/// Records the submissions stored on chain as completed and returns the pending submissions.
+/// The key share is submitted during the key generation, the partial decryption of a question
+/// once its last shuffle is completed.
+///
+/// A vote is finished once all questions are decrypted, or if the key generation ended
+/// without a key share of the sealer.
+fn pending(progress: &mut Progress, votes: &[VoteState]) -> Vec<Task> {
+    let mut tasks = Vec::new();
+    for vote in votes.iter() {
+        let task = Task::KeyShare {
+            vote: vote.vote.clone(),
+        };
+        if vote.key_share {
+            progress.complete(&task);
+        } else if vote.phase == VotePhase::KeyGeneration && !progress.is_completed(&task) {
+            tasks.push(task);
+        }
+
+        for topic in vote.topics.iter() {
+            let task = Task::Decryption {
+                vote: vote.vote.clone(),
+                topic: topic.topic.clone(),
+            };
+            if topic.decrypted {
+                progress.complete(&task);
+            } else if vote.phase == VotePhase::Tallying
+                && topic.shuffled
+                && !progress.is_completed(&task)
+            {
+                tasks.push(task);
+            }
+        }
+
+        let missed_key_generation = vote.phase != VotePhase::KeyGeneration && !vote.key_share;
+        let decrypted =
+            vote.phase == VotePhase::Tallying && vote.topics.iter().all(|topic| topic.decrypted);
+        if missed_key_generation || decrypted {
+            progress.finish(&vote.vote);
+        }
+    }
+    tasks
+}
+
+/// The sealer's identity and keystore.
+struct Sealer {
+    name: String,
+    pair: Pair,
+    id: [u8; 32],
+    dir: String,
+    password: String,
+}
+
+async fn submit(
+    client: &Client<NodeTemplateRuntime>,
+    sealer: &Sealer,
+    task: &Task,
+) -> Result<(), Error> {
+    let vote = match task {
+        Task::KeyShare { vote } | Task::Decryption { vote, .. } => vote,
+    };
+    let vote_id = vote.as_bytes().to_vec();
+    let store = VoteStore {
+        vote_id: vote_id.clone(),
+    };
+    let params: ElGamalParams = client
+        .fetch(&store, None)
+        .await?
+        .ok_or("the vote does not exist!")?
+        .params
+        .into();
+    let path = keystore_path(&sealer.dir, &sealer.name, vote);
+    let pair = sealer.pair.clone();
+
+    match task {
+        Task::KeyShare { .. } => {
+            // a key written before a restart is reused, its share might not be stored yet
+            let (sk, pk) = if path.exists() {
+                unseal_key(&KeystoreEntry::read(&path)?, &sealer.password, &params)?
+            } else {
+                create_key(&path, vote, &params, &sealer.password)?
+            };
+            submit_key_share(client, pair, &sealer.id, vote_id, &sk, &pk).await
+        }
+        Task::Decryption { topic, .. } => {
+            let (sk, pk) = unseal_key(&KeystoreEntry::read(&path)?, &sealer.password, &params)?;
+            let topic_id = topic.as_bytes().to_vec();
+            submit_decryption(client, pair, &sealer.id, vote_id, topic_id, &sk, &pk).await
+        }
+    }
+}
+
+/// Submits the sealer's public key shares and partial decryptions of all votes.
+///
+/// The state of the unfinished votes is checked on every finalized block. A fresh key is created
+/// once a vote is in the `KeyGeneration` phase, a question is partially decrypted once
+/// the vote is in the `Tallying` phase and the last shuffle is completed.
+/// The completed submissions are persisted, the daemon can be restarted at any time.
+/// Failed submissions are retried with a backoff and given up after `MAX_ATTEMPTS`.
+pub async fn daemon(config: Config, sealer: String, dir: String) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+
+    let password = keystore::password()?;
+    let (pair, id) = get_sealer(&dir, &sealer, &password)?;
+    let account = PairSigner::<NodeTemplateRuntime, Pair>::new(pair.clone())
+        .account_id()
+        .clone();
+    let sealer = Sealer {
+        name: sealer,
+        pair,
+        id,
+        dir,
+        password,
+    };
+    let sealers = client.fetch_or_default(&SealersStore {}, None).await?;
+    if !sealers.contains(&account) {
+        println!("{} is not a sealer (yet), waiting...", sealer.name);
+    }
+
+    fs::create_dir_all(&sealer.dir)
+        .map_err(|err| Error::Other(format!("failed to create {}: {}", sealer.dir, err)))?;
+    let path = progress_path(&sealer.dir, &sealer.name);
+    let mut progress = Progress::load(&path)?;
+    println!(
+        "progress of: {} is stored in: {}",
+        sealer.name,
+        path.display()
+    );
+
+    let mut retries = Retries::default();
+    let mut blocks = client.subscribe_finalized_blocks().await?;
+    loop {
+        let header = blocks.next().await;
+        let votes = match fetch_votes(&client, &account, &progress, Some(header.hash())).await {
+            Ok(votes) => votes,
+            Err(err) => {
+                eprintln!(
+                    "failed to fetch the votes at block {}: {:?}",
+                    header.number, err
+                );
+                continue;
+            }
+        };
+
+        let before = progress.clone();
+        let tasks = pending(&mut progress, &votes);
+        if progress != before {
+            progress.save(&path)?;
+        }
+
+        for task in tasks {
+            if !retries.is_due(&task, header.number) {
+                continue;
+            }
+            println!("submitting {}...", task);
+            match submit(&client, &sealer, &task).await {
+                Ok(_) => {
+                    retries.succeed(&task);
+                    progress.complete(&task);
+                    progress.save(&path)?;
+                    println!("successfully submitted {}", task);
+                }
+                Err(err) => {
+                    eprintln!("failed to submit {}: {:?}", task, err);
+                    if retries.fail(&task, header.number) {
+                        eprintln!("giving up {} until the daemon is restarted", task);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(phase: VotePhase, key_share: bool, topics: Vec<(&str, bool, bool)>) -> VoteState {
+        VoteState {
+            vote: "vote".to_string(),
+            phase,
+            key_share,
+            topics: topics
+                .into_iter()
+                .map(|(topic, shuffled, decrypted)| TopicState {
+                    topic: topic.to_string(),
+                    shuffled,
+                    decrypted,
+                })
+                .collect(),
+        }
+    }
+
+    fn key_share() -> Task {
+        Task::KeyShare {
+            vote: "vote".to_string(),
+        }
+    }
+
+    fn decryption(topic: &str) -> Task {
+        Task::Decryption {
+            vote: "vote".to_string(),
+            topic: topic.to_string(),
+        }
+    }
+
+    #[test]
+    fn it_should_submit_the_key_share_during_the_key_generation() {
+        let mut progress = Progress::default();
+        let votes = vec![vote(
+            VotePhase::KeyGeneration,
+            false,
+            vec![("q1", false, false)],
+        )];
+        assert_eq!(pending(&mut progress, &votes), vec![key_share()]);
+
+        // a submitted key share is not submitted again, even if it is not finalized yet
+        progress.complete(&key_share());
+        assert!(pending(&mut progress, &votes).is_empty());
+
+        // the key share is never submitted after the key generation
+        let mut progress = Progress::default();
+        let votes = vec![vote(VotePhase::Voting, false, vec![])];
+        assert!(pending(&mut progress, &votes).is_empty());
+    }
+
+    #[test]
+    fn it_should_decrypt_the_shuffled_questions_while_tallying() {
+        let mut progress = Progress::default();
+        let topics = vec![
+            ("q1", true, false),
+            ("q2", false, false),
+            ("q3", true, true),
+        ];
+        let votes = vec![vote(VotePhase::Tallying, true, topics)];
+        assert_eq!(pending(&mut progress, &votes), vec![decryption("q1")]);
+
+        // the submissions stored on chain are recorded
+        assert!(progress.is_completed(&key_share()));
+        assert!(progress.is_completed(&decryption("q3")));
+        assert!(!progress.is_completed(&decryption("q1")));
+
+        let votes = vec![vote(VotePhase::Voting, true, vec![("q1", true, false)])];
+        assert!(pending(&mut Progress::default(), &votes).is_empty());
+    }
+
+    #[test]
+    fn it_should_finish_the_votes_without_pending_submissions() {
+        let mut progress = Progress::default();
+        let votes = vec![vote(VotePhase::Tallying, true, vec![("q1", true, false)])];
+        pending(&mut progress, &votes);
+        assert!(!progress.is_finished("vote"));
+
+        let votes = vec![vote(VotePhase::Tallying, true, vec![("q1", true, true)])];
+        pending(&mut progress, &votes);
+        assert!(progress.is_finished("vote"));
+
+        // a sealer which missed the key generation has nothing to decrypt
+        let mut progress = Progress::default();
+        let votes = vec![vote(VotePhase::Voting, false, vec![])];
+        pending(&mut progress, &votes);
+        assert!(progress.is_finished("vote"));
+
+        let mut progress = Progress::default();
+        let votes = vec![vote(VotePhase::Voting, true, vec![])];
+        pending(&mut progress, &votes);
+        assert!(!progress.is_finished("vote"));
+    }
+
+    #[test]
+    fn it_should_back_off_and_give_up_failing_submissions() {
+        let mut retries = Retries::default();
+        let task = key_share();
+        assert!(retries.is_due(&task, 10));
+
+        assert!(!retries.fail(&task, 10));
+        assert!(!retries.is_due(&task, 11));
+        assert!(retries.is_due(&task, 12));
+        assert!(!retries.fail(&task, 12));
+        assert!(!retries.is_due(&task, 15));
+        assert!(retries.is_due(&task, 16));
+
+        // other submissions are not affected
+        assert!(retries.is_due(&decryption("q1"), 11));
+
+        retries.succeed(&task);
+        assert!(retries.is_due(&task, 16));
+
+        for block in 0..MAX_ATTEMPTS - 1 {
+            assert!(!retries.fail(&task, block));
+        }
+        assert!(retries.fail(&task, MAX_ATTEMPTS));
+        assert!(!retries.is_due(&task, u32::MAX));
+    }
+
+    #[test]
+    fn it_should_persist_the_progress() {
+        let dir = std::env::temp_dir().join(format!("provotum-daemon-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bob.progress.json");
+        assert_eq!(Progress::load(&path).unwrap(), Progress::default());
+
+        let mut progress = Progress::default();
+        progress.complete(&key_share());
+        progress.complete(&decryption("q1"));
+        progress.save(&path).unwrap();
+        progress.complete(&decryption("q2"));
+        progress.save(&path).unwrap();
+        assert_eq!(Progress::load(&path).unwrap(), progress);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
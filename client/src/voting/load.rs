@@ -0,0 +1,547 @@
+use crate::config::Config;
+use crate::output::{to_json, Output, Rows};
+use crate::voting::voter::{authenticate, get_options, randomize_answers};
+use async_std::task;
+use crypto::types::PublicKey as ElGamalPK;
+use pallet_mixnet::types::{
+    AnswerValue, Ballot, RandomizerSignature, Topic, TopicAnswer, TopicId, VoteId, VotePhase,
+};
+use provotum_sdk::rpc::{cast_ballot, get_credential_key, get_vote, get_vote_public_key};
+use provotum_sdk::stores::BallotStore;
+use rand::Rng;
+use serde::Serialize;
+use sp_keyring::sr25519::sr25519::Pair;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+use substrate_subxt::{
+    sp_core::Pair as KeyPairGenerator,
+    system::{AccountStoreExt, System},
+    Client, Signer,
+};
+use substrate_subxt::{Error, NodeTemplateRuntime, PairSigner};
+
+/// the delay before the first retry, doubled on each further retry
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The step of casting a ballot which failed.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+enum Stage {
+    Authentication,
+    Randomization,
+    Inclusion,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Outcome {
+    Included {
+        randomization: Duration,
+        inclusion: Duration,
+        attempts: u32,
+    },
+    Failed {
+        stage: Stage,
+        error: String,
+    },
+}
+
+/// The state shared by all workers.
+struct Context {
+    config: Config,
+    client: Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    pk: ElGamalPK,
+    options: Vec<(Topic, Vec<TopicAnswer>)>,
+    offset: usize,
+    retries: u32,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct Latency {
+    p50_ms: u128,
+    p90_ms: u128,
+    p99_ms: u128,
+    max_ms: u128,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct Failure {
+    stage: Stage,
+    error: String,
+    count: usize,
+}
+
+/// The summary of a load run.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct LoadReport {
+    vote: String,
+    voters: usize,
+    concurrency: usize,
+    included: usize,
+    failed: usize,
+    // the number of included ballots which required more than one attempt
+    retried: usize,
+    duration_ms: u128,
+    // included ballots per second
+    throughput: f64,
+    // the time to encrypt and randomize a ballot, including the authentication
+    randomization: Option<Latency>,
+    // the time from the submission until the ballot is included in a block
+    inclusion: Option<Latency>,
+    failures: Vec<Failure>,
+}
+
+/// Returns the percentile of the sorted durations (nearest rank).
+fn percentile(sorted: &[Duration], percentile: usize) -> Duration {
+    if sorted.is_empty() {
+        return Duration::default();
+    }
+    let rank = (percentile as f64 * sorted.len() as f64 / 100.0).ceil() as usize;
+    sorted[rank.max(1).min(sorted.len()) - 1]
+}
+
+fn latency(mut durations: Vec<Duration>) -> Option<Latency> {
+    if durations.is_empty() {
+        return None;
+    }
+    durations.sort();
+    Some(Latency {
+        p50_ms: percentile(&durations, 50).as_millis(),
+        p90_ms: percentile(&durations, 90).as_millis(),
+        p99_ms: percentile(&durations, 99).as_millis(),
+        max_ms: durations[durations.len() - 1].as_millis(),
+    })
+}
+
+impl LoadReport {
+    fn new(vote: String, concurrency: usize, outcomes: &[Outcome], duration: Duration) -> Self {
+        let mut randomization = Vec::new();
+        let mut inclusion = Vec::new();
+        let mut retried = 0;
+        let mut failures: BTreeMap<(Stage, String), usize> = BTreeMap::new();
+        for outcome in outcomes.iter() {
+            match outcome {
+                Outcome::Included {
+                    randomization: randomized,
+                    inclusion: included,
+                    attempts,
+                } => {
+                    randomization.push(*randomized);
+                    inclusion.push(*included);
+                    if *attempts > 1 {
+                        retried += 1;
+                    }
+                }
+                Outcome::Failed { stage, error } => {
+                    *failures.entry((*stage, error.clone())).or_default() += 1;
+                }
+            }
+        }
+        let included = inclusion.len();
+        let seconds = duration.as_secs_f64();
+        LoadReport {
+            vote,
+            voters: outcomes.len(),
+            concurrency,
+            included,
+            failed: outcomes.len() - included,
+            retried,
+            duration_ms: duration.as_millis(),
+            throughput: if seconds > 0.0 {
+                included as f64 / seconds
+            } else {
+                0.0
+            },
+            randomization: latency(randomization),
+            inclusion: latency(inclusion),
+            failures: failures
+                .into_iter()
+                .map(|((stage, error), count)| Failure {
+                    stage,
+                    error,
+                    count,
+                })
+                .collect(),
+        }
+    }
+
+    fn render(&self, output: Output) -> Result<String, Error> {
+        if output == Output::Json {
+            return to_json(self);
+        }
+        let mut rows = vec![
+            ("vote", self.vote.clone()),
+            ("voters", self.voters.to_string()),
+            ("concurrency", self.concurrency.to_string()),
+            ("included", self.included.to_string()),
+            ("failed", self.failed.to_string()),
+            ("retried", self.retried.to_string()),
+            ("duration_ms", self.duration_ms.to_string()),
+            ("throughput", format!("{:.2}", self.throughput)),
+        ];
+        for (name, latency) in [
+            ("randomization", &self.randomization),
+            ("inclusion", &self.inclusion),
+        ]
+        .iter()
+        {
+            if let Some(latency) = latency {
+                rows.push((name, format!("p50 {} ms", latency.p50_ms)));
+                rows.push((name, format!("p90 {} ms", latency.p90_ms)));
+                rows.push((name, format!("p99 {} ms", latency.p99_ms)));
+                rows.push((name, format!("max {} ms", latency.max_ms)));
+            }
+        }
+        let mut rows = Rows {
+            headers: vec!["metric", "value"],
+            rows: rows
+                .into_iter()
+                .map(|(name, value)| vec![name.to_string(), value])
+                .collect(),
+        };
+        for failure in self.failures.iter() {
+            rows.rows.push(vec![
+                "failure".to_string(),
+                format!(
+                    "{:?} ({}x): {}",
+                    failure.stage, failure.count, failure.error
+                ),
+            ]);
+        }
+        Ok(match output {
+            Output::Csv => rows.csv(),
+            _ => rows.table(),
+        })
+    }
+}
+
+/// Runs the future until it succeeds or the retries are used up.
+/// Returns the last error and the number of attempts.
+async fn retry<T, E, F, Fut>(retries: u32, mut attempt: F) -> (Result<T, String>, u32)
+where
+    E: Display,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut delay = RETRY_DELAY;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        match attempt().await {
+            Ok(value) => return (Ok(value), attempts),
+            Err(err) if attempts > retries => return (Err(err.to_string()), attempts),
+            Err(_) => {
+                task::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+}
+
+/// Picks a random answer for each question.
+fn random_choices(options: &[(Topic, Vec<TopicAnswer>)]) -> Vec<(TopicId, AnswerValue)> {
+    let mut rng = rand::thread_rng();
+    options
+        .iter()
+        .map(|((topic_id, _), answers)| {
+            let (value, _) = &answers[rng.gen_range(0, answers.len())];
+            (topic_id.clone(), *value)
+        })
+        .collect()
+}
+
+async fn is_ballot_stored(
+    context: &Context,
+    voter: &<NodeTemplateRuntime as System>::AccountId,
+    ballot: &Ballot,
+) -> bool {
+    let store = BallotStore {
+        vote_id: context.vote_id.clone(),
+        voter: voter.clone(),
+    };
+    match context.client.fetch(&store, None).await {
+        Ok(stored) => stored.as_ref() == Some(ballot),
+        Err(_) => false,
+    }
+}
+
+/// Submits the ballot and waits until it is included in a block.
+///
+/// The nonce is read from the chain before each attempt. An attempt can fail even though the
+/// ballot has been included (e.g. a lost connection), the ballot is never submitted again then.
+async fn include(
+    context: &Context,
+    voter: Pair,
+    ballot: Ballot,
+    signatures: Vec<RandomizerSignature>,
+) -> (Result<(), String>, u32) {
+    let mut signer = PairSigner::<NodeTemplateRuntime, Pair>::new(voter);
+    let account_id = signer.account_id().clone();
+    let mut delay = RETRY_DELAY;
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let error = match context.client.account(&account_id, None).await {
+            Ok(account) => {
+                signer.set_nonce(account.nonce);
+                let submission = cast_ballot(
+                    &context.client,
+                    &signer,
+                    context.vote_id.clone(),
+                    ballot.clone(),
+                    signatures.clone(),
+                )
+                .await;
+                match submission {
+                    Ok(_) => return (Ok(()), attempts),
+                    // the ballot has been rejected by the runtime, e.g. the voter is not eligible
+                    Err(err @ Error::Runtime(_)) => return (Err(err.to_string()), attempts),
+                    Err(err) => err.to_string(),
+                }
+            }
+            Err(err) => err.to_string(),
+        };
+        if is_ballot_stored(context, &account_id, &ballot).await {
+            return (Ok(()), attempts);
+        }
+        if attempts > context.retries {
+            return (Err(error), attempts);
+        }
+        task::sleep(delay).await;
+        delay *= 2;
+    }
+}
+
+/// Casts the ballot of the development voter `//<offset + index>`.
+async fn cast_random_ballot(context: &Context, index: usize) -> Outcome {
+    let voter: Pair =
+        match KeyPairGenerator::from_string(&format!("//{}", context.offset + index), None) {
+            Ok(voter) => voter,
+            Err(_) => {
+                return Outcome::Failed {
+                    stage: Stage::Authentication,
+                    error: "invalid voter".to_string(),
+                }
+            }
+        };
+    let voter_id = PairSigner::<NodeTemplateRuntime, Pair>::new(voter.clone())
+        .account_id()
+        .clone();
+
+    let started = Instant::now();
+    let (token, _) = retry(context.retries, || {
+        authenticate(&context.config, &context.vote_id, &voter)
+    })
+    .await;
+    let token = match token {
+        Ok(token) => token,
+        Err(error) => {
+            return Outcome::Failed {
+                stage: Stage::Authentication,
+                error,
+            }
+        }
+    };
+    let choices = random_choices(&context.options);
+    let (randomized, _) = retry(context.retries, || {
+        randomize_answers(
+            &context.config,
+            &context.pk,
+            &context.vote_id,
+            &voter_id,
+            &token,
+            choices.clone(),
+        )
+    })
+    .await;
+    let (ballot, signatures) = match randomized {
+        Ok(randomized) => randomized,
+        Err(error) => {
+            return Outcome::Failed {
+                stage: Stage::Randomization,
+                error,
+            }
+        }
+    };
+    let randomization = started.elapsed();
+
+    let submitted = Instant::now();
+    match include(context, voter, ballot, signatures).await {
+        (Ok(()), attempts) => Outcome::Included {
+            randomization,
+            inclusion: submitted.elapsed(),
+            attempts,
+        },
+        (Err(error), _) => Outcome::Failed {
+            stage: Stage::Inclusion,
+            error,
+        },
+    }
+}
+
+/// Casts the ballots of `voters` development voters concurrently, at most `concurrency` at once.
+/// Each ballot answers all questions of the vote randomly.
+pub async fn load(
+    config: Config,
+    vote: String,
+    voters: usize,
+    concurrency: usize,
+    offset: usize,
+    retries: u32,
+    output: Output,
+) -> Result<(), Error> {
+    if voters == 0 || concurrency == 0 {
+        return Err("the number of voters and the concurrency must be greater than zero".into());
+    }
+    let client = config.client().await?;
+    let vote_id = vote.as_bytes().to_vec();
+    let details = get_vote(&client, vote_id.clone())
+        .await?
+        .ok_or("the vote does not exist!")?;
+    if details.phase != VotePhase::Voting {
+        return Err(Error::Other(format!(
+            "the vote is in phase: {:?}",
+            details.phase
+        )));
+    }
+    // the development voters are not eligible for voter tokens
+    if get_credential_key(&client, vote_id.clone())
+        .await?
+        .is_some()
+    {
+        return Err("the vote only accepts anonymous ballots, use voter cast".into());
+    }
+    let options = get_options(&client, &vote_id).await?;
+    if options.is_empty() || options.iter().any(|(_, answers)| answers.is_empty()) {
+        return Err("all questions of the vote require answers".into());
+    }
+    let pk: ElGamalPK = get_vote_public_key(&client, vote_id.clone()).await?.into();
+
+    let context = Arc::new(Context {
+        config,
+        client,
+        vote_id,
+        pk,
+        options,
+        offset,
+        retries,
+    });
+    let next = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+
+    // each worker casts the ballot of the next voter until all voters are done
+    let workers: Vec<_> = (0..concurrency.min(voters))
+        .map(|_| {
+            let context = context.clone();
+            let next = next.clone();
+            task::spawn(async move {
+                let mut outcomes = Vec::new();
+                loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    if index >= voters {
+                        return outcomes;
+                    }
+                    let outcome = cast_random_ballot(&context, index).await;
+                    if let Outcome::Failed { stage, error } = &outcome {
+                        eprintln!("voter //{}: {:?} failed: {}", offset + index, stage, error);
+                    }
+                    outcomes.push(outcome);
+                }
+            })
+        })
+        .collect();
+    let mut outcomes = Vec::with_capacity(voters);
+    for worker in workers {
+        outcomes.extend(worker.await);
+    }
+
+    let report = LoadReport::new(vote, concurrency, &outcomes, started.elapsed());
+    println!("{}", report.render(output)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn it_should_compute_percentiles() {
+        let durations: Vec<Duration> = (1..=100).map(ms).collect();
+        assert_eq!(percentile(&durations, 50), ms(50));
+        assert_eq!(percentile(&durations, 99), ms(99));
+        assert_eq!(percentile(&[ms(7)], 90), ms(7));
+        assert_eq!(percentile(&[ms(1), ms(2), ms(3)], 50), ms(2));
+        assert_eq!(percentile(&[], 50), ms(0));
+    }
+
+    #[test]
+    fn it_should_summarize_the_outcomes() {
+        let included = |inclusion: u64, attempts: u32| Outcome::Included {
+            randomization: ms(10),
+            inclusion: ms(inclusion),
+            attempts,
+        };
+        let failed = |stage: Stage| Outcome::Failed {
+            stage,
+            error: "timeout".to_string(),
+        };
+        let outcomes = vec![
+            included(4000, 1),
+            included(6000, 2),
+            failed(Stage::Inclusion),
+            failed(Stage::Inclusion),
+            failed(Stage::Randomization),
+        ];
+        let report = LoadReport::new("vote".to_string(), 2, &outcomes, ms(4000));
+        assert_eq!(report.included, 2);
+        assert_eq!(report.failed, 3);
+        assert_eq!(report.retried, 1);
+        assert!((report.throughput - 0.5).abs() < f64::EPSILON);
+        assert_eq!(report.inclusion.as_ref().unwrap().p50_ms, 4000);
+        assert_eq!(report.inclusion.as_ref().unwrap().max_ms, 6000);
+        assert_eq!(
+            report.failures,
+            vec![
+                Failure {
+                    stage: Stage::Randomization,
+                    error: "timeout".to_string(),
+                    count: 1,
+                },
+                Failure {
+                    stage: Stage::Inclusion,
+                    error: "timeout".to_string(),
+                    count: 2,
+                },
+            ]
+        );
+        assert!(report
+            .render(Output::Table)
+            .unwrap()
+            .contains("Inclusion (2x): timeout"));
+    }
+
+    #[test]
+    fn it_should_retry_until_the_retries_are_used_up() {
+        let mut calls = 0;
+        let (result, attempts) = task::block_on(retry(2, || {
+            calls += 1;
+            let result: Result<(), &str> = if calls < 3 { Err("busy") } else { Ok(()) };
+            async move { result }
+        }));
+        assert_eq!((result, attempts), (Ok(()), 3));
+
+        let (result, attempts) =
+            task::block_on(retry(1, || async { Err::<(), &str>("unavailable") }));
+        assert_eq!((result, attempts), (Err("unavailable".to_string()), 2));
+    }
+}
@@ -0,0 +1,456 @@
+use crate::config::Config;
+use crate::voting::voter::get_options;
+use codec::{Compact, Decode, Encode};
+use crypto::types::{Cipher as BigCipher, ElGamalParams, PublicKey as ElGamalPK};
+use pallet_mixnet::types::{Topic, TopicAnswer, VoteId, Wrapper};
+use provotum_sdk::calls::{CastBallot, SubmitPartialDecryption};
+use provotum_sdk::rpc::{get_public_key, get_vote};
+use provotum_sdk::stores::{CiphersStore, CredentialKeyStore, RandomizerSignatureRequiredStore};
+use provotum_sdk::NR_OF_SHUFFLES;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sp_keyring::sr25519::sr25519::Pair;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use substrate_subxt::{
+    extrinsic::{create_signed, Extra},
+    sp_core::crypto::Ss58Codec,
+    system::{AccountStoreExt, System},
+    Call, Encoded, EventsDecoder, Runtime, RuntimeVersion, UncheckedExtrinsic,
+};
+use substrate_subxt::{Error, NodeTemplateRuntime, PairSigner, Signer};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+type Hash = <NodeTemplateRuntime as System>::Hash;
+type Index = <NodeTemplateRuntime as System>::Index;
+
+/// the version byte of a signed extrinsic (format version 4)
+const SIGNED_EXTRINSIC_VERSION: u8 = 0b1000_0100;
+
+/// The indices of the pallet and the call prefixing an encoded call.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallIndex {
+    pub module: u8,
+    pub call: u8,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallIndices {
+    pub cast_ballot: CallIndex,
+    pub submit_decrypted_shares: CallIndex,
+}
+
+/// A question of the vote, its answers and the ciphers to decrypt (once shuffled).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QuestionExport {
+    pub id: String,
+    pub question: String,
+    pub answers: Vec<(u32, String)>,
+    pub ciphers: Vec<BigCipher>,
+}
+
+/// Everything required to sign the extrinsics of a vote on a host without network access.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VoteExport {
+    pub vote: String,
+    pub genesis_hash: Hash,
+    pub runtime_version: RuntimeVersion,
+    pub calls: CallIndices,
+    pub params: ElGamalParams,
+    // the combined public key, once the key generation is completed
+    pub public_key: Option<ElGamalPK>,
+    pub randomizer_signature_required: bool,
+    pub voter_token_required: bool,
+    pub questions: Vec<QuestionExport>,
+    // the nonces of the accounts (ss58) at the time of the export
+    pub nonces: BTreeMap<String, Index>,
+}
+
+impl VoteExport {
+    pub fn vote_id(&self) -> VoteId {
+        self.vote.as_bytes().to_vec()
+    }
+
+    /// The questions and their answers as stored on chain.
+    pub fn options(&self) -> Vec<(Topic, Vec<TopicAnswer>)> {
+        self.questions
+            .iter()
+            .map(|question| {
+                let topic = (
+                    question.id.as_bytes().to_vec(),
+                    question.question.as_bytes().to_vec(),
+                );
+                let answers = question
+                    .answers
+                    .iter()
+                    .map(|(value, label)| (*value, label.as_bytes().to_vec()))
+                    .collect();
+                (topic, answers)
+            })
+            .collect()
+    }
+
+    pub fn question(&self, question: &str) -> Result<&QuestionExport, Error> {
+        self.questions
+            .iter()
+            .find(|exported| exported.id == question)
+            .ok_or_else(|| Error::Other(format!("unknown question: {}", question)))
+    }
+
+    /// The nonce passed explicitly, otherwise the exported nonce of the account.
+    pub fn nonce(&self, account: &AccountId, nonce: Option<Index>) -> Result<Index, Error> {
+        let ss58 = account.to_ss58check();
+        nonce
+            .or_else(|| self.nonces.get(&ss58).copied())
+            .ok_or_else(|| {
+                Error::Other(format!(
+                    "the nonce of: {} is not exported, use --nonce",
+                    ss58
+                ))
+            })
+    }
+}
+
+/// A signed extrinsic, created offline and submitted by `broadcast`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SignedExtrinsic {
+    pub vote: String,
+    pub call: String,
+    pub signer: String,
+    pub nonce: Index,
+    // the SCALE encoded extrinsic (hex)
+    pub extrinsic: String,
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T, Error> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| Error::Other(format!("failed to read {}: {}", path.display(), err)))?;
+    serde_json::from_str(&content)
+        .map_err(|err| Error::Other(format!("invalid file: {}: {}", path.display(), err)))
+}
+
+/// Writes the value to the file, existing files are never overwritten.
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    if path.exists() {
+        return Err(Error::Other(format!("{} already exists", path.display())));
+    }
+    let content =
+        serde_json::to_string_pretty(value).map_err(|err| Error::Other(err.to_string()))?;
+    fs::write(path, content)
+        .map_err(|err| Error::Other(format!("failed to write {}: {}", path.display(), err)))
+}
+
+pub fn read_export(path: &str) -> Result<VoteExport, Error> {
+    read_json(Path::new(path))
+}
+
+fn utf8(bytes: Vec<u8>) -> Result<String, Error> {
+    String::from_utf8(bytes).map_err(|_| Error::Other("invalid utf-8 on chain".to_string()))
+}
+
+fn call_index<C: Call<NodeTemplateRuntime>>(
+    metadata: &substrate_subxt::Metadata,
+) -> Result<CallIndex, Error> {
+    // the encoded call without arguments consists of the indices only
+    let encoded = metadata
+        .module_with_calls(C::MODULE)?
+        .call(C::FUNCTION, ())?;
+    Ok(CallIndex {
+        module: encoded.0[0],
+        call: encoded.0[1],
+    })
+}
+
+/// Fetches the public parameters of the vote and the chain state required to sign
+/// its extrinsics offline and writes them to the file.
+pub async fn export(
+    config: Config,
+    vote: String,
+    accounts: Vec<String>,
+    file: String,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+
+    let vote_id = vote.as_bytes().to_vec();
+    let details = get_vote(&client, vote_id.clone())
+        .await?
+        .ok_or("the vote does not exist!")?;
+    let public_key = get_public_key(&client, vote_id.clone())
+        .await?
+        .map(|pk| pk.into());
+
+    // the ciphers are only decrypted after the last shuffle
+    let mut questions = Vec::new();
+    for ((topic_id, question), answers) in get_options(&client, &vote_id).await? {
+        let store = CiphersStore {
+            topic_id: topic_id.clone(),
+            nr_of_shuffles: NR_OF_SHUFFLES,
+        };
+        let ciphers = client.fetch_or_default(&store, None).await?;
+        let mut labels = Vec::with_capacity(answers.len());
+        for (value, label) in answers {
+            labels.push((value, utf8(label)?));
+        }
+        questions.push(QuestionExport {
+            id: utf8(topic_id)?,
+            question: utf8(question)?,
+            answers: labels,
+            ciphers: Wrapper(ciphers).into(),
+        });
+    }
+
+    let mut nonces = BTreeMap::new();
+    for account in accounts {
+        let account_id = AccountId::from_ss58check(&account)
+            .map_err(|err| Error::Other(format!("invalid account: {:?}", err)))?;
+        let info = client.account(&account_id, None).await?;
+        nonces.insert(account_id.to_ss58check(), info.nonce);
+    }
+
+    // the runtime version is not exposed by the client
+    let rpc = jsonrpsee::ws_client(&config.node_url).await?;
+    let runtime_version: RuntimeVersion = rpc
+        .request("state_getRuntimeVersion", jsonrpsee::common::Params::None)
+        .await?;
+
+    let store = RandomizerSignatureRequiredStore {
+        vote_id: vote_id.clone(),
+    };
+    let randomizer_signature_required = client.fetch_or_default(&store, None).await?;
+    let store = CredentialKeyStore { vote_id };
+    let voter_token_required = client.fetch(&store, None).await?.is_some();
+
+    let metadata = client.metadata();
+    let export = VoteExport {
+        vote,
+        genesis_hash: *client.genesis(),
+        runtime_version,
+        calls: CallIndices {
+            cast_ballot: call_index::<CastBallot>(metadata)?,
+            submit_decrypted_shares: call_index::<SubmitPartialDecryption>(metadata)?,
+        },
+        params: details.params.into(),
+        public_key,
+        randomizer_signature_required,
+        voter_token_required,
+        questions,
+        nonces,
+    };
+    write_json(Path::new(&file), &export)?;
+    println!("vote exported to: {}", file);
+    Ok(())
+}
+
+/// Signs a call using the exported genesis hash and runtime version, no connection is required.
+/// The extrinsic is immortal, it is only valid for the given nonce of the signer.
+pub async fn sign<A: Encode>(
+    export: &VoteExport,
+    name: &str,
+    index: CallIndex,
+    args: A,
+    signer: Pair,
+    nonce: Option<Index>,
+) -> Result<SignedExtrinsic, Error> {
+    let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(signer);
+    let nonce = export.nonce(signer.account_id(), nonce)?;
+    let mut call = vec![index.module, index.call];
+    call.extend(args.encode());
+
+    let extrinsic = create_signed::<NodeTemplateRuntime>(
+        &export.runtime_version,
+        export.genesis_hash,
+        nonce,
+        Encoded(call),
+        &signer,
+    )
+    .await?;
+    Ok(SignedExtrinsic {
+        vote: export.vote.clone(),
+        call: name.to_string(),
+        signer: signer.account_id().to_ss58check(),
+        nonce,
+        extrinsic: hex::encode(extrinsic.encode()),
+    })
+}
+
+pub fn write_signed(file: &str, signed: &SignedExtrinsic) -> Result<(), Error> {
+    write_json(Path::new(file), signed)?;
+    println!(
+        "signed {} of: {} (nonce: {}) written to: {}",
+        signed.call, signed.signer, signed.nonce, file
+    );
+    Ok(())
+}
+
+/// Decodes a signed extrinsic, the call is kept encoded.
+pub fn decode_extrinsic(bytes: &[u8]) -> Result<UncheckedExtrinsic<NodeTemplateRuntime>, Error> {
+    let invalid = || Error::Other("invalid signed extrinsic".to_string());
+    let input = &mut &bytes[..];
+    let length = Compact::<u32>::decode(input).map_err(|_| invalid())?;
+    if length.0 as usize != input.len() {
+        return Err(invalid());
+    }
+    if u8::decode(input).map_err(|_| invalid())? != SIGNED_EXTRINSIC_VERSION {
+        return Err(Error::Other("the extrinsic is not signed".to_string()));
+    }
+    let signature = <(
+        <NodeTemplateRuntime as System>::Address,
+        <NodeTemplateRuntime as Runtime>::Signature,
+        Extra<NodeTemplateRuntime>,
+    )>::decode(input)
+    .map_err(|_| invalid())?;
+    if input.len() < 2 {
+        return Err(invalid());
+    }
+    Ok(UncheckedExtrinsic::<NodeTemplateRuntime> {
+        signature: Some(signature),
+        function: Encoded(input.to_vec()),
+    })
+}
+
+/// Submits an extrinsic signed offline and waits for its inclusion.
+pub async fn broadcast(config: Config, file: String) -> Result<(), Error> {
+    let signed: SignedExtrinsic = read_json(Path::new(&file))?;
+    let bytes = hex::decode(&signed.extrinsic)
+        .map_err(|_| Error::Other("the extrinsic is not hex encoded".to_string()))?;
+    let extrinsic = decode_extrinsic(&bytes)?;
+    println!(
+        "broadcasting {} of: {} (nonce: {})",
+        signed.call, signed.signer, signed.nonce
+    );
+
+    // init substrate client
+    let client = config.client().await?;
+    let mut decoder = EventsDecoder::<NodeTemplateRuntime>::new(client.metadata().clone());
+    CastBallot::events_decoder(&mut decoder);
+    SubmitPartialDecryption::events_decoder(&mut decoder);
+    let response = client
+        .submit_and_watch_extrinsic(extrinsic, decoder)
+        .await?;
+    println!("extrinsic included in block: {:?}", response.block);
+    for event in response.events.iter() {
+        println!("event: {}::{}", event.module, event.variant);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::helper::Helper;
+    use substrate_subxt::{
+        sp_core::{Pair as KeyPairGenerator, H256},
+        sp_runtime::traits::Verify,
+    };
+
+    fn vote_export() -> VoteExport {
+        let (params, _, pk) = Helper::setup_md_system();
+        let runtime_version = RuntimeVersion {
+            spec_version: 1,
+            transaction_version: 1,
+            ..Default::default()
+        };
+        VoteExport {
+            vote: "vote".to_string(),
+            genesis_hash: H256::repeat_byte(7),
+            runtime_version,
+            calls: CallIndices {
+                cast_ballot: CallIndex { module: 8, call: 9 },
+                submit_decrypted_shares: CallIndex {
+                    module: 8,
+                    call: 14,
+                },
+            },
+            params,
+            public_key: Some(pk),
+            randomizer_signature_required: false,
+            voter_token_required: false,
+            questions: vec![QuestionExport {
+                id: "q1".to_string(),
+                question: "Question 1?".to_string(),
+                answers: vec![(0, "no".to_string()), (1, "yes".to_string())],
+                ciphers: vec![],
+            }],
+            nonces: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn it_should_sign_offline_and_decode_the_extrinsic() {
+        let export = vote_export();
+        let pair: Pair = KeyPairGenerator::from_string("//Voter", None).unwrap();
+        let args = (export.vote_id(), 42u32);
+        let signed = async_std::task::block_on(sign(
+            &export,
+            "cast_ballot",
+            export.calls.cast_ballot,
+            args.clone(),
+            pair.clone(),
+            Some(3),
+        ))
+        .unwrap();
+        assert_eq!(signed.nonce, 3);
+
+        // the extrinsic is encoded unchanged
+        let bytes = hex::decode(&signed.extrinsic).unwrap();
+        let extrinsic = decode_extrinsic(&bytes).unwrap();
+        assert_eq!(extrinsic.encode(), bytes);
+        let mut call = vec![8, 9];
+        call.extend(args.encode());
+        assert_eq!(extrinsic.function.0, call);
+
+        // the signature covers the call, the nonce and the exported chain state
+        let (address, signature, extra) = extrinsic.signature.unwrap();
+        let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(pair)
+            .account_id()
+            .clone();
+        assert_eq!(address, signer);
+        assert_eq!(signer.to_ss58check(), signed.signer);
+        let payload = (
+            Encoded(call),
+            extra.clone(),
+            (
+                1u32,
+                1u32,
+                export.genesis_hash,
+                export.genesis_hash,
+                (),
+                (),
+                (),
+            ),
+        );
+        assert!(signature.verify(&payload.encode()[..], &signer));
+
+        // truncated or unsigned extrinsics are refused
+        assert!(decode_extrinsic(&bytes[..bytes.len() - 1]).is_err());
+        assert!(decode_extrinsic(&[4, 4, 8, 9]).is_err());
+    }
+
+    #[test]
+    fn it_should_require_a_nonce() {
+        let mut export = vote_export();
+        let pair: Pair = KeyPairGenerator::from_string("//Voter", None).unwrap();
+        let account = PairSigner::<NodeTemplateRuntime, Pair>::new(pair)
+            .account_id()
+            .clone();
+        assert!(export.nonce(&account, None).is_err());
+        assert_eq!(export.nonce(&account, Some(2)).unwrap(), 2);
+
+        export.nonces.insert(account.to_ss58check(), 5);
+        assert_eq!(export.nonce(&account, None).unwrap(), 5);
+        assert_eq!(export.nonce(&account, Some(2)).unwrap(), 2);
+    }
+
+    #[test]
+    fn it_should_round_trip_the_export() {
+        let export = vote_export();
+        let json = serde_json::to_string(&export).unwrap();
+        let decoded: VoteExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.genesis_hash, export.genesis_hash);
+        assert_eq!(decoded.runtime_version, export.runtime_version);
+        assert_eq!(decoded.public_key, export.public_key);
+        assert_eq!(decoded.options(), export.options());
+        assert!(decoded.question("q2").is_err());
+    }
+}
@@ -3,66 +3,184 @@ use crypto::{
     helper::Helper,
     proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
     random::Random,
-    types::Cipher as BigCipher,
+    types::{Cipher as BigCipher, ElGamalParams, PrivateKey, PublicKey as ElGamalPK},
 };
-use hex_literal::hex;
-use num_bigint::BigUint;
-use pallet_mixnet::types::{Cipher, PublicKeyShare, Wrapper};
-use sp_keyring::{sr25519::sr25519::Pair, AccountKeyring};
-use substrate_subxt::{Client, PairSigner};
-use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime};
-
-use super::substrate::rpc::{get_ciphers, store_public_key_share, submit_partial_decryptions};
-
-async fn init() -> Result<Client<NodeTemplateRuntime>, Error> {
-    env_logger::init();
-    let url = "ws://127.0.0.1:9944";
-    let client = ClientBuilder::<NodeTemplateRuntime>::new()
-        .set_url(url)
-        .build()
-        .await?;
-    Ok(client)
-}
-
-fn get_sealer(sealer: String) -> (Pair, [u8; 32]) {
-    
-    // get the sealer and sealer_id
-    if sealer == "bob" {
-        return (
-            AccountKeyring::Bob.pair(),
-            hex!("8eaf04151687736326c9fea17e25fc5287613693c912909cb226aa4794f26a48").into(),
-        );
-    } else {
-        return (
-            AccountKeyring::Charlie.pair(),
-            hex!("90b5ab205c6974c9ea841be688864633dc9ca8a357843eeacf2314649965fe22").into(),
-        );
-    };
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::Zero;
+use pallet_mixnet::types::{Cipher, DecryptedShare, PublicKeyShare, TopicId, VoteId, Wrapper};
+use rand::rngs::OsRng;
+use sp_keyring::sr25519::sr25519::Pair;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use substrate_subxt::{sp_core::Pair as KeyPairGenerator, Client, PairSigner};
+use substrate_subxt::{Error, NodeTemplateRuntime};
+
+use super::offline::{read_export, sign, write_signed};
+use crate::config::Config;
+use crate::keystore;
+use provotum_sdk::calls::SubmitPartialDecryption;
+use provotum_sdk::keystore::{KeystoreEntry, DEFAULT_LOG_N};
+use provotum_sdk::rpc::{get_ciphers, get_vote, store_public_key_share, submit_partial_decryptions};
+use provotum_sdk::NR_OF_SHUFFLES;
+
+/// the env variable containing the sealer's secret uri or mnemonic, read from stdin if unset
+pub const SECRET_ENV: &str = "PROVOTUM_SEALER_SECRET";
+
+/// the keystore of a sealer's account key: `<dir>/<sealer>.json`
+pub fn account_path(dir: &str, sealer: &str) -> PathBuf {
+    Path::new(dir).join(format!("{}.json", sealer))
 }
 
-pub async fn keygen(vote: String, sk_as_string: String, sealer: String) -> Result<(), Error> {
-    // init substrate client
-    let client = init().await?;
+/// the keystore of a sealer's secret key: `<dir>/<sealer>/<vote>.json`
+pub fn keystore_path(dir: &str, sealer: &str, vote: &str) -> PathBuf {
+    Path::new(dir).join(sealer).join(format!("{}.json", vote))
+}
 
-    // create private and public key
-    let (params, sk, pk) = Helper::setup_lg_system_with_sk(sk_as_string.as_bytes());
+/// Loads the sealer's account key and its id (the sr25519 public key) from the keystore.
+/// Sealers which have not been added to the keystore are refused.
+pub fn get_sealer(dir: &str, sealer: &str, password: &str) -> Result<(Pair, [u8; 32]), Error> {
+    let path = account_path(dir, sealer);
+    if !path.exists() {
+        return Err(Error::Other(format!(
+            "unknown sealer: {}, add it using: sealer add",
+            sealer
+        )));
+    }
+    let entry = KeystoreEntry::read(&path)?;
+    if entry.name != sealer {
+        return Err(Error::Other(format!(
+            "the keystore entry {} does not belong to sealer: {}",
+            path.display(),
+            sealer
+        )));
+    }
+    let pair = entry.decrypt(password)?;
+    let sealer_id = pair.public().0;
+    Ok((pair, sealer_id))
+}
 
-    // get the sealer and sealer_id
-    let (sealer, sealer_id): (Pair, [u8; 32]) = get_sealer(sealer);
+/// Encrypts the sealer's secret uri or mnemonic (read from the env or stdin) and adds it to the keystore.
+pub fn add_sealer(name: String, dir: String) -> Result<(), Error> {
+    let secret = keystore::read_secret(SECRET_ENV)?;
+    let entry = KeystoreEntry::encrypt(&name, &secret, &keystore::password()?, DEFAULT_LOG_N)?;
+    entry.write(Path::new(&dir))?;
+    println!("sealer account: {}", entry.public);
+    Ok(())
+}
+
+pub async fn get_params(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<ElGamalParams, Error> {
+    let vote = get_vote(client, vote_id)
+        .await?
+        .ok_or("the vote does not exist!")?;
+    Ok(vote.params.into())
+}
+
+/// Generates a secret key: 0 < x < q using the randomness of the OS.
+fn generate_secret_key(params: &ElGamalParams) -> BigUint {
+    let q = params.q();
+    loop {
+        let x = OsRng.gen_biguint_below(&q);
+        if !x.is_zero() {
+            return x;
+        }
+    }
+}
+
+/// Encrypts the secret key, the public key share is stored in plain.
+fn seal_key(
+    vote: &str,
+    sk: &PrivateKey,
+    pk: &ElGamalPK,
+    password: &str,
+    log_n: u8,
+) -> Result<KeystoreEntry, Error> {
+    let public = pk.h.to_str_radix(16);
+    let secret = sk.x.to_str_radix(16);
+    let entry = KeystoreEntry::encrypt_secret(vote, &public, &secret, password, log_n)?;
+    Ok(entry)
+}
+
+/// Decrypts the secret key and checks it against the public key share and the vote's parameters.
+pub fn unseal_key(
+    entry: &KeystoreEntry,
+    password: &str,
+    params: &ElGamalParams,
+) -> Result<(PrivateKey, ElGamalPK), Error> {
+    let secret = entry.decrypt_secret(password)?;
+    let x = BigUint::parse_bytes(secret.as_bytes(), 16)
+        .ok_or_else(|| Error::Other(format!("invalid keystore entry: {}", entry.name)))?;
+    if x.is_zero() || x >= params.q() {
+        return Err(Error::Other(format!(
+            "the secret key of: {} does not belong to the vote's parameters",
+            entry.name
+        )));
+    }
+
+    let (pk, sk) = Helper::generate_key_pair(params, &x);
+    if pk.h.to_str_radix(16) != entry.public {
+        return Err(Error::Other(format!(
+            "the public key does not match the secret of: {}",
+            entry.name
+        )));
+    }
+    Ok((sk, pk))
+}
 
+/// Generates a fresh secret key and writes it to the keystore.
+/// Existing keys are never replaced, their public key shares might be stored on chain.
+pub fn create_key(
+    path: &Path,
+    vote: &str,
+    params: &ElGamalParams,
+    password: &str,
+) -> Result<(PrivateKey, ElGamalPK), Error> {
+    if path.exists() {
+        return Err(Error::Other(format!("{} already exists", path.display())));
+    }
+
+    // create private and public key using the vote's parameters
+    let x = generate_secret_key(params);
+    let (pk, sk) = Helper::generate_key_pair(params, &x);
+
+    // store the secret key before the public key share is submitted
+    let entry = seal_key(vote, &sk, &pk, password, DEFAULT_LOG_N)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            Error::Other(format!("failed to create {}: {}", parent.display(), err))
+        })?;
+    }
+    entry.write_file(path)?;
+    println!("secret key written to: {}", path.display());
+    Ok((sk, pk))
+}
+
+/// Submits the public key share of the sealer including the proof of its secret key.
+pub async fn submit_key_share(
+    client: &Client<NodeTemplateRuntime>,
+    sealer: Pair,
+    sealer_id: &[u8; 32],
+    vote_id: VoteId,
+    sk: &PrivateKey,
+    pk: &ElGamalPK,
+) -> Result<(), Error> {
     // create public key share + proof
+    let params = &pk.params;
     let r = Random::get_random_less_than(&params.q());
-    let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id);
+    let proof = KeyGenerationProof::generate(params, &sk.x, &pk.h, &r, sealer_id);
     let pk_share = PublicKeyShare {
-        proof: proof.clone().into(),
+        proof: proof.into(),
         pk: pk.h.to_bytes_be(),
     };
-    let vote_id = vote.as_bytes().to_vec();
 
     // submit the public key share + proof
     let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
     let store_public_key_share_response =
-        store_public_key_share(&client, &signer, vote_id, pk_share).await?;
+        store_public_key_share(client, &signer, vote_id, pk_share).await?;
     println!(
         "store_public_key_share_response: {:?}",
         store_public_key_share_response.events[0].variant
@@ -70,31 +188,42 @@ pub async fn keygen(vote: String, sk_as_string: String, sealer: String) -> Resul
     Ok(())
 }
 
-pub async fn decrypt(
+pub async fn keygen(
+    config: Config,
     vote: String,
-    question: String,
-    sk_as_string: String,
     sealer: String,
+    dir: String,
 ) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
 
-    // create private and public key
-    let (params, sk, pk) = Helper::setup_lg_system_with_sk(sk_as_string.as_bytes());
-    // get the sealer and sealer_id
-    let (sealer, sealer_id): (Pair, [u8; 32]) = get_sealer(sealer);
+    // never replace an existing key, its public key share might be stored on chain
+    let path = keystore_path(&dir, &sealer, &vote);
+    if path.exists() {
+        return Err(Error::Other(format!("{} already exists", path.display())));
+    }
+    let password = keystore::password()?;
+    let (sealer, sealer_id) = get_sealer(&dir, &sealer, &password)?;
 
-    // fetch the encrypted votes from chain
     let vote_id = vote.as_bytes().to_vec();
-    let topic_id = question.as_bytes().to_vec();
-    let nr_of_shuffles = 3;
-    let encryptions: Vec<Cipher> = get_ciphers(&client, topic_id.clone(), nr_of_shuffles).await?;
-    let encryptions: Vec<BigCipher> = Wrapper(encryptions).into();
+    let params = get_params(&client, vote_id.clone()).await?;
+    let (sk, pk) = create_key(&path, &vote, &params, &password)?;
 
+    submit_key_share(&client, sealer, &sealer_id, vote_id, &sk, &pk).await
+}
+
+/// Partially decrypts the ciphers and proves the decryption using the sealer's key.
+fn partial_decryption(
+    params: &ElGamalParams,
+    sk: &PrivateKey,
+    pk: &ElGamalPK,
+    sealer_id: &[u8; 32],
+    encryptions: Vec<BigCipher>,
+) -> (Vec<DecryptedShare>, DecryptionProof) {
     // get partial decryptions
     let partial_decryptions = encryptions
         .iter()
-        .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+        .map(|cipher| ElGamal::partial_decrypt_a(cipher, sk))
         .collect::<Vec<BigUint>>();
 
     // convert the decrypted shares: Vec<BigUint> to Vec<Vec<u8>>
@@ -102,24 +231,43 @@ pub async fn decrypt(
         .iter()
         .map(|c| c.to_bytes_be())
         .collect::<Vec<Vec<u8>>>();
-   
+
     // create proof using public and private key share
     let r = Random::get_random_less_than(&params.q());
-
     let proof = DecryptionProof::generate(
-        &params,
+        params,
         &sk.x,
-        &pk.h.into(),
+        &pk.h,
         &r,
         encryptions,
         partial_decryptions,
-        &sealer_id,
+        sealer_id,
     );
- 
+    (shares, proof)
+}
+
+/// Partially decrypts the shuffled ciphers of a question and submits the shares + proof.
+pub async fn submit_decryption(
+    client: &Client<NodeTemplateRuntime>,
+    sealer: Pair,
+    sealer_id: &[u8; 32],
+    vote_id: VoteId,
+    topic_id: TopicId,
+    sk: &PrivateKey,
+    pk: &ElGamalPK,
+) -> Result<(), Error> {
+    // fetch the encrypted votes from chain
+    let nr_of_shuffles = NR_OF_SHUFFLES;
+    let encryptions: Vec<Cipher> = get_ciphers(client, topic_id.clone(), nr_of_shuffles).await?;
+    let encryptions: Vec<BigCipher> = Wrapper(encryptions).into();
+
+    // get partial decryptions + proof
+    let (shares, proof) = partial_decryption(&pk.params, sk, pk, sealer_id, encryptions);
+
     // submit the partial decryption + proof
     let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(sealer);
     let response = submit_partial_decryptions(
-        &client,
+        client,
         &signer,
         vote_id,
         topic_id,
@@ -129,6 +277,110 @@ pub async fn decrypt(
     )
     .await?;
     println!("response: {:?}", response.events[0].variant);
-
     Ok(())
 }
+
+pub async fn decrypt(
+    config: Config,
+    vote: String,
+    question: String,
+    sealer: String,
+    dir: String,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+
+    // read private and public key from the sealer's keystore
+    let vote_id = vote.as_bytes().to_vec();
+    let params = get_params(&client, vote_id.clone()).await?;
+    let password = keystore::password()?;
+    let entry = KeystoreEntry::read(&keystore_path(&dir, &sealer, &vote))?;
+    let (sk, pk) = unseal_key(&entry, &password, &params)?;
+    let (sealer, sealer_id) = get_sealer(&dir, &sealer, &password)?;
+
+    let topic_id = question.as_bytes().to_vec();
+    submit_decryption(&client, sealer, &sealer_id, vote_id, topic_id, &sk, &pk).await
+}
+
+/// Partially decrypts a question using the exported ciphers and signs the submission offline.
+/// The secret key never leaves the host, the signed extrinsic is submitted using `broadcast`.
+pub async fn sign_decryption(
+    export: String,
+    question: String,
+    sealer: String,
+    dir: String,
+    nonce: Option<u32>,
+    file: String,
+) -> Result<(), Error> {
+    let export = read_export(&export)?;
+    let ciphers = export.question(&question)?.ciphers.clone();
+    if ciphers.is_empty() {
+        return Err(Error::Other(format!(
+            "no shuffled ciphers exported for question: {}",
+            question
+        )));
+    }
+
+    // read private and public key from the sealer's keystore
+    let params = export.params.clone();
+    let password = keystore::password()?;
+    let entry = KeystoreEntry::read(&keystore_path(&dir, &sealer, &export.vote))?;
+    let (sk, pk) = unseal_key(&entry, &password, &params)?;
+    let (sealer, sealer_id) = get_sealer(&dir, &sealer, &password)?;
+
+    let (shares, proof) = partial_decryption(&params, &sk, &pk, &sealer_id, ciphers);
+    let call = SubmitPartialDecryption {
+        vote_id: export.vote_id(),
+        topic_id: question.as_bytes().to_vec(),
+        shares,
+        proof: proof.into(),
+        nr_of_shuffles: NR_OF_SHUFFLES,
+    };
+    let index = export.calls.submit_decrypted_shares;
+    let signed = sign(&export, "submit_decrypted_shares", index, call, sealer, nonce).await?;
+    write_signed(&file, &signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // keep the tests fast, never use this in production
+    const TEST_LOG_N: u8 = 4;
+
+    #[test]
+    fn it_should_seal_and_unseal_a_secret_key() {
+        let (params, _, _) = Helper::setup_md_system();
+        let x = generate_secret_key(&params);
+        let (pk, sk) = Helper::generate_key_pair(&params, &x);
+
+        let entry = seal_key("vote", &sk, &pk, "password", TEST_LOG_N).unwrap();
+
+        let (unsealed_sk, unsealed_pk) = unseal_key(&entry, "password", &params).unwrap();
+        assert_eq!(unsealed_sk, sk);
+        assert_eq!(unsealed_pk, pk);
+        assert!(unseal_key(&entry, "wrong", &params).is_err());
+
+        // the key must belong to the vote's parameters
+        let (other_params, _, _) = Helper::setup_sm_system();
+        assert!(unseal_key(&entry, "password", &other_params).is_err());
+    }
+
+    #[test]
+    fn it_should_load_a_sealer_from_the_keystore() {
+        let dir = std::env::temp_dir().join("provotum-client-test-sealers");
+        let _ = fs::remove_dir_all(&dir);
+        let entry = KeystoreEntry::encrypt("bob", "//Bob", "password", TEST_LOG_N).unwrap();
+        entry.write(&dir).unwrap();
+        let dir = dir.to_str().unwrap();
+
+        let (pair, sealer_id) = get_sealer(dir, "bob", "password").unwrap();
+        assert_eq!(pair.public(), sp_keyring::AccountKeyring::Bob.public());
+        assert_eq!(sealer_id, pair.public().0);
+
+        // unknown sealers are refused, no dev key is used instead
+        assert!(get_sealer(dir, "charlie", "password").is_err());
+        assert!(get_sealer(dir, "bob", "wrong").is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,26 +1,39 @@
-use crate::voting::substrate::rpc::{
-    combine_decrypted_shares, combine_pk_shares, create_vote, get_tally, set_vote_phase,
-    store_question,
+use crate::config::Config;
+use crate::election::Election;
+use crate::output::{Output, QuestionResult, VoteMetadata};
+use chrono::Utc;
+use crypto::{helper::Helper, random::Random};
+use pallet_mixnet::types::{
+    CredentialKey, PublicParameters, RandomizerKey, Topic, TopicAnswer, TopicId, VotePhase,
 };
-use crypto::helper::Helper;
-use pallet_mixnet::types::{Topic, VotePhase};
-use std::str::FromStr;
-use substrate_subxt::Client;
-use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime};
-
-async fn init() -> Result<Client<NodeTemplateRuntime>, Error> {
-    env_logger::init();
-    let url = "ws://127.0.0.1:9944";
-    let client = ClientBuilder::<NodeTemplateRuntime>::new()
-        .set_url(url)
-        .build()
-        .await?;
-    Ok(client)
-}
+use provotum_sdk::{
+    rpc::{
+        combine_decrypted_shares, combine_pk_shares, create_vote, get_answers, get_public_key,
+        get_tally, get_topics, get_vote, get_vote_ids, set_randomizer_signature_required,
+        set_vote_phase, store_answers, store_credential_key, store_question, store_randomizer_key,
+        store_relayer,
+    },
+    NR_OF_SHUFFLES,
+};
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    str::FromStr,
+};
+use substrate_subxt::{sp_core::crypto::Ss58Codec, system::System, Client};
+use substrate_subxt::{Error, NodeTemplateRuntime};
 
-pub async fn setup_vote(vote_title: String, topic_question: String) -> Result<(), Error> {
+pub async fn setup_vote(
+    config: Config,
+    vote_title: String,
+    topic_question: String,
+) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create the vote
     let (params, _, _) = Helper::setup_lg_system();
@@ -36,9 +49,10 @@ pub async fn setup_vote(vote_title: String, topic_question: String) -> Result<()
     // setup the vote
     let create_vote_response = create_vote(
         &client,
-        params.into(),
-        vote_title,
+        &signer,
         vote_id.clone(),
+        vote_title,
+        params.into(),
         topics,
         75,
     )
@@ -57,9 +71,127 @@ pub async fn setup_vote(vote_title: String, topic_question: String) -> Result<()
     Ok(())
 }
 
-pub async fn setup_question(vote: String, question: String) -> Result<(), Error> {
+/// Sets up the vote of an election file, every step is skipped if it has been completed before.
+///
+/// Once the voting starts and the sealers' public key shares are combined, the vote is opened.
+/// Once the voting ends, the vote is closed. Run the command again to advance the vote.
+pub async fn create_election(config: Config, file: String, roll_file: String) -> Result<(), Error> {
+    // validate the election before anything is submitted
+    let election = Election::load(&file)?;
+    let vote_id = election.vote_id();
+    let params: PublicParameters = election.params.params();
+    let title = election.title.as_bytes().to_vec();
+
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
+
+    // the eligible voters are read by the randomizer and the api
+    if election.store_roll(Path::new(&roll_file))? {
+        println!("electoral roll written to: {}", roll_file);
+    }
+
+    // create the vote including all questions
+    match get_vote(&client, vote_id.clone()).await? {
+        Some(vote) if vote.title == title && vote.params == params => {
+            println!("vote {:?} already exists", election.vote);
+        }
+        Some(_) => {
+            return Err(Error::Other(format!(
+                "the vote {:?} exists with a different title or parameters",
+                election.vote
+            )))
+        }
+        None => {
+            let topics = election.questions.iter().map(|q| q.topic()).collect();
+            let response = create_vote(
+                &client,
+                &signer,
+                vote_id.clone(),
+                title,
+                params,
+                topics,
+                election.batch_size,
+            )
+            .await?;
+            println!("response: {:?}", response.events[0].variant);
+        }
+    }
+
+    // add the questions which are missing, e.g. if the vote was created by `va setup`
+    let topics = get_topics(&client, vote_id.clone()).await?;
+    for question in election.questions.iter() {
+        if topics
+            .iter()
+            .any(|(topic_id, _)| *topic_id == question.topic_id())
+        {
+            continue;
+        }
+        let topic = question.topic();
+        let response = store_question(
+            &client,
+            &signer,
+            vote_id.clone(),
+            topic,
+            election.batch_size,
+        )
+        .await?;
+        println!("response: {:?}", response.events[0].variant);
+    }
+
+    // store the answers of each question
+    for question in election.questions.iter() {
+        let answers = question.topic_answers();
+        let stored = get_answers(&client, question.topic_id()).await?;
+        if stored == answers {
+            continue;
+        } else if !stored.is_empty() {
+            return Err(Error::Other(format!(
+                "the question {:?} has different answers on chain",
+                question.question
+            )));
+        }
+        let topic_id = question.topic_id();
+        let response = store_answers(&client, &signer, vote_id.clone(), topic_id, answers).await?;
+        println!("response: {:?}", response.events[0].variant);
+    }
+
+    // advance the vote according to the schedule
+    let now = Utc::now();
+    let schedule = &election.schedule;
+    let phase = get_vote(&client, vote_id.clone())
+        .await?
+        .ok_or("failed to fetch vote!")?
+        .phase;
+    let next_phase = match phase {
+        VotePhase::KeyGeneration if now >= schedule.voting_start => {
+            if get_public_key(&client, vote_id.clone()).await?.is_none() {
+                println!("the voting starts once the sealers' public key shares are combined");
+                None
+            } else {
+                Some(VotePhase::Voting)
+            }
+        }
+        VotePhase::Voting if now >= schedule.voting_end => Some(VotePhase::Tallying),
+        _ => None,
+    };
+    match next_phase {
+        Some(next_phase) => {
+            let response = set_vote_phase(&client, &signer, vote_id, next_phase).await?;
+            println!("response: {:?}", response.events[0].variant);
+        }
+        None => println!(
+            "vote phase: {:?}, voting: {} - {}",
+            phase, schedule.voting_start, schedule.voting_end
+        ),
+    }
+    Ok(())
+}
+
+pub async fn setup_question(config: Config, vote: String, question: String) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create the question + input parameters
     let vote_id = vote.as_bytes().to_vec();
@@ -68,14 +200,44 @@ pub async fn setup_question(vote: String, question: String) -> Result<(), Error>
     let topic: Topic = (topic_id.clone(), topic_question);
 
     // store question
-    let response = store_question(&client, vote_id, topic, 75).await?;
+    let response = store_question(&client, &signer, vote_id, topic, 75).await?;
+    println!("response: {:?}", response.events[0].variant);
+    Ok(())
+}
+
+pub async fn setup_answers(
+    config: Config,
+    vote: String,
+    question: String,
+    labels: Vec<String>,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let signer = config.signer()?;
+
+    // the answers are encrypted as 0, 1, ... in the given order
+    let vote_id = vote.as_bytes().to_vec();
+    let topic_id = question.as_bytes().to_vec();
+    let answers: Vec<TopicAnswer> = labels
+        .into_iter()
+        .enumerate()
+        .map(|(value, label)| (value as u32, label.into_bytes()))
+        .collect();
+
+    // store answers
+    let response = store_answers(&client, &signer, vote_id, topic_id, answers).await?;
     println!("response: {:?}", response.events[0].variant);
     Ok(())
 }
 
-pub async fn change_vote_phase(vote: String, vote_phase: String) -> Result<(), Error> {
+pub async fn change_vote_phase(
+    config: Config,
+    vote: String,
+    vote_phase: String,
+) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create input parameters
     let vote_id = vote.as_bytes().to_vec();
@@ -83,53 +245,193 @@ pub async fn change_vote_phase(vote: String, vote_phase: String) -> Result<(), E
         VotePhase::from_str(&vote_phase).expect("only valid VotePhase values should be parsbed!");
 
     // update vote phase to Voting
-    let response = set_vote_phase(&client, vote_id.clone(), vote_phase).await?;
+    let response = set_vote_phase(&client, &signer, vote_id.clone(), vote_phase).await?;
+    println!("response: {:?}", response.events[0].variant);
+    Ok(())
+}
+
+pub async fn register_randomizer(config: Config, key: String) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let signer = config.signer()?;
+
+    // parse the randomizer's public key (ss58)
+    let key = RandomizerKey::from_ss58check(&key)
+        .map_err(|err| Error::Other(format!("invalid randomizer key: {:?}", err)))?;
+
+    // register the randomizer key
+    let response = store_randomizer_key(&client, &signer, key).await?;
     println!("response: {:?}", response.events[0].variant);
     Ok(())
 }
 
-pub async fn combine_public_key_shares(vote: String) -> Result<(), Error> {
+pub async fn register_relayer(config: Config, account: String) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
+
+    // parse the relayer's account (ss58)
+    let relayer = <NodeTemplateRuntime as System>::AccountId::from_ss58check(&account)
+        .map_err(|err| Error::Other(format!("invalid relayer account: {:?}", err)))?;
+
+    // register the relayer
+    let response = store_relayer(&client, &signer, relayer).await?;
+    println!("response: {:?}", response.events[0].variant);
+    Ok(())
+}
+
+pub async fn create_credential_key(
+    config: Config,
+    vote: String,
+    bits: u64,
+    dir: String,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create input parameters
     let vote_id = vote.as_bytes().to_vec();
 
-    // update vote phase to Voting
-    let response = combine_pk_shares(&client, vote_id.clone()).await?;
+    // generate the key and keep the private part for the api which issues the credentials
+    let sk = Random::generate_blind_key(bits);
+    let path = Path::new(&dir).join(format!("{}.json", vote));
+    let content = serde_json::to_string(&sk).map_err(|err| Error::Other(err.to_string()))?;
+    fs::create_dir_all(&dir)
+        .and_then(|_| write_private_key(&path, content.as_bytes()))
+        .map_err(|err| Error::Other(format!("failed to write credential key: {:?}", err)))?;
+    println!("credential key written to: {}", path.display());
+
+    // store the public key on chain
+    let key: CredentialKey = sk.public.into();
+    let response = store_credential_key(&client, &signer, vote_id, key).await?;
+    println!("response: {:?}", response.events[0].variant);
+    Ok(())
+}
+
+/// Writes the private key readable by the owner only, an existing key is never overwritten.
+fn write_private_key(path: &Path, content: &[u8]) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    options.open(path)?.write_all(content)
+}
+
+pub async fn require_randomizer_signature(
+    config: Config,
+    vote: String,
+    required: bool,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let signer = config.signer()?;
+
+    // create input parameters
+    let vote_id = vote.as_bytes().to_vec();
+
+    // update whether ballots require a randomizer signature
+    let response = set_randomizer_signature_required(&client, &signer, vote_id, required).await?;
     println!("response: {:?}", response.events[0].variant);
     Ok(())
 }
 
-pub async fn tally_question(vote: String, question: String) -> Result<(), Error> {
+pub async fn combine_public_key_shares(config: Config, vote: String) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create input parameters
     let vote_id = vote.as_bytes().to_vec();
-    let topic_id = question.as_bytes().to_vec();
 
     // update vote phase to Voting
-    let response = combine_decrypted_shares(&client, vote_id, topic_id).await?;
-    println!(
-        "response: {:?}, data: {:?}",
-        response.events[0].variant, response.events[0]
-    );
+    let response = combine_pk_shares(&client, &signer, vote_id.clone()).await?;
+    println!("response: {:?}", response.events[0].variant);
     Ok(())
 }
 
-pub async fn get_result(question: String) -> Result<(), Error> {
+pub async fn tally_question(
+    config: Config,
+    vote: String,
+    question: String,
+    encoded: bool,
+    output: Output,
+) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
+    let signer = config.signer()?;
 
     // create input parameters
+    let vote_id = vote.as_bytes().to_vec();
     let topic_id = question.as_bytes().to_vec();
 
-    // update vote phase to Voting
-    let result = get_tally(&client, topic_id).await?;
-    println!("The result of the question: {:?} is...", question);
-    for (vote, count) in result {
-        println!("\tVote: {:?}, Count: {:?}", vote, count);
+    // combine the decrypted shares and print the tally
+    combine_decrypted_shares(
+        &client,
+        &signer,
+        vote_id,
+        topic_id.clone(),
+        encoded,
+        NR_OF_SHUFFLES,
+    )
+    .await?;
+    let result = question_result(&client, Some(vote), topic_id).await?;
+    println!("{}", result.render(output)?);
+    Ok(())
+}
+
+/// Finds the vote of a question, all votes are searched if the vote is not given.
+async fn find_topic(
+    client: &Client<NodeTemplateRuntime>,
+    vote: Option<String>,
+    topic_id: &TopicId,
+) -> Result<(VoteMetadata, Topic), Error> {
+    let vote_ids = match vote {
+        Some(vote) => vec![vote.as_bytes().to_vec()],
+        None => get_vote_ids(client).await?,
+    };
+    for vote_id in vote_ids {
+        let topic = get_topics(client, vote_id.clone())
+            .await?
+            .into_iter()
+            .find(|(id, _)| id == topic_id);
+        if let Some(topic) = topic {
+            let vote = get_vote(client, vote_id.clone())
+                .await?
+                .ok_or("the vote does not exist!")?;
+            return Ok((VoteMetadata::new(&vote_id, &vote), topic));
+        }
     }
+    Err("the question does not exist!".into())
+}
+
+async fn question_result(
+    client: &Client<NodeTemplateRuntime>,
+    vote: Option<String>,
+    topic_id: TopicId,
+) -> Result<QuestionResult, Error> {
+    let (vote, topic) = find_topic(client, vote, &topic_id).await?;
+    let tally = get_tally(client, topic_id.clone())
+        .await?
+        .ok_or("failed to fetch tally!")?;
+    let answers = get_answers(client, topic_id).await?;
+    Ok(QuestionResult::new(vote, &topic, &answers, &tally))
+}
+
+pub async fn get_result(
+    config: Config,
+    vote: Option<String>,
+    question: String,
+    output: Output,
+) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+
+    // create input parameters
+    let topic_id = question.as_bytes().to_vec();
+
+    // fetch the tally, the answers and the vote of the question
+    let result = question_result(&client, vote, topic_id).await?;
+    println!("{}", result.render(output)?);
     Ok(())
 }
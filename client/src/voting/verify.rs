@@ -0,0 +1,671 @@
+use crate::config::Config;
+use crate::output::{to_json, Output, Rows};
+use codec::Encode;
+use crypto::encryption::ElGamal;
+use crypto::proofs::{decryption::DecryptionProof, shuffle::ShuffleProof};
+use crypto::types::{Cipher as BigCipher, ElGamalParams, PublicKey as ElGamalPK};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use pallet_mixnet::types::{
+    Cipher, DecryptedShare, DecryptionPayload, NrOfShuffles, ShufflePayload, TopicId, TopicResult,
+    VoteId, Wrapper,
+};
+use provotum_sdk::stores::{
+    AnswersStore, CiphersStore, DecryptedSharesStore, DecryptionProofsStore,
+    PublicKeyShareBySealerStore, PublicKeyStore, SealersStore, ShuffleProofsStore, TallyStore,
+    TopicsStore, VoteStore,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use substrate_subxt::{sp_core::crypto::Ss58Codec, system::System, Client};
+use substrate_subxt::{Error, NodeTemplateRuntime};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+type Hash = <NodeTemplateRuntime as System>::Hash;
+
+/// The result of re-running the verifier of a single shuffle proof.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct ShuffleReport {
+    iteration: NrOfShuffles,
+    start_position: u64,
+    batch_size: u64,
+    ciphers: usize,
+    valid: bool,
+}
+
+/// The result of re-running the verifier of a sealer's decryption proof.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct DecryptionReport {
+    sealer: String,
+    nr_of_shuffles: NrOfShuffles,
+    shares: usize,
+    valid: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct TopicReport {
+    topic: String,
+    ballots: usize,
+    shuffles: Vec<ShuffleReport>,
+    // whether the ciphers of each iteration are exactly the outputs of the verified shuffles
+    shuffles_complete: bool,
+    decryptions: Vec<DecryptionReport>,
+    // the sealers whose stored decrypted shares are not covered by a verified proof
+    unproven_shares: Vec<String>,
+    // the decrypted votes (value -> count)
+    tally: Option<BTreeMap<String, String>>,
+    recomputed_tally: Option<BTreeMap<String, String>>,
+    encoded: Option<bool>,
+    tally_matches: bool,
+    valid: bool,
+}
+
+/// The verification report of a vote at a finalized block.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+struct Report {
+    vote: String,
+    block: u32,
+    hash: String,
+    phase: String,
+    topics: Vec<TopicReport>,
+    valid: bool,
+}
+
+impl Report {
+    fn render(&self, output: Output) -> Result<String, Error> {
+        if output == Output::Json {
+            return to_json(self);
+        }
+        let count = |valid: usize, total: usize| format!("{}/{}", valid, total);
+        let mut rows = Rows {
+            headers: vec![
+                "topic",
+                "ballots",
+                "shuffles",
+                "shuffles_complete",
+                "decryptions",
+                "unproven_shares",
+                "tally_matches",
+                "valid",
+            ],
+            rows: Vec::new(),
+        };
+        for topic in self.topics.iter() {
+            let shuffles = topic.shuffles.iter().filter(|shuffle| shuffle.valid);
+            let decryptions = topic
+                .decryptions
+                .iter()
+                .filter(|decryption| decryption.valid);
+            rows.rows.push(vec![
+                topic.topic.clone(),
+                topic.ballots.to_string(),
+                count(shuffles.count(), topic.shuffles.len()),
+                topic.shuffles_complete.to_string(),
+                count(decryptions.count(), topic.decryptions.len()),
+                topic.unproven_shares.len().to_string(),
+                topic.tally_matches.to_string(),
+                topic.valid.to_string(),
+            ]);
+        }
+        if output == Output::Csv {
+            rows.headers.insert(0, "vote");
+            rows.headers.insert(1, "block");
+            for row in rows.rows.iter_mut() {
+                row.insert(0, self.vote.clone());
+                row.insert(1, self.block.to_string());
+            }
+            return Ok(rows.csv());
+        }
+        Ok(format!(
+            "Vote: {}  (finalized block #{}), phase: {}, valid: {}\n{}",
+            self.vote,
+            self.block,
+            self.phase,
+            self.valid,
+            rows.table()
+        ))
+    }
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn readable(result: &TopicResult) -> BTreeMap<String, String> {
+    result
+        .iter()
+        .map(|(value, count)| {
+            (
+                BigUint::from_bytes_be(value).to_str_radix(10),
+                BigUint::from_bytes_be(count).to_str_radix(10),
+            )
+        })
+        .collect()
+}
+
+/// Checks that the shuffles of an iteration process every input cipher once, in batches,
+/// and that their outputs are the ciphers of the next iteration.
+fn is_iteration_complete(
+    nr_of_inputs: usize,
+    payloads: &[&ShufflePayload],
+    outputs: &[Cipher],
+) -> bool {
+    let mut position: usize = 0;
+    let mut shuffled: Vec<Cipher> = Vec::with_capacity(nr_of_inputs);
+    for payload in payloads.iter() {
+        let expected = (payload.batch_size as usize).min(nr_of_inputs.saturating_sub(position));
+        if payload.start_position as usize != position
+            || expected == 0
+            || payload.ciphers.len() != expected
+        {
+            return false;
+        }
+        position += expected;
+        shuffled.extend(payload.ciphers.iter().cloned());
+    }
+    position == nr_of_inputs && shuffled == outputs
+}
+
+/// Re-runs the verifier of a shuffle proof on the ciphers of the payload's batch.
+fn verify_shuffle(
+    topic_id: &TopicId,
+    payload: &ShufflePayload,
+    inputs: &[Cipher],
+    pk: &ElGamalPK,
+) -> bool {
+    let start = payload.start_position as usize;
+    let end = (start + payload.batch_size as usize).min(inputs.len());
+    if start >= end {
+        return false;
+    }
+    let encryptions: Vec<BigCipher> = Wrapper(inputs[start..end].to_vec()).into();
+    let shuffled_encryptions: Vec<BigCipher> = Wrapper(payload.ciphers.clone()).into();
+    let proof: ShuffleProof = payload.proof.clone().into();
+    proof.verify(topic_id, encryptions, shuffled_encryptions, pk)
+}
+
+/// Re-runs the verifier of a sealer's decryption proof on the ciphers it decrypted and its stored shares.
+fn verify_decryption(
+    params: &ElGamalParams,
+    sealer_pk: &BigUint,
+    sealer_id: &[u8],
+    payload: &DecryptionPayload,
+    shares: &[DecryptedShare],
+    ciphers: &[Cipher],
+) -> bool {
+    if shares.is_empty() || shares.len() != ciphers.len() {
+        return false;
+    }
+    let encryptions: Vec<BigCipher> = Wrapper(ciphers.to_vec()).into();
+    let shares: Vec<BigUint> = shares
+        .iter()
+        .map(|share| BigUint::from_bytes_be(share))
+        .collect();
+    DecryptionProof::verify(
+        params,
+        sealer_pk,
+        &payload.proof.clone().into(),
+        encryptions,
+        shares,
+        sealer_id,
+    )
+}
+
+fn count(plaintexts: Vec<BigUint>) -> TopicResult {
+    let one = BigUint::one();
+    let mut results: BTreeMap<BigUint, BigUint> = BTreeMap::new();
+    plaintexts
+        .into_iter()
+        .for_each(|item| *results.entry(item).or_default() += &one);
+    results
+        .iter()
+        .map(|(value, count)| (value.to_bytes_be(), count.to_bytes_be()))
+        .collect()
+}
+
+/// the largest decoded value if no answers are stored on chain
+pub const MAX_DECODED_VALUE: u32 = 1024;
+
+/// Brute forces the discrete logarithm of an encoded message up to the given limit.
+fn decode(encoded: &BigUint, limit: &BigUint, params: &ElGamalParams) -> Option<BigUint> {
+    let mut message = BigUint::zero();
+    while message <= *limit {
+        if ElGamal::encode_message(&message, &params.g, &params.p) == *encoded {
+            return Some(message);
+        }
+        message += 1u32;
+    }
+    None
+}
+
+/// Recomputes the tally from the ciphers and the decrypted shares of all sealers.
+///
+/// Whether the votes were encoded is not stored on chain. The plaintexts are decoded
+/// if their tally differs from the given tally, the search is bounded by the largest answer value.
+/// Fails if a plaintext cannot be decoded within the bound.
+fn recompute_tally(
+    params: &ElGamalParams,
+    ciphers: &[Cipher],
+    partial_decryptions: Vec<Vec<DecryptedShare>>,
+    tally: Option<&TopicResult>,
+    limit: &BigUint,
+) -> Option<(TopicResult, bool)> {
+    if ciphers.is_empty()
+        || partial_decryptions.is_empty()
+        || partial_decryptions
+            .iter()
+            .any(|shares| shares.len() != ciphers.len())
+    {
+        return None;
+    }
+    let p = &params.p;
+    let partial_decryptions: Vec<Vec<BigUint>> = partial_decryptions
+        .iter()
+        .map(|shares| {
+            shares
+                .iter()
+                .map(|share| BigUint::from_bytes_be(share))
+                .collect()
+        })
+        .collect();
+    let combined = ElGamal::combine_partial_decrypted_as(partial_decryptions, p);
+    let ciphers: Vec<BigCipher> = Wrapper(ciphers.to_vec()).into();
+    let mut plaintexts = Vec::with_capacity(ciphers.len());
+    for (cipher, decrypted_a) in ciphers.iter().zip(combined.iter()) {
+        if decrypted_a.is_zero() {
+            return None;
+        }
+        plaintexts.push(ElGamal::partial_decrypt_b(&cipher.b, decrypted_a, p));
+    }
+
+    let plain = count(plaintexts.clone());
+    match tally {
+        Some(tally) if *tally != plain => {}
+        _ => return Some((plain, false)),
+    }
+    let decoded: Option<Vec<BigUint>> = plaintexts
+        .iter()
+        .map(|encoded| decode(encoded, limit, params))
+        .collect();
+    decoded.map(|decoded| (count(decoded), true))
+}
+
+async fn verify_topic(
+    client: &Client<NodeTemplateRuntime>,
+    at: Option<Hash>,
+    vote_id: &VoteId,
+    topic_id: TopicId,
+    params: &ElGamalParams,
+    pk: Option<&ElGamalPK>,
+    sealers: &[AccountId],
+) -> Result<TopicReport, Error> {
+    let ciphers_at = |nr_of_shuffles: NrOfShuffles| CiphersStore {
+        topic_id: topic_id.clone(),
+        nr_of_shuffles,
+    };
+    let ballots = client.fetch_or_default(&ciphers_at(0), at).await?;
+
+    // re-run the verifier of each shuffle proof, iteration by iteration
+    let shuffle_proofs_store = ShuffleProofsStore {
+        vote_id: vote_id.clone(),
+        topic_id: topic_id.clone(),
+    };
+    let payloads = client.fetch_or_default(&shuffle_proofs_store, at).await?;
+    let mut shuffles = Vec::with_capacity(payloads.len());
+    let mut shuffles_complete = true;
+    let mut inputs = ballots.clone();
+    let mut iteration: NrOfShuffles = 0;
+    loop {
+        let batches: Vec<&ShufflePayload> = payloads
+            .iter()
+            .filter(|payload| payload.iteration == iteration)
+            .collect();
+        if batches.is_empty() {
+            break;
+        }
+        let outputs = client
+            .fetch_or_default(&ciphers_at(iteration + 1), at)
+            .await?;
+        shuffles_complete &= is_iteration_complete(inputs.len(), &batches, &outputs);
+        for payload in batches {
+            let valid = match pk {
+                Some(pk) => verify_shuffle(&topic_id, payload, &inputs, pk),
+                None => false,
+            };
+            shuffles.push(ShuffleReport {
+                iteration,
+                start_position: payload.start_position,
+                batch_size: payload.batch_size,
+                ciphers: payload.ciphers.len(),
+                valid,
+            });
+        }
+        inputs = outputs;
+        iteration += 1;
+    }
+    // every payload must belong to one of the consecutive iterations
+    shuffles_complete &= shuffles.len() == payloads.len();
+
+    // re-run the verifier of each decryption proof and collect the decrypted shares
+    let mut decryptions = Vec::new();
+    let mut unproven_shares = Vec::new();
+    let mut partial_decryptions = Vec::with_capacity(sealers.len());
+    let mut decrypted_iteration: Option<NrOfShuffles> = None;
+    for sealer in sealers.iter() {
+        let shares_store = DecryptedSharesStore {
+            topic_id: topic_id.clone(),
+            sealer: sealer.clone(),
+        };
+        let shares = client.fetch_or_default(&shares_store, at).await?;
+        let proofs_store = DecryptionProofsStore {
+            topic_id: topic_id.clone(),
+            sealer: sealer.clone(),
+        };
+        let key_share_store = PublicKeyShareBySealerStore {
+            vote_id: vote_id.clone(),
+            sealer: sealer.clone(),
+        };
+        let sealer_pk = client
+            .fetch(&key_share_store, at)
+            .await?
+            .map(|share| BigUint::from_bytes_be(&share.pk));
+
+        // each proof covers all stored shares of the sealer
+        let mut proven = false;
+        for payload in client.fetch_or_default(&proofs_store, at).await? {
+            let ciphers = client
+                .fetch_or_default(&ciphers_at(payload.nr_of_shuffles), at)
+                .await?;
+            let valid = match &sealer_pk {
+                Some(sealer_pk) => verify_decryption(
+                    params,
+                    sealer_pk,
+                    &sealer.encode(),
+                    &payload,
+                    &shares,
+                    &ciphers,
+                ),
+                None => false,
+            };
+            proven |= valid;
+            if decrypted_iteration.get_or_insert(payload.nr_of_shuffles) != &payload.nr_of_shuffles
+            {
+                shuffles_complete = false;
+            }
+            decryptions.push(DecryptionReport {
+                sealer: sealer.to_ss58check(),
+                nr_of_shuffles: payload.nr_of_shuffles,
+                shares: shares.len(),
+                valid,
+            });
+        }
+        if !shares.is_empty() && !proven {
+            unproven_shares.push(sealer.to_ss58check());
+        }
+        partial_decryptions.push(shares);
+    }
+
+    // recompute the tally using the ciphers which have been decrypted
+    let tally_store = TallyStore {
+        topic_id: topic_id.clone(),
+    };
+    let tally = client.fetch(&tally_store, at).await?;
+    let decrypted_iteration = decrypted_iteration.unwrap_or(iteration);
+    let decrypted = client
+        .fetch_or_default(&ciphers_at(decrypted_iteration), at)
+        .await?;
+    let answers_store = AnswersStore {
+        topic_id: topic_id.clone(),
+    };
+    let limit = client
+        .fetch_or_default(&answers_store, at)
+        .await?
+        .iter()
+        .map(|(value, _)| BigUint::from(*value))
+        .max()
+        .unwrap_or_else(|| BigUint::from(MAX_DECODED_VALUE));
+    let recomputed = recompute_tally(
+        params,
+        &decrypted,
+        partial_decryptions,
+        tally.as_ref(),
+        &limit,
+    );
+    let tally_matches = match (&tally, &recomputed) {
+        (Some(tally), Some((recomputed, _))) => tally == recomputed,
+        _ => false,
+    };
+
+    let valid = tally_matches
+        && shuffles_complete
+        && unproven_shares.is_empty()
+        && shuffles.iter().all(|shuffle| shuffle.valid)
+        && decryptions.iter().all(|decryption| decryption.valid);
+    Ok(TopicReport {
+        topic: label(&topic_id),
+        ballots: ballots.len(),
+        shuffles,
+        shuffles_complete,
+        decryptions,
+        unproven_shares,
+        tally: tally.as_ref().map(readable),
+        recomputed_tally: recomputed.as_ref().map(|(result, _)| readable(result)),
+        encoded: recomputed.map(|(_, encoded)| encoded),
+        tally_matches,
+        valid,
+    })
+}
+
+/// Re-verifies all shuffles and decryptions of a vote at the finalized head and
+/// recomputes the tally of each question, prints the report as json.
+///
+/// Returns whether the vote has been verified successfully.
+pub async fn verify(config: Config, vote: String, output: Output) -> Result<bool, Error> {
+    let client = config.client().await?;
+    let vote_id = vote.as_bytes().to_vec();
+    let hash = client.finalized_head().await?;
+    let header = client
+        .header(Some(hash))
+        .await?
+        .ok_or("the finalized block does not exist!")?;
+    let at = Some(hash);
+
+    let vote_store = VoteStore {
+        vote_id: vote_id.clone(),
+    };
+    let vote = client
+        .fetch(&vote_store, at)
+        .await?
+        .ok_or("the vote does not exist!")?;
+    let params: ElGamalParams = vote.params.clone().into();
+    let public_key_store = PublicKeyStore {
+        vote_id: vote_id.clone(),
+    };
+    let pk: Option<ElGamalPK> = client
+        .fetch(&public_key_store, at)
+        .await?
+        .map(|pk| pk.into());
+    let sealers = client.fetch_or_default(&SealersStore {}, at).await?;
+    let topics_store = TopicsStore {
+        vote_id: vote_id.clone(),
+    };
+
+    let mut topics = Vec::new();
+    for (topic_id, _) in client.fetch_or_default(&topics_store, at).await? {
+        let report = verify_topic(
+            &client,
+            at,
+            &vote_id,
+            topic_id,
+            &params,
+            pk.as_ref(),
+            &sealers,
+        )
+        .await?;
+        topics.push(report);
+    }
+
+    let valid = !topics.is_empty() && topics.iter().all(|topic| topic.valid);
+    let report = Report {
+        vote: label(&vote_id),
+        block: header.number,
+        hash: format!("{:?}", hash),
+        phase: format!("{:?}", vote.phase),
+        topics,
+        valid,
+    };
+    println!("{}", report.render(output)?);
+    Ok(valid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::helper::Helper;
+    use crypto::random::Random;
+
+    fn ciphers(
+        params: &ElGamalParams,
+        pk: &ElGamalPK,
+        messages: &[u32],
+        encoded: bool,
+    ) -> Vec<Cipher> {
+        let big_ciphers: Vec<BigCipher> = messages
+            .iter()
+            .map(|message| {
+                let message = BigUint::from(*message);
+                let r = Random::get_random_less_than(&params.q());
+                if encoded {
+                    ElGamal::encrypt_encode(&message, &r, pk)
+                } else {
+                    ElGamal::encrypt(&message, &r, pk)
+                }
+            })
+            .collect();
+        Wrapper(big_ciphers).into()
+    }
+
+    #[test]
+    fn it_should_check_that_an_iteration_is_complete() {
+        let payload = |start_position: u64, ciphers: Vec<Cipher>| ShufflePayload {
+            iteration: 0,
+            ciphers,
+            start_position,
+            batch_size: 2,
+            ..Default::default()
+        };
+        let (a, b, c) = (
+            Cipher {
+                a: vec![1],
+                b: vec![1],
+            },
+            Cipher {
+                a: vec![2],
+                b: vec![2],
+            },
+            Cipher {
+                a: vec![3],
+                b: vec![3],
+            },
+        );
+        let first = payload(0, vec![b.clone(), a.clone()]);
+        let last = payload(2, vec![c.clone()]);
+        let outputs = vec![b.clone(), a.clone(), c.clone()];
+
+        assert!(is_iteration_complete(3, &[&first, &last], &outputs));
+        assert!(!is_iteration_complete(3, &[&first], &outputs));
+        assert!(!is_iteration_complete(3, &[&first, &last, &last], &outputs));
+        assert!(!is_iteration_complete(3, &[&last, &first], &outputs));
+        assert!(!is_iteration_complete(3, &[&first, &last], &[a, b, c]));
+    }
+
+    #[test]
+    fn it_should_verify_decryptions_and_recompute_the_tally() {
+        // a tiny group accepts a manipulated proof with a probability of 1/q
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let sealer_id = b"sealer".to_vec();
+        let ciphers = ciphers(&params, &pk, &[1, 0, 1], true);
+        let encryptions: Vec<BigCipher> = Wrapper(ciphers.clone()).into();
+        let shares: Vec<BigUint> = encryptions
+            .iter()
+            .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+            .collect();
+        let r = Random::get_random_less_than(&params.q());
+        let proof = DecryptionProof::generate(
+            &params,
+            &sk.x,
+            &pk.h,
+            &r,
+            encryptions,
+            shares.clone(),
+            &sealer_id,
+        );
+        let shares: Vec<DecryptedShare> = shares.iter().map(|share| share.to_bytes_be()).collect();
+        let payload = DecryptionPayload {
+            nr_of_shuffles: 0,
+            proof: proof.into(),
+        };
+
+        assert!(verify_decryption(
+            &params, &pk.h, &sealer_id, &payload, &shares, &ciphers
+        ));
+        assert!(!verify_decryption(
+            &params, &pk.h, b"other", &payload, &shares, &ciphers
+        ));
+        assert!(!verify_decryption(
+            &params,
+            &pk.h,
+            &sealer_id,
+            &payload,
+            &shares[1..],
+            &ciphers[1..]
+        ));
+
+        // the shares must be elements of G_q, -share is not
+        let mut negated = shares.clone();
+        negated[0] = (&params.p - BigUint::from_bytes_be(&shares[0])).to_bytes_be();
+        assert!(!verify_decryption(
+            &params, &pk.h, &sealer_id, &payload, &negated, &ciphers
+        ));
+
+        // the tally of the decoded votes: { 0: 1, 1: 2 }
+        let mut tally: TopicResult = BTreeMap::new();
+        tally.insert(vec![0], vec![1]);
+        tally.insert(vec![1], vec![2]);
+        let (recomputed, encoded) = recompute_tally(
+            &params,
+            &ciphers,
+            vec![shares.clone()],
+            Some(&tally),
+            &BigUint::one(),
+        )
+        .unwrap();
+        assert_eq!(recomputed, tally);
+        assert!(encoded);
+
+        // a manipulated tally never matches
+        tally.insert(vec![1], vec![3]);
+        let (recomputed, _) = recompute_tally(
+            &params,
+            &ciphers,
+            vec![shares.clone()],
+            Some(&tally),
+            &BigUint::one(),
+        )
+        .unwrap();
+        assert_ne!(recomputed, tally);
+        assert!(
+            recompute_tally(&params, &ciphers, vec![], Some(&tally), &BigUint::one()).is_none()
+        );
+
+        // the votes cannot be decoded if the answers are bounded below the votes
+        assert!(recompute_tally(
+            &params,
+            &ciphers,
+            vec![shares.clone()],
+            Some(&tally),
+            &BigUint::zero()
+        )
+        .is_none());
+    }
+}
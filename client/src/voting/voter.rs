@@ -1,48 +1,121 @@
-use crate::voting::substrate::rpc::submit_ballot;
-use crypto::{
-    proofs::re_encryption::ReEncryptionProof,
-    types::{Cipher, PublicKey},
-};
+use async_std::{io::BufReader, prelude::*};
+use crypto::blind::{BlindPublicKey, BlindSignature};
+use crypto::{encryption::ElGamal, proofs::re_encryption::ReEncryptionProof, types::Cipher};
 use crypto::{random::Random, types::PublicKey as ElGamalPK};
-use pallet_mixnet::types::Ballot;
+use num_bigint::BigUint;
+use pallet_mixnet::types::{
+    credential_message, AnswerValue, Ballot, RandomizerSignature, Topic, TopicAnswer, TopicId,
+    VoteId, VotePhase, VoterToken,
+};
+use provotum_sdk::auth::{auth_message, credential_request_message};
+use provotum_sdk::rpc::{
+    cast_anonymous_ballot, cast_ballot, get_answers, get_credential_key, get_topics, get_vote,
+    submit_ballot,
+};
 use serde::{Deserialize, Serialize};
 use sp_keyring::sr25519::sr25519::Pair;
-use substrate_subxt::{sp_core::Pair as KeyPairGenerator, Client};
-use substrate_subxt::{ClientBuilder, Error, NodeTemplateRuntime, PairSigner};
+use std::path::Path;
+use substrate_subxt::{
+    sp_core::{crypto::Ss58Codec, Pair as KeyPairGenerator},
+    system::System,
+    Client,
+};
+use substrate_subxt::{Error, NodeTemplateRuntime, PairSigner, Signer};
 use surf::Body;
 
-use super::substrate::rpc::get_vote_public_key;
+use super::offline::{read_export, sign, write_signed};
+use crate::config::Config;
+use crate::keystore;
+use provotum_sdk::calls::CastBallot;
+use provotum_sdk::keystore::{KeystoreEntry, DEFAULT_LOG_N};
+use provotum_sdk::rpc::get_vote_public_key;
+
+/// the env variable containing the voter's mnemonic, read from stdin if unset
+pub const MNEMONIC_ENV: &str = "PROVOTUM_VOTER_MNEMONIC";
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct RequestBody {
-    pub pk: PublicKey,
+    pub vote_id: VoteId,
     pub cipher: Cipher,
+    pub verifier_pk: BigUint,
+    pub voter: <NodeTemplateRuntime as System>::AccountId,
+    pub token: String,
 }
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct ResponseBody {
     pub proof: ReEncryptionProof,
     pub cipher: Cipher,
+    pub signature: RandomizerSignature,
 }
 
-async fn init() -> Result<Client<NodeTemplateRuntime>, Error> {
-    env_logger::init();
-    let url = "ws://127.0.0.1:9944";
-    let client = ClientBuilder::<NodeTemplateRuntime>::new()
-        .set_url(url)
-        .build()
-        .await?;
-    Ok(client)
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchEntry {
+    pub cipher: Cipher,
+    pub verifier_pk: BigUint,
+    pub voter: <NodeTemplateRuntime as System>::AccountId,
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchRequestBody {
+    pub vote_id: VoteId,
+    pub entries: Vec<BatchEntry>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct ChallengeRequestBody {
+    pub vote_id: VoteId,
+    pub voter: <NodeTemplateRuntime as System>::AccountId,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct ChallengeResponseBody {
+    pub challenge: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct TokenRequestBody {
+    pub vote_id: VoteId,
+    pub voter: <NodeTemplateRuntime as System>::AccountId,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct TokenResponseBody {
+    pub token: String,
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct CredentialRequestBody {
+    pub voter: String,
+    pub blinded: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct CredentialResponseBody {
+    pub vote: String,
+    pub blind_signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchResponseEntry {
+    pub index: usize,
+    #[serde(flatten)]
+    pub response: ResponseBody,
 }
 
 pub async fn create_votes(
+    config: Config,
     vote: String,
     question: String,
     nr_of_votes: usize,
     votes: Vec<u32>,
 ) -> Result<(), Error> {
     // init substrate client
-    let client = init().await?;
+    let client = config.client().await?;
 
     // create the vote
     let vote_id = vote.as_bytes().to_vec();
@@ -53,44 +126,533 @@ pub async fn create_votes(
     // generate random encryptions
     let encryptions = Random::generate_encryptions(&pk, q, nr_of_votes, votes);
 
-    // submit some ballots
+    // create the voters and their designated verifier keys,
+    // the randomizer's proof only convinces the voter
+    let mut voters = Vec::with_capacity(encryptions.len());
+    let mut entries = Vec::with_capacity(encryptions.len());
     for (index, cipher) in encryptions.into_iter().enumerate() {
         let index_string = (index as u64).to_string();
-        let voter_keypair = KeyPairGenerator::from_string(&format!("//{}", index_string), None)?;
-        let voter = PairSigner::<NodeTemplateRuntime, Pair>::new(voter_keypair);
+        let voter_keypair: Pair =
+            KeyPairGenerator::from_string(&format!("//{}", index_string), None)?;
+        let voter = PairSigner::<NodeTemplateRuntime, Pair>::new(voter_keypair.clone());
 
-        let body = RequestBody {
-            pk: pk.clone(),
-            cipher: cipher.clone(),
+        // authenticate the voter at the randomizer
+        let token = authenticate(&config, &vote_id, &voter_keypair)
+            .await
+            .map_err(|err| Error::Other(format!("failed to authenticate: {}", err)))?;
+
+        let x_v = Random::get_random_less_than(q);
+        let verifier_pk = pk.params.g.modpow(&x_v, &pk.params.p);
+        entries.push(BatchEntry {
+            cipher,
+            verifier_pk,
+            voter: voter.account_id().clone(),
+            token,
+        });
+        voters.push(voter);
+    }
+
+    // randomize all ciphers, use a single batch request for many votes
+    let responses: Vec<ResponseBody> = if entries.len() > 1 {
+        let body = BatchRequestBody {
+            vote_id: vote_id.clone(),
+            entries: entries.clone(),
         };
-        let response: ResponseBody = randomize_cipher(&body).await.unwrap();
-        let proof_is_valid =
-            ReEncryptionProof::verify(&pk, &response.proof, &cipher, &response.cipher);
+        let mut responses = randomize_ciphers(&config, &body)
+            .await
+            .map_err(|err| Error::Other(format!("failed to randomize the ballots: {}", err)))?;
+        responses.sort_by_key(|entry| entry.index);
+        responses.into_iter().map(|entry| entry.response).collect()
+    } else {
+        let mut responses = Vec::new();
+        for entry in entries.iter() {
+            let body = RequestBody {
+                vote_id: vote_id.clone(),
+                cipher: entry.cipher.clone(),
+                verifier_pk: entry.verifier_pk.clone(),
+                voter: entry.voter.clone(),
+                token: entry.token.clone(),
+            };
+            let response = randomize_cipher(&config, &body)
+                .await
+                .map_err(|err| Error::Other(format!("failed to randomize the ballot: {}", err)))?;
+            responses.push(response);
+        }
+        responses
+    };
+    assert_eq!(responses.len(), entries.len());
+
+    // submit some ballots
+    for (index, ((voter, entry), response)) in
+        voters.iter().zip(entries.iter()).zip(responses).enumerate()
+    {
+        let proof_is_valid = ReEncryptionProof::verify_designated(
+            &pk,
+            &entry.verifier_pk,
+            &response.proof,
+            &entry.cipher,
+            &response.cipher,
+        );
         assert!(proof_is_valid);
         let re_encrypted_cipher = response.cipher;
         println!(
             "randomized ballot + verified proof for voter: {:?}",
-            index_string
+            index.to_string()
         );
 
-        // create ballot
+        // create ballot + the randomizer's signature for each answer
         let ballot: Ballot = Ballot {
             answers: vec![(topic_id.clone(), re_encrypted_cipher.into())],
         };
+        let signatures = vec![response.signature];
 
         // submit ballot
         let ballot_submission_hash =
-            submit_ballot(&client, &voter, vote_id.clone(), ballot).await?;
+            submit_ballot(&client, voter, vote_id.clone(), ballot, signatures).await?;
         println!("ballot_submission_hash: {:?}", ballot_submission_hash);
     }
     Ok(())
 }
 
-pub async fn randomize_cipher(body: &RequestBody) -> Result<ResponseBody, surf::Error> {
+/// Loads the voter's key from the keystore file or the mnemonic.
+fn load_voter(keystore: Option<String>) -> Result<Pair, Error> {
+    match keystore {
+        Some(path) => {
+            let pair = KeystoreEntry::read(Path::new(&path))?.decrypt(&keystore::password()?)?;
+            Ok(pair)
+        }
+        None => {
+            let mnemonic = keystore::read_secret(MNEMONIC_ENV)?;
+            // the error would contain the mnemonic
+            KeyPairGenerator::from_string(&mnemonic, None)
+                .map_err(|_| Error::Other("invalid mnemonic".to_string()))
+        }
+    }
+}
+
+/// Encrypts the voter's mnemonic (read from the env or stdin) and writes it to the keystore file.
+pub fn create_keystore(name: String, file: String) -> Result<(), Error> {
+    let mnemonic = keystore::read_secret(MNEMONIC_ENV)?;
+    let entry = KeystoreEntry::encrypt(&name, &mnemonic, &keystore::password()?, DEFAULT_LOG_N)?;
+    entry.write_file(Path::new(&file))?;
+    println!("voter account: {}", entry.public);
+    Ok(())
+}
+
+/// Fetches the questions of the vote and their allowed answers.
+pub async fn get_options(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: &VoteId,
+) -> Result<Vec<(Topic, Vec<TopicAnswer>)>, Error> {
+    let mut options = Vec::new();
+    for topic in get_topics(client, vote_id.clone()).await? {
+        let answers = get_answers(client, topic.0.clone()).await?;
+        options.push((topic, answers));
+    }
+    Ok(options)
+}
+
+/// Maps the selected answers (`<question>=<label>`) to their values.
+/// Each question can be answered once, only the labels stored on chain are accepted.
+pub fn select_answers(
+    options: &[(Topic, Vec<TopicAnswer>)],
+    selections: &[String],
+) -> Result<Vec<(TopicId, AnswerValue)>, Error> {
+    if selections.is_empty() {
+        return Err(Error::Other("at least one answer is required".to_string()));
+    }
+    let mut choices: Vec<(TopicId, AnswerValue)> = Vec::with_capacity(selections.len());
+    for selection in selections {
+        let mut parts = selection.splitn(2, '=');
+        let (question, label) = match (parts.next(), parts.next()) {
+            (Some(question), Some(label)) => (question, label),
+            _ => {
+                return Err(Error::Other(format!(
+                    "invalid answer: {}, expected <question>=<label>",
+                    selection
+                )))
+            }
+        };
+        let topic_id = question.as_bytes().to_vec();
+        let (_, answers) = options
+            .iter()
+            .find(|(topic, _)| topic.0 == topic_id)
+            .ok_or_else(|| Error::Other(format!("unknown question: {}", question)))?;
+        if choices.iter().any(|(id, _)| *id == topic_id) {
+            return Err(Error::Other(format!(
+                "question answered twice: {}",
+                question
+            )));
+        }
+        if answers.is_empty() {
+            return Err(Error::Other(format!(
+                "no answers stored for question: {}",
+                question
+            )));
+        }
+
+        let value = answers
+            .iter()
+            .find(|(_, allowed)| allowed.as_slice() == label.as_bytes())
+            .map(|(value, _)| *value)
+            .ok_or_else(|| {
+                let allowed: Vec<String> = answers
+                    .iter()
+                    .map(|(_, allowed)| String::from_utf8_lossy(allowed).to_string())
+                    .collect();
+                Error::Other(format!(
+                    "invalid answer: {} to question: {}, allowed: {}",
+                    label,
+                    question,
+                    allowed.join(", ")
+                ))
+            })?;
+        choices.push((topic_id, value));
+    }
+    Ok(choices)
+}
+
+/// Casts a single ballot answering one or more questions of the vote.
+///
+/// Each answer is encrypted (exponential ElGamal), randomized by the randomizer
+/// and the randomizer's designated verifier proof is verified before the ballot is submitted.
+/// Votes with a credential key are cast anonymously using a voter token of a one-time account.
+pub async fn cast(
+    config: Config,
+    vote: String,
+    selections: Vec<String>,
+    keystore: Option<String>,
+    skip_randomizer: bool,
+) -> Result<(), Error> {
+    let voter = load_voter(keystore)?;
+    let voter_id = PairSigner::<NodeTemplateRuntime, Pair>::new(voter.clone())
+        .account_id()
+        .clone();
+    println!("voter: {}", voter_id.to_ss58check());
+
+    // init substrate client
+    let client = config.client().await?;
+
+    // the vote must accept ballots
+    let vote_id = vote.as_bytes().to_vec();
+    let details = get_vote(&client, vote_id.clone())
+        .await?
+        .ok_or("the vote does not exist!")?;
+    if details.phase != VotePhase::Voting {
+        return Err(Error::Other(format!(
+            "the vote is in phase: {:?}",
+            details.phase
+        )));
+    }
+
+    // map the selected labels to the allowed answers on chain
+    let options = get_options(&client, &vote_id).await?;
+    let choices = select_answers(&options, &selections)?;
+
+    let pk: ElGamalPK = get_vote_public_key(&client, vote_id.clone()).await?.into();
+
+    // votes with a credential key only accept ballots of one-time accounts
+    if let Some(key) = get_credential_key(&client, vote_id.clone()).await? {
+        let (account, token) = request_voter_token(&config, &vote, &voter, &key.into()).await?;
+        let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(account);
+        println!("one-time account: {}", signer.account_id().to_ss58check());
+
+        // randomizer signatures cannot be required, they would be bound to the one-time account.
+        // the answers are still randomized (authenticated as the voter) and the proofs verified,
+        // unless the voter prefers that the randomizer cannot link the one-time account
+        let ballot = if skip_randomizer {
+            encrypt_answers(&pk, choices)
+        } else {
+            let token = authenticate(&config, &vote_id, &voter)
+                .await
+                .map_err(|err| Error::Other(format!("failed to authenticate: {}", err)))?;
+            let (ballot, _) =
+                randomize_answers(&config, &pk, &vote_id, &voter_id, &token, choices).await?;
+            ballot
+        };
+        let response =
+            cast_anonymous_ballot(&client, &signer, vote_id, ballot, vec![], token).await?;
+        println!("ballot included in block: {:?}", response.block);
+        return Ok(());
+    }
+
+    let token = authenticate(&config, &vote_id, &voter)
+        .await
+        .map_err(|err| Error::Other(format!("failed to authenticate: {}", err)))?;
+
+    let (ballot, signatures) =
+        randomize_answers(&config, &pk, &vote_id, &voter_id, &token, choices).await?;
+
+    // submit all answers as a single ballot
+    let signer = PairSigner::<NodeTemplateRuntime, Pair>::new(voter);
+    let response = cast_ballot(&client, &signer, vote_id, ballot, signatures).await?;
+    println!("ballot included in block: {:?}", response.block);
+    Ok(())
+}
+
+/// Encrypts the answers using the exported public key and signs the ballot offline.
+///
+/// The answers cannot be randomized without network access, votes requiring
+/// a randomizer signature or a voter token are refused.
+pub async fn sign_ballot(
+    export: String,
+    selections: Vec<String>,
+    keystore: Option<String>,
+    nonce: Option<u32>,
+    file: String,
+) -> Result<(), Error> {
+    let export = read_export(&export)?;
+    if export.randomizer_signature_required || export.voter_token_required {
+        return Err(Error::Other(format!(
+            "the vote: {} requires a randomizer signature or a voter token",
+            export.vote
+        )));
+    }
+    let pk = export
+        .public_key
+        .clone()
+        .ok_or("the public key of the vote is not combined yet!")?;
+    let choices = select_answers(&export.options(), &selections)?;
+    let voter = load_voter(keystore)?;
+
+    let call = CastBallot {
+        vote_id: export.vote_id(),
+        ballot: encrypt_answers(&pk, choices),
+        signatures: vec![],
+    };
+    let index = export.calls.cast_ballot;
+    let signed = sign(&export, "cast_ballot", index, call, voter, nonce).await?;
+    write_signed(&file, &signed)
+}
+
+/// Encrypts the answers (exponential ElGamal) without randomizing them.
+pub fn encrypt_answers(pk: &ElGamalPK, choices: Vec<(TopicId, AnswerValue)>) -> Ballot {
+    let q = &pk.params.q();
+    let answers = choices
+        .into_iter()
+        .map(|(topic_id, value)| {
+            let r = Random::get_random_less_than(q);
+            let cipher = ElGamal::encrypt_encode(&BigUint::from(value), &r, pk);
+            (topic_id, cipher.into())
+        })
+        .collect();
+    Ballot { answers }
+}
+
+/// Requests a voter token for a new one-time account, returns the account and the token.
+///
+/// The message containing the one-time account is blinded before it is sent to the api,
+/// the voting authority learns the voter's identity but not the one-time account.
+pub async fn request_voter_token(
+    config: &Config,
+    vote: &str,
+    voter: &Pair,
+    key: &BlindPublicKey,
+) -> Result<(Pair, VoterToken), Error> {
+    let vote_id = vote.as_bytes().to_vec();
+    let (account, _) = Pair::generate();
+    let account_id = PairSigner::<NodeTemplateRuntime, Pair>::new(account.clone())
+        .account_id()
+        .clone();
+    let message = credential_message(&vote_id, &account_id);
+    let r = Random::get_random_less_than(&key.n);
+    let blinded = BlindSignature::blind(key, &message, &r).to_bytes_be();
+
+    // the request is signed by the voter's identity, checked against the eligible voters
+    let voter_id = PairSigner::<NodeTemplateRuntime, Pair>::new(voter.clone())
+        .account_id()
+        .clone();
+    let signature = voter.sign(&credential_request_message(&vote_id, &blinded));
+    let body = CredentialRequestBody {
+        voter: voter_id.to_ss58check(),
+        blinded: hex::encode(&blinded),
+        signature: hex::encode(signature.0),
+    };
+    let response = request_credential(config, vote, &body)
+        .await
+        .map_err(|err| Error::Other(format!("failed to request the voter token: {}", err)))?;
+
+    let blind_signature = hex::decode(&response.blind_signature)
+        .map_err(|_| Error::Other("invalid blind signature".to_string()))?;
+    let token = BlindSignature::unblind(key, &BigUint::from_bytes_be(&blind_signature), &r)
+        .filter(|token| BlindSignature::verify(key, &message, token))
+        .ok_or("the voter token is invalid!")?;
+    Ok((account, token.to_bytes_be()))
+}
+
+/// Requests the blind signature of the voter token from the api.
+pub async fn request_credential(
+    config: &Config,
+    vote: &str,
+    body: &CredentialRequestBody,
+) -> Result<CredentialResponseBody, surf::Error> {
+    let body = Body::from_json(body)?;
+    let mut response = surf::post(config.api(&format!("/credentials/{}", vote)))
+        .body(body)
+        .await?;
+    if !response.status().is_success() {
+        let message = response.body_string().await?;
+        return Err(surf::Error::from_str(response.status(), message));
+    }
+    response.body_json::<CredentialResponseBody>().await
+}
+
+/// Encrypts the answers (exponential ElGamal) and randomizes them using the randomizer.
+/// The randomizer's designated verifier proof of each answer is verified.
+pub async fn randomize_answers(
+    config: &Config,
+    pk: &ElGamalPK,
+    vote_id: &VoteId,
+    voter_id: &<NodeTemplateRuntime as System>::AccountId,
+    token: &str,
+    choices: Vec<(TopicId, AnswerValue)>,
+) -> Result<(Ballot, Vec<RandomizerSignature>), Error> {
+    let q = &pk.params.q();
+    let mut answers = Vec::with_capacity(choices.len());
+    let mut signatures = Vec::with_capacity(choices.len());
+    for (topic_id, value) in choices {
+        let r = Random::get_random_less_than(q);
+        let cipher = ElGamal::encrypt_encode(&BigUint::from(value), &r, pk);
+
+        // the proof only convinces the holder of the designated verifier key
+        let x_v = Random::get_random_less_than(q);
+        let verifier_pk = pk.params.g.modpow(&x_v, &pk.params.p);
+        let body = RequestBody {
+            vote_id: vote_id.clone(),
+            cipher: cipher.clone(),
+            verifier_pk: verifier_pk.clone(),
+            voter: voter_id.clone(),
+            token: token.to_string(),
+        };
+        let response = randomize_cipher(config, &body)
+            .await
+            .map_err(|err| Error::Other(format!("failed to randomize the answer: {}", err)))?;
+        let proof_is_valid = ReEncryptionProof::verify_designated(
+            pk,
+            &verifier_pk,
+            &response.proof,
+            &cipher,
+            &response.cipher,
+        );
+        if !proof_is_valid {
+            return Err(Error::Other(
+                "the randomizer's re-encryption proof is invalid!".to_string(),
+            ));
+        }
+        answers.push((topic_id, response.cipher.into()));
+        signatures.push(response.signature);
+    }
+    Ok((Ballot { answers }, signatures))
+}
+
+/// Authenticates the voter at the randomizer by signing a challenge, returns the token.
+pub async fn authenticate(
+    config: &Config,
+    vote_id: &VoteId,
+    voter: &Pair,
+) -> Result<String, surf::Error> {
+    let account_id = PairSigner::<NodeTemplateRuntime, Pair>::new(voter.clone())
+        .account_id()
+        .clone();
+    let body = Body::from_json(&ChallengeRequestBody {
+        vote_id: vote_id.clone(),
+        voter: account_id.clone(),
+    })?;
+    let response = surf::post(config.randomizer("/auth/challenge"))
+        .body(body)
+        .recv_json::<ChallengeResponseBody>()
+        .await?;
+
+    // sign the challenge, bound to the vote
+    let challenge = hex::decode(&response.challenge)?;
+    let message = auth_message(vote_id, &challenge);
+    let signature = voter.sign(&message);
+
+    let body = Body::from_json(&TokenRequestBody {
+        vote_id: vote_id.clone(),
+        voter: account_id,
+        signature: hex::encode(signature.0),
+    })?;
+    let response = surf::post(config.randomizer("/auth/token"))
+        .body(body)
+        .recv_json::<TokenResponseBody>()
+        .await?;
+    Ok(response.token)
+}
+
+pub async fn randomize_cipher(
+    config: &Config,
+    body: &RequestBody,
+) -> Result<ResponseBody, surf::Error> {
     let body = Body::from_json(body)?;
-    let response = surf::post("http://0.0.0.0:8080/randomize")
+    let response = surf::post(config.randomizer("/randomize"))
         .body(body)
         .recv_json::<ResponseBody>()
         .await?;
     Ok(response)
 }
+
+/// Randomizes many ciphers at once, the randomizer streams one JSON object per line.
+/// Each line is parsed as soon as it is received, the response is never buffered as a whole.
+pub async fn randomize_ciphers(
+    config: &Config,
+    body: &BatchRequestBody,
+) -> Result<Vec<BatchResponseEntry>, surf::Error> {
+    let body = Body::from_json(body)?;
+    let mut response = surf::post(config.randomizer("/randomize/batch"))
+        .body(body)
+        .await?;
+    if !response.status().is_success() {
+        let message = response.body_string().await?;
+        return Err(surf::Error::from_str(response.status(), message));
+    }
+    let mut entries = Vec::new();
+    let mut lines = BufReader::new(response).lines();
+    while let Some(line) = lines.next().await {
+        let line = line?;
+        if !line.is_empty() {
+            entries.push(serde_json::from_str::<BatchResponseEntry>(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> Vec<(Topic, Vec<TopicAnswer>)> {
+        vec![
+            (
+                (b"q1".to_vec(), b"Question 1?".to_vec()),
+                vec![(0, b"no".to_vec()), (1, b"yes".to_vec())],
+            ),
+            ((b"q2".to_vec(), b"Question 2?".to_vec()), vec![]),
+        ]
+    }
+
+    fn selections(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn it_should_select_the_answers_by_label() {
+        let choices = select_answers(&options(), &selections(&["q1=yes"])).unwrap();
+        assert_eq!(choices, vec![(b"q1".to_vec(), 1)]);
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_selections() {
+        let invalid = [
+            vec![],
+            vec!["q1"],
+            vec!["q1=maybe"],
+            vec!["q3=yes"],
+            vec!["q1=yes", "q1=no"],
+            vec!["q2=yes"],
+        ];
+        for values in invalid.iter() {
+            assert!(select_answers(&options(), &selections(values)).is_err());
+        }
+    }
+}
@@ -0,0 +1,399 @@
+use crate::config::Config;
+use async_std::task;
+use num_bigint::BigUint;
+use pallet_mixnet::types::{
+    ShuffleState, Topic, TopicAnswer, TopicId, TopicResult, VoteId, VotePhase,
+};
+use provotum_sdk::events::{self, register_type_sizes, MixnetEvent};
+use provotum_sdk::stores::{
+    AnswersStore, CiphersStore, DecryptedSharesStore, PublicKeyShareBySealerStore, PublicKeyStore,
+    SealersStore, ShuffleStateStore, TallyStore, TopicsStore, VoteStore,
+};
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+use substrate_subxt::{
+    sp_core::crypto::Ss58Codec, system::System, Client, EventSubscription, EventsDecoder, RawEvent,
+};
+use substrate_subxt::{Error, NodeTemplateRuntime};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+type Hash = <NodeTemplateRuntime as System>::Hash;
+
+/// the number of events shown in the view
+const RECENT_EVENTS: usize = 10;
+
+/// the ciphers of the ballots, before any shuffle
+const INITIAL_NUMBER_OF_SHUFFLES: u8 = 0;
+
+/// The progress of a question.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct TopicView {
+    topic: Topic,
+    answers: Vec<TopicAnswer>,
+    ballots: usize,
+    shuffle: Option<ShuffleState>,
+    // the number of decrypted shares per sealer
+    decrypted_shares: Vec<(AccountId, usize)>,
+    tally: Option<TopicResult>,
+}
+
+/// The state of the vote at a finalized block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct View {
+    vote: String,
+    block: u32,
+    phase: VotePhase,
+    public_key: bool,
+    // whether each sealer submitted its public key share
+    key_shares: Vec<(AccountId, bool)>,
+    topics: Vec<TopicView>,
+}
+
+fn label(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+fn answer_label(answers: &[TopicAnswer], value: &[u8]) -> String {
+    let value = BigUint::from_bytes_be(value);
+    answers
+        .iter()
+        .find(|(answer, _)| BigUint::from(*answer) == value)
+        .map(|(_, answer)| label(answer))
+        .unwrap_or_else(|| value.to_str_radix(10))
+}
+
+async fn snapshot(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: &VoteId,
+    block: u32,
+    hash: Hash,
+) -> Result<View, Error> {
+    let at = Some(hash);
+    let vote_store = VoteStore {
+        vote_id: vote_id.clone(),
+    };
+    let vote = client
+        .fetch(&vote_store, at)
+        .await?
+        .ok_or("the vote does not exist!")?;
+    let public_key_store = PublicKeyStore {
+        vote_id: vote_id.clone(),
+    };
+    let public_key = client.fetch(&public_key_store, at).await?.is_some();
+
+    let sealers = client.fetch_or_default(&SealersStore {}, at).await?;
+    let mut key_shares = Vec::new();
+    for sealer in sealers.iter() {
+        let store = PublicKeyShareBySealerStore {
+            vote_id: vote_id.clone(),
+            sealer: sealer.clone(),
+        };
+        key_shares.push((sealer.clone(), client.fetch(&store, at).await?.is_some()));
+    }
+
+    let topics_store = TopicsStore {
+        vote_id: vote_id.clone(),
+    };
+    let mut topics = Vec::new();
+    for topic in client.fetch_or_default(&topics_store, at).await? {
+        let topic_id = topic.0.clone();
+        let answers_store = AnswersStore {
+            topic_id: topic_id.clone(),
+        };
+        let ciphers_store = CiphersStore {
+            topic_id: topic_id.clone(),
+            nr_of_shuffles: INITIAL_NUMBER_OF_SHUFFLES,
+        };
+        let shuffle_store = ShuffleStateStore {
+            vote_id: vote_id.clone(),
+            topic_id: topic_id.clone(),
+        };
+        let tally_store = TallyStore {
+            topic_id: topic_id.clone(),
+        };
+        let mut decrypted_shares = Vec::new();
+        for sealer in sealers.iter() {
+            let store = DecryptedSharesStore {
+                topic_id: topic_id.clone(),
+                sealer: sealer.clone(),
+            };
+            let shares = client.fetch_or_default(&store, at).await?;
+            decrypted_shares.push((sealer.clone(), shares.len()));
+        }
+        topics.push(TopicView {
+            topic,
+            answers: client.fetch_or_default(&answers_store, at).await?,
+            ballots: client.fetch_or_default(&ciphers_store, at).await?.len(),
+            shuffle: client.fetch(&shuffle_store, at).await?,
+            decrypted_shares,
+            tally: client.fetch(&tally_store, at).await?,
+        });
+    }
+
+    Ok(View {
+        vote: label(vote_id),
+        block,
+        phase: vote.phase,
+        public_key,
+        key_shares,
+        topics,
+    })
+}
+
+fn render(view: &View, events: &VecDeque<String>) -> String {
+    let mut out = Vec::new();
+    out.push(format!(
+        "Vote: {}  (finalized block #{})",
+        view.vote, view.block
+    ));
+    out.push(format!("Phase: {:?}", view.phase));
+    out.push(format!(
+        "Public key: {}",
+        if view.public_key {
+            "combined"
+        } else {
+            "pending"
+        }
+    ));
+    out.push("Key shares:".to_string());
+    for (sealer, submitted) in view.key_shares.iter() {
+        let state = if *submitted { "submitted" } else { "pending" };
+        out.push(format!("  {}  {}", sealer.to_ss58check(), state));
+    }
+
+    for topic in view.topics.iter() {
+        out.push(String::new());
+        out.push(format!("Question: {}", label(&topic.topic.1)));
+        out.push(format!("  Ballots: {}", topic.ballots));
+        match &topic.shuffle {
+            Some(state) => out.push(format!(
+                "  Shuffle: iteration {}, start position {}, batch size {}{}",
+                state.iteration,
+                state.start_position,
+                state.batch_size,
+                if state.done { ", done" } else { "" }
+            )),
+            None => out.push("  Shuffle: not started".to_string()),
+        }
+        out.push("  Decrypted shares:".to_string());
+        for (sealer, shares) in topic.decrypted_shares.iter() {
+            out.push(format!("    {}  {}", sealer.to_ss58check(), shares));
+        }
+        match &topic.tally {
+            Some(tally) => {
+                out.push("  Tally:".to_string());
+                for (value, count) in tally.iter() {
+                    out.push(format!(
+                        "    {}: {}",
+                        answer_label(&topic.answers, value),
+                        BigUint::from_bytes_be(count)
+                    ));
+                }
+            }
+            None => out.push("  Tally: pending".to_string()),
+        }
+    }
+
+    out.push(String::new());
+    out.push("Recent events (best blocks):".to_string());
+    for event in events.iter() {
+        out.push(format!("  {}", event));
+    }
+    out.join("\n")
+}
+
+/// Describes the events of the mixnet pallet which belong to the vote, all others are ignored.
+fn describe(
+    raw: &RawEvent,
+    vote_id: &VoteId,
+    topics: &[TopicId],
+) -> Result<Option<String>, codec::Error> {
+    let event = match events::from_raw(raw)? {
+        Some(event) => event,
+        None => return Ok(None),
+    };
+    let (vote, details) = match &event {
+        MixnetEvent::BallotSubmitted(voter, vote, _) => {
+            (vote, format!("voter: {}", voter.to_ss58check()))
+        }
+        MixnetEvent::VotePhaseChanged(vote, phase) => (vote, format!("phase: {:?}", phase)),
+        MixnetEvent::VoteTopicQuestionStored(vote, topic) => {
+            (vote, format!("question: {}", label(&topic.1)))
+        }
+        MixnetEvent::PublicKeyCreated(vote, _) => (vote, String::new()),
+        MixnetEvent::TopicAnswersStored(vote, topic_id)
+        | MixnetEvent::TopicTallied(vote, topic_id, _) => {
+            (vote, format!("question: {}", label(topic_id)))
+        }
+        MixnetEvent::ShuffleProofSubmitted(topic_id, sealer)
+        | MixnetEvent::DecryptedShareSubmitted(topic_id, sealer) => {
+            if !topics.contains(topic_id) {
+                return Ok(None);
+            }
+            let details = format!(
+                "question: {}, sealer: {}",
+                label(topic_id),
+                sealer.to_ss58check()
+            );
+            (vote_id, details)
+        }
+        _ => return Ok(None),
+    };
+    if vote != vote_id {
+        return Ok(None);
+    }
+    Ok(Some(
+        format!("{} {}", events::variant(&event), details)
+            .trim_end()
+            .to_string(),
+    ))
+}
+
+/// Appends the events of the vote to the recent events, the events of best blocks are shown
+/// since the subscription of finalized events is not available.
+async fn follow_events(
+    client: Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    events: Arc<Mutex<VecDeque<String>>>,
+) -> Result<(), Error> {
+    let topics_store = TopicsStore {
+        vote_id: vote_id.clone(),
+    };
+    let mut topics: Vec<TopicId> = client
+        .fetch_or_default(&topics_store, None)
+        .await?
+        .into_iter()
+        .map(|(topic_id, _)| topic_id)
+        .collect();
+
+    let mut decoder = EventsDecoder::<NodeTemplateRuntime>::new(client.metadata().clone());
+    register_type_sizes(&mut decoder);
+
+    let subscription = client.subscribe_events().await?;
+    let mut subscription = EventSubscription::<NodeTemplateRuntime>::new(subscription, decoder);
+    while let Some(raw) = subscription.next().await {
+        let raw = match raw {
+            Ok(raw) => raw,
+            // e.g. the error of a failed extrinsic
+            Err(_) => continue,
+        };
+        if let Ok(Some(MixnetEvent::VoteTopicQuestionStored(vote, (topic_id, _)))) =
+            events::from_raw(&raw)
+        {
+            if vote == vote_id {
+                topics.push(topic_id);
+            }
+        }
+        if let Some(event) = describe(&raw, &vote_id, &topics)? {
+            let mut events = events.lock().unwrap();
+            events.push_back(event);
+            while events.len() > RECENT_EVENTS {
+                events.pop_front();
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Renders a live view of the vote on every finalized block.
+pub async fn watch(config: Config, vote: String) -> Result<(), Error> {
+    // init substrate client
+    let client = config.client().await?;
+    let vote_id = vote.as_bytes().to_vec();
+    let vote_store = VoteStore {
+        vote_id: vote_id.clone(),
+    };
+    client
+        .fetch(&vote_store, None)
+        .await?
+        .ok_or("the vote does not exist!")?;
+
+    let events = Arc::new(Mutex::new(VecDeque::new()));
+    let follower = follow_events(client.clone(), vote_id.clone(), events.clone());
+    task::spawn(async move {
+        if let Err(err) = follower.await {
+            eprintln!("failed to follow the events: {:?}", err);
+        }
+    });
+
+    let mut blocks = client.subscribe_finalized_blocks().await?;
+    loop {
+        let header = blocks.next().await;
+        let view = snapshot(&client, &vote_id, header.number, header.hash()).await?;
+        let events = events.lock().unwrap().clone();
+        // clear the screen before the view is rendered again
+        println!("\x1B[2J\x1B[H{}", render(&view, &events));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+    use sp_keyring::AccountKeyring;
+    use std::collections::BTreeMap;
+
+    fn event(variant: &str, data: Vec<u8>) -> RawEvent {
+        RawEvent {
+            module: "PalletMixnet".to_string(),
+            variant: variant.to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn it_should_only_describe_events_of_the_vote() {
+        let vote_id = b"vote".to_vec();
+        let topics = vec![b"question".to_vec()];
+        let bob = AccountKeyring::Bob.to_account_id();
+
+        let phase = event("VotePhaseChanged", (&vote_id, VotePhase::Voting).encode());
+        let description = describe(&phase, &vote_id, &topics).unwrap();
+        assert_eq!(
+            description,
+            Some("VotePhaseChanged phase: Voting".to_string())
+        );
+
+        let other = event(
+            "VotePhaseChanged",
+            (b"other".to_vec(), VotePhase::Voting).encode(),
+        );
+        assert_eq!(describe(&other, &vote_id, &topics).unwrap(), None);
+
+        let shuffle = event("ShuffleProofSubmitted", (&topics[0], &bob).encode());
+        assert!(describe(&shuffle, &vote_id, &topics).unwrap().is_some());
+
+        let shuffle = event("ShuffleProofSubmitted", (b"other".to_vec(), &bob).encode());
+        assert_eq!(describe(&shuffle, &vote_id, &topics).unwrap(), None);
+    }
+
+    #[test]
+    fn it_should_render_the_view() {
+        let bob = AccountKeyring::Bob.to_account_id();
+        let mut tally = BTreeMap::new();
+        tally.insert(vec![1u8], vec![3u8]);
+        let view = View {
+            vote: "vote".to_string(),
+            block: 42,
+            phase: VotePhase::Tallying,
+            public_key: true,
+            key_shares: vec![(bob.clone(), true)],
+            topics: vec![TopicView {
+                topic: (b"question".to_vec(), b"question".to_vec()),
+                answers: vec![(0, b"no".to_vec()), (1, b"yes".to_vec())],
+                ballots: 3,
+                shuffle: None,
+                decrypted_shares: vec![(bob, 3)],
+                tally: Some(tally),
+            }],
+        };
+
+        let rendered = render(&view, &VecDeque::new());
+        assert!(rendered.contains("finalized block #42"));
+        assert!(rendered.contains("Phase: Tallying"));
+        assert!(rendered.contains("Ballots: 3"));
+        assert!(rendered.contains("yes: 3"));
+    }
+}
@@ -0,0 +1,142 @@
+use crate::types::ModuloOperations;
+use alloc::vec::Vec;
+use blake2::{Blake2b, Digest};
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// the public exponent of all blind signature keys
+pub const PUBLIC_EXPONENT: u32 = 65537;
+
+/// The RSA public key used to verify blind signatures.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BlindPublicKey {
+    // modulus: n = p * q
+    pub n: BigUint,
+
+    // public exponent: e
+    pub e: BigUint,
+}
+
+/// The RSA private key used to blind-sign messages.
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub struct BlindPrivateKey {
+    pub public: BlindPublicKey,
+
+    // private exponent: d = e^-1 mod phi(n)
+    pub d: BigUint,
+}
+
+/// RSA blind signatures (Chaum) with a full domain hash.
+///
+/// 1. the requester blinds the hash of the message with a random factor r: m' = H(m) * r^e mod n
+/// 2. the signer signs the blinded message without learning it: s' = m'^d mod n
+/// 3. the requester removes the blinding factor: s = s' * r^-1 mod n = H(m)^d mod n
+/// 4. anyone can verify the signature: s^e mod n == H(m)
+#[derive(Clone, Eq, PartialEq, Debug, Hash)]
+pub struct BlindSignature;
+
+impl BlindSignature {
+    /// Hashes the message to a value in Z_n.
+    /// The Blake2 hash is expanded (counter mode) to the size of the modulus.
+    pub fn hash_message(message: &[u8], n: &BigUint) -> BigUint {
+        let size = n.to_bytes_be().len();
+        let mut digest: Vec<u8> = Vec::with_capacity(size + 64);
+        let mut counter: u32 = 0;
+        while digest.len() < size {
+            let hash = Blake2b::new()
+                .chain(b"blind")
+                .chain(counter.to_be_bytes())
+                .chain(message)
+                .finalize();
+            digest.extend_from_slice(&hash);
+            counter += 1;
+        }
+        BigUint::from_bytes_be(&digest[..size]) % n
+    }
+
+    /// Blinds the message using the blinding factor r (0 < r < n, gcd(r, n) = 1).
+    pub fn blind(pk: &BlindPublicKey, message: &[u8], r: &BigUint) -> BigUint {
+        let h = Self::hash_message(message, &pk.n);
+        h.modmul(&r.modpow(&pk.e, &pk.n), &pk.n)
+    }
+
+    /// Signs the blinded message.
+    pub fn sign(sk: &BlindPrivateKey, blinded: &BigUint) -> BigUint {
+        blinded.modpow(&sk.d, &sk.public.n)
+    }
+
+    /// Removes the blinding factor from the blind signature.
+    /// Returns None if r is not invertible mod n.
+    pub fn unblind(pk: &BlindPublicKey, blind_signature: &BigUint, r: &BigUint) -> Option<BigUint> {
+        let r_inverse = r.invmod(&pk.n)?;
+        Some(blind_signature.modmul(&r_inverse, &pk.n))
+    }
+
+    /// Verifies the (unblinded) signature of the message.
+    pub fn verify(pk: &BlindPublicKey, message: &[u8], signature: &BigUint) -> bool {
+        if pk.n.is_zero() || signature >= &pk.n {
+            return false;
+        }
+        let h = Self::hash_message(message, &pk.n);
+        signature.modpow(&pk.e, &pk.n) == h
+    }
+
+    /// Creates the private key from the two primes p and q.
+    /// Returns None if the public exponent is not invertible mod phi(n).
+    pub fn key_from_primes(p: &BigUint, q: &BigUint) -> Option<BlindPrivateKey> {
+        let one = BigUint::one();
+        let n = p * q;
+        let phi = (p - &one) * (q - &one);
+        let e = BigUint::from(PUBLIC_EXPONENT);
+        let d = e.invmod(&phi)?;
+        Some(BlindPrivateKey {
+            public: BlindPublicKey { n, e },
+            d,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{blind::BlindSignature, random::Random};
+    use num_bigint::BigUint;
+
+    #[test]
+    fn it_should_verify_an_unblinded_signature() {
+        let sk = Random::generate_blind_key(512);
+        let pk = &sk.public;
+        let message = b"vote|account";
+
+        // the signer only sees the blinded message
+        let r = Random::get_random_less_than(&pk.n);
+        let blinded = BlindSignature::blind(pk, message, &r);
+        assert_ne!(blinded, BlindSignature::hash_message(message, &pk.n));
+
+        let blind_signature = BlindSignature::sign(&sk, &blinded);
+        let signature = BlindSignature::unblind(pk, &blind_signature, &r).unwrap();
+        assert!(BlindSignature::verify(pk, message, &signature));
+
+        // the signature only covers the signed message
+        assert!(!BlindSignature::verify(pk, b"vote|other", &signature));
+        assert!(!BlindSignature::verify(pk, message, &blind_signature));
+    }
+
+    #[test]
+    fn it_should_refuse_signatures_out_of_range() {
+        let p = BigUint::from(1_000_003u32);
+        let q = BigUint::from(1_000_033u32);
+        let sk = BlindSignature::key_from_primes(&p, &q).unwrap();
+        let pk = &sk.public;
+        let signature = BlindSignature::sign(&sk, &BlindSignature::hash_message(b"m", &pk.n));
+        assert!(BlindSignature::verify(pk, b"m", &signature));
+
+        // s + n is congruent but not a valid encoding
+        let shifted = signature + &pk.n;
+        assert!(!BlindSignature::verify(pk, b"m", &shifted));
+    }
+}
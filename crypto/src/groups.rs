@@ -0,0 +1,145 @@
+//! Standardized groups, the safe primes are taken from RFC 3526 (MODP) and RFC 7919 (ffdhe).
+//!
+//! The primes are derived from the digits of π (MODP) and e (ffdhe), nobody chose them.
+
+use crate::{helper::Helper, types::ElGamalParams};
+use num_bigint::BigUint;
+
+/// A named group, the public parameters are resolved from its name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NamedGroup {
+    /// the 2048-bit MODP group 14 of RFC 3526
+    Modp2048,
+    /// the 3072-bit MODP group 15 of RFC 3526
+    Modp3072,
+    /// the 2048-bit group ffdhe2048 of RFC 7919
+    Ffdhe2048,
+    /// the 3072-bit group ffdhe3072 of RFC 7919
+    Ffdhe3072,
+}
+
+impl NamedGroup {
+    /// all named groups
+    pub const ALL: [NamedGroup; 4] = [
+        NamedGroup::Modp2048,
+        NamedGroup::Modp3072,
+        NamedGroup::Ffdhe2048,
+        NamedGroup::Ffdhe3072,
+    ];
+
+    /// The name of the group, e.g. `modp2048`.
+    pub fn name(self) -> &'static str {
+        match self {
+            NamedGroup::Modp2048 => "modp2048",
+            NamedGroup::Modp3072 => "modp3072",
+            NamedGroup::Ffdhe2048 => "ffdhe2048",
+            NamedGroup::Ffdhe3072 => "ffdhe3072",
+        }
+    }
+
+    /// Resolves a group from its name.
+    ///
+    /// Arguments
+    /// * `name` - the name of the group, e.g. `modp2048`
+    pub fn from_name(name: &[u8]) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|group| group.name().as_bytes() == name)
+            .copied()
+    }
+
+    /// The safe prime p of the group.
+    pub fn p(self) -> BigUint {
+        let hex: &[u8] = match self {
+            NamedGroup::Modp2048 => b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AACAA68FFFFFFFFFFFFFFFF",
+            NamedGroup::Modp3072 => b"FFFFFFFFFFFFFFFFC90FDAA22168C234C4C6628B80DC1CD129024E088A67CC74020BBEA63B139B22514A08798E3404DDEF9519B3CD3A431B302B0A6DF25F14374FE1356D6D51C245E485B576625E7EC6F44C42E9A637ED6B0BFF5CB6F406B7EDEE386BFB5A899FA5AE9F24117C4B1FE649286651ECE45B3DC2007CB8A163BF0598DA48361C55D39A69163FA8FD24CF5F83655D23DCA3AD961C62F356208552BB9ED529077096966D670C354E4ABC9804F1746C08CA18217C32905E462E36CE3BE39E772C180E86039B2783A2EC07A28FB5C55DF06F4C52C9DE2BCBF6955817183995497CEA956AE515D2261898FA051015728E5A8AAAC42DAD33170D04507A33A85521ABDF1CBA64ECFB850458DBEF0A8AEA71575D060C7DB3970F85A6E1E4C7ABF5AE8CDB0933D71E8C94E04A25619DCEE3D2261AD2EE6BF12FFA06D98A0864D87602733EC86A64521F2B18177B200CBBE117577A615D6C770988C0BAD946E208E24FA074E5AB3143DB5BFCE0FD108E4B82D120A93AD2CAFFFFFFFFFFFFFFFF",
+            NamedGroup::Ffdhe2048 => b"FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF",
+            NamedGroup::Ffdhe3072 => b"FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617AD3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797ABC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F619172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035BBC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91CAEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF",
+        };
+        BigUint::parse_bytes(hex, 16).unwrap()
+    }
+
+    /// The public parameters of the group.
+    ///
+    /// g = 2 is the generator of both RFCs. Since p = 7 mod 8, 2 is a quadratic residue and generates the subgroup of order q.
+    /// h is derived from the name of the group using `Helper::get_generators`, nobody knows the discrete logarithm of h to the base g.
+    pub fn params(self) -> ElGamalParams {
+        let p = self.p();
+        let h = Helper::get_generators(self.name().as_bytes(), &p, 1).remove(0);
+        ElGamalParams {
+            p,
+            g: BigUint::from(2u32),
+            h,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NamedGroup;
+    use crate::helper::Helper;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn it_should_resolve_groups_by_name() {
+        for group in NamedGroup::ALL.iter() {
+            assert_eq!(NamedGroup::from_name(group.name().as_bytes()), Some(*group));
+        }
+        assert_eq!(NamedGroup::from_name(b"modp1024"), None);
+        assert_eq!(NamedGroup::from_name(b"MODP2048"), None);
+    }
+
+    #[test]
+    fn it_should_use_the_sizes_of_the_names() {
+        assert_eq!(NamedGroup::Modp2048.p().bits(), 2048);
+        assert_eq!(NamedGroup::Modp3072.p().bits(), 3072);
+        assert_eq!(NamedGroup::Ffdhe2048.p().bits(), 2048);
+        assert_eq!(NamedGroup::Ffdhe3072.p().bits(), 3072);
+    }
+
+    #[test]
+    fn it_should_use_safe_primes_and_generators_of_the_subgroup() {
+        for group in NamedGroup::ALL.iter() {
+            let params = group.params();
+            let q = params.q();
+            assert!(Helper::is_safe_prime(&params.p), "{}", group.name());
+            assert!(Helper::is_generator(&params.p, &q, &params.g));
+            assert!(Helper::is_generator(&params.p, &q, &params.h));
+            assert_ne!(params.g, params.h);
+            assert_eq!(group.params(), params);
+        }
+    }
+
+    #[test]
+    fn it_should_match_the_rfcs() {
+        // the 64 most and least significant bits are set
+        let mask = BigUint::from(u64::MAX);
+        for group in NamedGroup::ALL.iter() {
+            let p = group.p();
+            assert_eq!(&p & &mask, mask);
+            assert_eq!(&p >> (p.bits() - 64), mask);
+        }
+
+        // the 64 bits following the most significant bits, as listed in the RFCs
+        let digits = |group: NamedGroup| {
+            let p = group.p();
+            (&p >> (p.bits() - 128)) & BigUint::from(u64::MAX)
+        };
+        assert_eq!(
+            digits(NamedGroup::Modp2048),
+            BigUint::from(0xC90FDAA22168C234u64)
+        );
+        assert_eq!(
+            digits(NamedGroup::Modp3072),
+            BigUint::from(0xC90FDAA22168C234u64)
+        );
+        assert_eq!(
+            digits(NamedGroup::Ffdhe2048),
+            BigUint::from(0xADF85458A2BB4A9Au64)
+        );
+        assert_eq!(
+            digits(NamedGroup::Ffdhe3072),
+            BigUint::from(0xADF85458A2BB4A9Au64)
+        );
+    }
+}
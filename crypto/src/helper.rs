@@ -4,6 +4,14 @@ use blake2::{Blake2b, Digest};
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
+/// the number of Miller-Rabin rounds used to check the primes of a group
+pub const PRIME_CERTAINTY: usize = 40;
+
+/// the odd primes used to sieve candidates before running Miller-Rabin
+const SMALL_PRIMES: [u32; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
 pub struct Helper;
 
 impl Helper {
@@ -164,6 +172,140 @@ impl Helper {
         vec_h
     }
 
+    /// Derives two independent generators (g, h) of G_q ∈ Z*_p from a public seed using `get_generators`.
+    /// Anyone can re-derive the generators from the seed, nobody knows the discrete logarithm of h to the base g.
+    ///
+    /// Arguments
+    /// * `seed` - the public seed
+    /// * `p` - the safe prime
+    pub fn derive_generators(seed: &[u8], p: &BigUint) -> (BigUint, BigUint) {
+        let generators = Self::get_generators(seed, p, 2);
+        (generators[0].clone(), generators[1].clone())
+    }
+
+    /// Checks that the generators (g, h) of the parameters have been derived from the seed.
+    ///
+    /// Arguments
+    /// * `params` - the public parameters
+    /// * `seed` - the public seed
+    pub fn verify_generators(params: &ElGamalParams, seed: &[u8]) -> bool {
+        let (g, h) = Self::derive_generators(seed, &params.p);
+        params.g == g && params.h == h
+    }
+
+    /// Checks that x is an element of the subgroup G_q ∈ Z*_p: 0 < x < p and x^q mod p == 1
+    ///
+    /// Arguments
+    /// * `p` - the safe prime
+    /// * `q` - the order of the subgroup: q = (p - 1) / 2
+    /// * `x` - the value to check
+    pub fn is_element_of_subgroup(p: &BigUint, q: &BigUint, x: &BigUint) -> bool {
+        !x.is_zero() && x < p && x.modpow(q, p).is_one()
+    }
+
+    /// Checks that both components of the cipher (a, b) are elements of the subgroup G_q ∈ Z*_p
+    ///
+    /// Arguments
+    /// * `p` - the safe prime
+    /// * `q` - the order of the subgroup: q = (p - 1) / 2
+    /// * `cipher` - the cipher to check
+    pub fn is_valid_cipher(p: &BigUint, q: &BigUint, cipher: &Cipher) -> bool {
+        Self::is_element_of_subgroup(p, q, &cipher.a)
+            && Self::is_element_of_subgroup(p, q, &cipher.b)
+    }
+
+    /// Miller-Rabin Primality Test, without any randomness.
+    ///
+    /// The witnesses are derived by hashing the candidate, they cannot be chosen before the candidate.
+    /// Hence, the test can be used in the runtime.
+    ///
+    /// Arguments
+    /// * `num` - the candidate
+    /// * `rounds` - the number of witnesses
+    pub fn is_probable_prime(num: &BigUint, rounds: usize) -> bool {
+        let one = BigUint::one();
+        let two = BigUint::from(2u32);
+        let three = BigUint::from(3u32);
+
+        if *num < two {
+            return false;
+        }
+        if *num == two || *num == three {
+            return true;
+        }
+        if (num % &two).is_zero() {
+            return false;
+        }
+
+        // sieve using the small primes
+        for small_prime in SMALL_PRIMES.iter() {
+            let small_prime = BigUint::from(*small_prime);
+            if *num == small_prime {
+                return true;
+            }
+            if (num % &small_prime).is_zero() {
+                return false;
+            }
+        }
+
+        // write num - 1 = 2^s * d
+        let num_less_one = num - &one;
+        let mut d = num_less_one.clone();
+        let mut s = 0usize;
+        while (&d % &two).is_zero() {
+            d /= &two;
+            s += 1;
+        }
+
+        // the witnesses are in the range [2, num - 2]
+        let range = num - &three;
+        'witnesses: for i in 0..rounds {
+            let a =
+                Self::hash_inputs_to_biguint(&[], "miller-rabin", i, num.clone()) % &range + &two;
+            let mut x = a.modpow(&d, num);
+            if x == one || x == num_less_one {
+                continue;
+            }
+            for _ in 1..s {
+                x = x.modpow(&two, num);
+                if x == num_less_one {
+                    continue 'witnesses;
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Checks that p is a safe prime: p = 2q + 1 where p and q are prime.
+    ///
+    /// q is checked using `is_probable_prime` with `PRIME_CERTAINTY` rounds.
+    /// p is then proven prime using Pocklington's criterion:
+    /// since the prime q > sqrt(p) divides p - 1, p is prime if 2^(p-1) mod p == 1 and 2^2 - 1 = 3 does not divide p.
+    ///
+    /// Arguments
+    /// * `p` - the candidate
+    pub fn is_safe_prime(p: &BigUint) -> bool {
+        let one = BigUint::one();
+        let two = BigUint::from(2u32);
+
+        if *p < BigUint::from(5u32) || (p % &two).is_zero() {
+            return false;
+        }
+
+        // sieve p using the small primes, this is cheaper than testing q
+        for small_prime in SMALL_PRIMES.iter() {
+            let small_prime = BigUint::from(*small_prime);
+            if *p != small_prime && (p % &small_prime).is_zero() {
+                return false;
+            }
+        }
+
+        let p_less_one = p - &one;
+        let q = &p_less_one / &two;
+        Self::is_probable_prime(&q, PRIME_CERTAINTY) && two.modpow(&p_less_one, p).is_one()
+    }
+
     /// Uses the Blak2 hash function and produces a hash of a BigUint. The result is returned as a Vec<u8>.
     pub fn hash_biguint(input: &BigUint) -> Vec<u8> {
         let mut hasher = Blake2b::new();
@@ -282,6 +424,28 @@ impl Helper {
         BigUint::from_bytes_be(&hash)
     }
 
+    /// Computes the hash of all inputs.
+    /// Used in the designated verifier re-encryption proof, the verifier's public key is part of the hash.
+    pub fn hash_designated_re_encryption_proof_inputs(
+        constant: &str,
+        c_one: &Cipher,
+        c_one_prime: &Cipher,
+        t2: &BigUint,
+        verifier_pk: &BigUint,
+    ) -> BigUint {
+        let hasher = Blake2b::new();
+        let hash = hasher
+            .chain(constant.as_bytes())
+            .chain(c_one.a.to_bytes_be())
+            .chain(c_one.b.to_bytes_be())
+            .chain(c_one_prime.a.to_bytes_be())
+            .chain(c_one_prime.b.to_bytes_be())
+            .chain(t2.to_bytes_be())
+            .chain(verifier_pk.to_bytes_be())
+            .finalize();
+        BigUint::from_bytes_be(&hash)
+    }
+
     /// Computes the hash of all inputs.
     ///
     /// Inputs:
@@ -361,8 +525,9 @@ impl Helper {
 
 #[cfg(test)]
 mod tests {
-    use super::Helper;
+    use super::{Helper, PRIME_CERTAINTY};
     use crate::{
+        encryption::ElGamal,
         random::Random,
         types::{Cipher, ElGamalParams},
     };
@@ -609,6 +774,137 @@ mod tests {
             .all(|gen| Helper::is_generator(&params.p, &params.q(), gen)));
     }
 
+    #[test]
+    fn it_should_check_probable_primes() {
+        // compare against trial division
+        for n in 0u32..500 {
+            let expected = n > 1 && (2..n).all(|d| n % d != 0);
+            assert_eq!(
+                Helper::is_probable_prime(&BigUint::from(n), PRIME_CERTAINTY),
+                expected,
+                "{}",
+                n
+            );
+        }
+
+        // carmichael numbers
+        for n in [561u32, 1105, 1729, 2465, 2821, 6601, 8911, 41041, 825265].iter() {
+            assert!(!Helper::is_probable_prime(
+                &BigUint::from(*n),
+                PRIME_CERTAINTY
+            ));
+        }
+
+        // 2^61 - 1 and 2^67 - 1 = 193707721 * 761838257287
+        let mersenne_61 = (BigUint::one() << 61usize) - BigUint::one();
+        assert!(Helper::is_probable_prime(&mersenne_61, PRIME_CERTAINTY));
+        let mersenne_67 = (BigUint::one() << 67usize) - BigUint::one();
+        assert!(!Helper::is_probable_prime(&mersenne_67, PRIME_CERTAINTY));
+    }
+
+    #[test]
+    fn it_should_check_safe_primes() {
+        for p in [5u32, 7, 11, 23, 47, 59, 83, 107, 167, 179, 227, 263].iter() {
+            assert!(Helper::is_safe_prime(&BigUint::from(*p)), "{}", p);
+        }
+
+        // primes which are not safe primes, composites and even numbers
+        for p in [
+            0u32, 1, 2, 3, 4, 13, 17, 29, 31, 37, 41, 43, 49, 91, 95, 561,
+        ]
+        .iter()
+        {
+            assert!(!Helper::is_safe_prime(&BigUint::from(*p)), "{}", p);
+        }
+
+        // all test systems use a safe prime
+        let systems = [
+            Helper::setup_tiny_system(),
+            Helper::setup_sm_system(),
+            Helper::setup_256bit_system(),
+            Helper::setup_512bit_system(),
+            Helper::setup_md_system(),
+            Helper::setup_lg_system(),
+            Helper::setup_xl_system(),
+        ];
+        for (params, _, _) in systems.iter() {
+            assert!(Helper::is_safe_prime(&params.p));
+        }
+
+        // p = 2q + 1 where q is composite
+        let (params, _, _) = Helper::setup_sm_system();
+        let q_composite = params.q() * BigUint::from(3u32);
+        let p = q_composite * BigUint::from(2u32) + BigUint::one();
+        assert!(!Helper::is_safe_prime(&p));
+    }
+
+    #[test]
+    fn it_should_check_subgroup_membership() {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let p = &params.p;
+        let q = params.q();
+        let one = BigUint::one();
+
+        assert!(Helper::is_element_of_subgroup(p, &q, &params.g));
+        assert!(Helper::is_element_of_subgroup(p, &q, &params.h));
+        assert!(Helper::is_element_of_subgroup(p, &q, &pk.h));
+        assert!(Helper::is_element_of_subgroup(p, &q, &one));
+
+        // 0, p and p - 1 (of order 2) are not in the subgroup
+        assert!(!Helper::is_element_of_subgroup(p, &q, &BigUint::from(0u32)));
+        assert!(!Helper::is_element_of_subgroup(p, &q, p));
+        assert!(!Helper::is_element_of_subgroup(p, &q, &(p - &one)));
+        assert!(!Helper::is_element_of_subgroup(p, &q, &(p + &params.g)));
+    }
+
+    #[test]
+    fn it_should_check_ciphers() {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let p = &params.p;
+        let q = params.q();
+        let r = BigUint::from(7u32);
+        let cipher = ElGamal::encrypt_encode(&BigUint::from(1u32), &r, &pk);
+        assert!(Helper::is_valid_cipher(p, &q, &cipher));
+
+        // a or b outside of the subgroup
+        let p_less_one = p - BigUint::one();
+        let invalid_a = Cipher {
+            a: p_less_one.clone(),
+            b: cipher.b.clone(),
+        };
+        let invalid_b = Cipher {
+            a: cipher.a.clone(),
+            b: BigUint::from(0u32),
+        };
+        let invalid_range = Cipher {
+            a: cipher.a.clone(),
+            b: &cipher.b + p,
+        };
+        assert!(!Helper::is_valid_cipher(p, &q, &invalid_a));
+        assert!(!Helper::is_valid_cipher(p, &q, &invalid_b));
+        assert!(!Helper::is_valid_cipher(p, &q, &invalid_range));
+    }
+
+    #[test]
+    fn it_should_derive_generators_from_a_seed() {
+        let seed = b"2020-12-12_01";
+        let (params, _, _) = Helper::setup_md_system();
+        let q = params.q();
+
+        let (g, h) = Helper::derive_generators(seed, &params.p);
+        assert_ne!(g, h);
+        assert!(Helper::is_generator(&params.p, &q, &g));
+        assert!(Helper::is_generator(&params.p, &q, &h));
+        assert_eq!(
+            Helper::derive_generators(seed, &params.p),
+            (g.clone(), h.clone())
+        );
+
+        let derived = ElGamalParams { p: params.p, g, h };
+        assert!(Helper::verify_generators(&derived, seed));
+        assert!(!Helper::verify_generators(&derived, b"2020-12-12_02"));
+    }
+
     #[test]
     fn it_should_hash_vec_biguints_to_biguint() {
         let one = BigUint::one();
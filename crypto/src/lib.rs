@@ -33,3 +33,8 @@ pub mod types;
 #[allow(clippy::many_single_char_names)]
 #[macro_use]
 pub mod proofs;
+
+#[allow(clippy::many_single_char_names)]
+pub mod blind;
+
+pub mod groups;
@@ -77,6 +77,8 @@ impl DecryptionProof {
     /// CheckDecryptionProof Algorithm 8.51 (CHVoteSpec 3.2)
     ///
     /// Verifies a proof of knowledge of a secret key (sk) that belongs to a public key (pk = g^sk) using the Schnorr protocol. It is a proof of knowledge of a discrete logarithm of x = log_g(g^x).
+    ///
+    /// The proof is invalid if the public key share, an encryption or a partial decryption is not an element of G_q.
     pub fn verify(
         params: &ElGamalParams,
         pk: &BigUint, // public key of public key share -> not system public key
@@ -84,6 +86,33 @@ impl DecryptionProof {
         vec_e: Vec<Cipher>,
         vec_c: Vec<BigUint>,
         id: &[u8],
+    ) -> bool {
+        let q = &params.q();
+        let p = &params.p;
+
+        // the public key share, the encryptions and the partial decryptions must be elements of G_q
+        if !Helper::is_element_of_subgroup(p, q, pk)
+            || !vec_e
+                .iter()
+                .all(|e| Helper::is_element_of_subgroup(p, q, &e.a))
+            || !vec_c
+                .iter()
+                .all(|c| Helper::is_element_of_subgroup(p, q, c))
+        {
+            return false;
+        }
+        Self::verify_unchecked(params, pk, proof, vec_e, vec_c, id)
+    }
+
+    /// Verifies the proof like `verify` without checking that the inputs are elements of G_q.
+    /// Only use it if the caller has already checked each element, e.g. when it was stored.
+    pub fn verify_unchecked(
+        params: &ElGamalParams,
+        pk: &BigUint,
+        proof: &DecryptionProof,
+        vec_e: Vec<Cipher>,
+        vec_c: Vec<BigUint>,
+        id: &[u8],
     ) -> bool {
         assert!(
             vec_e.len() == vec_c.len(),
@@ -185,6 +214,42 @@ mod tests {
         assert!(is_correct);
     }
 
+    #[test]
+    fn it_should_not_verify_decryption_proof_outside_of_the_subgroup() {
+        let sealer_id = "Mallory".as_bytes();
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let p = &params.p;
+        let encryptions = Random::generate_random_encryptions(&pk, q, 3);
+
+        // negate the first partial decryption: -c_1 is not an element of G_q
+        // for an even challenge (-c_1)^c = c_1^c and the proof would hold
+        let mut decryptions = encryptions
+            .iter()
+            .map(|cipher| ElGamal::partial_decrypt_a(cipher, &sk))
+            .collect::<Vec<BigUint>>();
+        decryptions[0] = p - &decryptions[0];
+
+        let proof = loop {
+            let r = Random::get_random_less_than(q);
+            let proof = DecryptionProof::generate(
+                &params,
+                &sk.x,
+                &pk.h,
+                &r,
+                encryptions.clone(),
+                decryptions.clone(),
+                sealer_id,
+            );
+            if (&proof.challenge % 2u32).is_zero() {
+                break proof;
+            }
+        };
+        let is_correct =
+            DecryptionProof::verify(&params, &pk.h, &proof, encryptions, decryptions, sealer_id);
+        assert!(!is_correct);
+    }
+
     #[test]
     fn it_should_verify_decryption_proof_multiple_partial_decryptions() {
         // create system parameters
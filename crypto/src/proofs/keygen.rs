@@ -63,11 +63,27 @@ impl KeyGenerationProof {
     /// 2. recompute the challenge c
     /// 3. verify that the challenge is correct
     /// 4. verify that: g^d == b * h^c
+    ///
+    /// The proof is invalid if the public key share is not an element of G_q.
     pub fn verify(
         params: &ElGamalParams,
         pk_share: &BigUint,
         proof: &KeyGenerationProof,
         id: &[u8],
+    ) -> bool {
+        if !Helper::is_element_of_subgroup(&params.p, &params.q(), pk_share) {
+            return false;
+        }
+        Self::verify_unchecked(params, pk_share, proof, id)
+    }
+
+    /// Verifies the proof like `verify` without checking that the public key share is an element of G_q.
+    /// Only use it if the caller has already checked the share, e.g. before it is stored.
+    pub fn verify_unchecked(
+        params: &ElGamalParams,
+        pk_share: &BigUint,
+        proof: &KeyGenerationProof,
+        id: &[u8],
     ) -> bool {
         // system parameters
         let g = &params.g;
@@ -106,6 +122,7 @@ impl KeyGenerationProof {
 mod tests {
     use crate::{helper::Helper, proofs::keygen::KeyGenerationProof, random::Random};
     use num_bigint::BigUint;
+    use num_traits::{One, Zero};
 
     #[test]
     fn it_should_create_keygen_proof_tiny() {
@@ -130,4 +147,27 @@ mod tests {
         let is_correct = KeyGenerationProof::verify(&params, &pk.h, &proof, sealer_id);
         assert!(is_correct);
     }
+
+    #[test]
+    fn it_should_not_verify_keygen_proof_outside_of_the_subgroup() {
+        // p - 1 has order 2, for an even challenge (p - 1)^c = 1 and the proof holds without any secret key
+        let sealer_id = "Mallory".as_bytes();
+        let (params, _, _) = Helper::setup_sm_system();
+        let h = &params.p - BigUint::one();
+        let q = params.q();
+
+        let mut d = BigUint::one();
+        let proof = loop {
+            let b = params.g.modpow(&d, &params.p);
+            let c = Helper::hash_key_gen_proof_inputs(sealer_id, "keygen", &h, &b) % &q;
+            if (&c % 2u32).is_zero() {
+                break KeyGenerationProof {
+                    challenge: c,
+                    response: d,
+                };
+            }
+            d += 1u32;
+        };
+        assert!(!KeyGenerationProof::verify(&params, &h, &proof, sealer_id));
+    }
 }
@@ -79,15 +79,12 @@ impl ReEncryptionProof {
     ) -> bool {
         // common parameters
         let p = &pk.params.p;
-        let g = &pk.params.g;
         let q = &pk.params.q();
 
         // deconstruct the proof
-        let challenge = &proof.challenge;
         let c_one_prime = &proof.c_one_prime;
         let h1 = &proof.h1;
         let h2 = &proof.h2;
-        let s2 = &proof.s2;
         let t2 = &proof.t2;
 
         // recompute c_one -> publicly known encryption of 1 using r1
@@ -106,28 +103,195 @@ impl ReEncryptionProof {
         // verify that the hashes are the same
         let v1 = h_prime == h;
 
+        // verify the commitments
+        let v2_v3 = Self::verify_commitments(pk, &pk.h, proof, &c_one);
+
+        // the proof is correct if all three checks pass
+        v1 && v2_v3
+    }
+
+    /// Generates a designated verifier re-encryption proof.
+    ///
+    /// Only convincing to the holder of the secret key belonging to `verifier_pk` (i.e. the voter).
+    /// Since the voter can simulate such a proof for any pair of ciphers (see `simulate_designated`),
+    /// the proof cannot be used to show a coercer how the re-encrypted ballot relates to the original one.
+    pub fn generate_designated(
+        r1: &BigUint, // random value r1 that was used to re_encrypt
+        r2: &BigUint,
+        h2: &BigUint,
+        s2: &BigUint,
+        c_one: &Cipher, // publicly known encryption of 1 using r1
+        pk: &PublicKey,
+        verifier_pk: &BigUint, // public key of the designated verifier: g^x_v mod p
+    ) -> ReEncryptionProof {
+        // common parameters
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+        let g = &pk.params.g;
+
+        // compute new random encryption of one
+        let one = BigUint::one();
+        let c_one_prime = ElGamal::encrypt(&one, r2, pk);
+
+        // generate the commitment using the designated verifier's public key
+        // t2 = g^s2 * verifier_pk^-h2 mod p = g^s2 / verifier_pk^h2 mod p
+        let g_pow_s2 = g.modpow(s2, p);
+        let verifier_pk_pow_h2 = verifier_pk.modpow(h2, p);
+        let t2 = g_pow_s2
+            .moddiv(&verifier_pk_pow_h2, p)
+            .expect("cannot compute mod_inverse in mod_div!");
+
+        // generate the challenge -> hash the commitment + the public values
+        let mut h = Helper::hash_designated_re_encryption_proof_inputs(
+            "designated_re_encryption",
+            c_one,
+            &c_one_prime,
+            &t2,
+            verifier_pk,
+        );
+        h %= q;
+
+        // split the hash into two parts h1 = h - h2
+        let h1 = h.modsub(h2, q);
+
+        // compute the challenge
+        let challenge = h1.modmul(r1, q).modadd(r2, q);
+        ReEncryptionProof {
+            c_one_prime,
+            challenge,
+            h1,
+            h2: h2.clone(),
+            s2: s2.clone(),
+            t2,
+        }
+    }
+
+    /// Simulates a designated verifier re-encryption proof for an arbitrary pair of ciphers.
+    ///
+    /// Requires the designated verifier's secret key. The simulated proof is indistinguishable from a real one,
+    /// which is why a designated verifier proof doesn't convince anyone else.
+    pub fn simulate_designated(
+        challenge: &BigUint,
+        h1: &BigUint,
+        w: &BigUint,
+        cipher: &Cipher,
+        re_enc_cipher: &Cipher,
+        pk: &PublicKey,
+        verifier_sk: &BigUint, // secret key of the designated verifier: x_v
+    ) -> ReEncryptionProof {
+        // common parameters
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+        let g = &pk.params.g;
+        let verifier_pk = g.modpow(verifier_sk, p);
+
+        // the (claimed) encryption of one
+        let c_one = ElGamal::homomorphic_subtraction(re_enc_cipher, cipher, p);
+
+        // choose c_one_prime such that: E(1,challenge) = h1 * c_one homomorphic_addition c_one_prime
+        let one = BigUint::one();
+        let e_one = ElGamal::encrypt(&one, challenge, pk);
+        let h1_c_one = ElGamal::homomorphic_multiply(&c_one, h1, p);
+        let c_one_prime = ElGamal::homomorphic_subtraction(&e_one, &h1_c_one, p);
+
+        // the commitment t2 = g^w
+        let t2 = g.modpow(w, p);
+
+        // compute the hash and split it into: h2 = h - h1
+        let mut h = Helper::hash_designated_re_encryption_proof_inputs(
+            "designated_re_encryption",
+            &c_one,
+            &c_one_prime,
+            &t2,
+            &verifier_pk,
+        );
+        h %= q;
+        let h2 = h.modsub(h1, q);
+
+        // use the trapdoor (the secret key) to answer: s2 = w + x_v * h2
+        let s2 = w.modadd(&verifier_sk.modmul(&h2, q), q);
+
+        ReEncryptionProof {
+            c_one_prime,
+            challenge: challenge.clone(),
+            h1: h1.clone(),
+            h2,
+            s2,
+            t2,
+        }
+    }
+
+    /// Verifies a designated verifier re-encryption proof.
+    pub fn verify_designated(
+        pk: &PublicKey,
+        verifier_pk: &BigUint,
+        proof: &ReEncryptionProof,
+        cipher: &Cipher,
+        re_enc_cipher: &Cipher,
+    ) -> bool {
+        // common parameters
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+
+        // recompute c_one -> publicly known encryption of 1 using r1
+        let c_one = ElGamal::homomorphic_subtraction(re_enc_cipher, cipher, p);
+
+        // recompute the hash, bound to the designated verifier's public key
+        let mut h_prime = Helper::hash_designated_re_encryption_proof_inputs(
+            "designated_re_encryption",
+            &c_one,
+            &proof.c_one_prime,
+            &proof.t2,
+            verifier_pk,
+        );
+        h_prime %= q;
+
+        // add the two hash parts from the prover
+        let h = proof.h1.modadd(&proof.h2, q);
+
+        // verify that the hashes are the same
+        let v1 = h_prime == h;
+
+        // verify the commitments
+        let v2_v3 = Self::verify_commitments(pk, verifier_pk, proof, &c_one);
+
+        // the proof is correct if all three checks pass
+        v1 && v2_v3
+    }
+
+    /// Verifies both commitments of the proof
+    /// - E(1,challenge) == h1 * c_one homomorphic_addition c_one_prime
+    /// - g^s2 == key^h2 * t2
+    fn verify_commitments(
+        pk: &PublicKey,
+        key: &BigUint,
+        proof: &ReEncryptionProof,
+        c_one: &Cipher,
+    ) -> bool {
+        let p = &pk.params.p;
+        let g = &pk.params.g;
+
         // verify the commitment: E(1,challenge) = h1 * c_one homomorphic_addition c_one_prime
         // 1. compute the left hand side E(1,challenge)
         let one = BigUint::one();
-        let lhs = ElGamal::encrypt(&one, challenge, pk);
+        let lhs = ElGamal::encrypt(&one, &proof.challenge, pk);
 
         // 2. compute the right hand side h1 * c_one homomorphic_addition c_one_prime
-        let h1_c_one = ElGamal::homomorphic_multiply(&c_one, h1, p);
-        let rhs = ElGamal::homomorphic_addition(&h1_c_one, c_one_prime, p);
+        let h1_c_one = ElGamal::homomorphic_multiply(c_one, &proof.h1, p);
+        let rhs = ElGamal::homomorphic_addition(&h1_c_one, &proof.c_one_prime, p);
 
         // verify that lhs == rhs
         let v2 = lhs == rhs;
 
-        // 3. test: verify that g^s2 == pk^c2 * t2
-        let lhs = g.modpow(s2, p);
-        let pk_pow_h2 = pk.h.modpow(h2, p);
-        let rhs = pk_pow_h2.modmul(t2, p);
+        // 3. test: verify that g^s2 == key^h2 * t2
+        let lhs = g.modpow(&proof.s2, p);
+        let key_pow_h2 = key.modpow(&proof.h2, p);
+        let rhs = key_pow_h2.modmul(&proof.t2, p);
 
         // verify that lhs == rhs
         let v3 = lhs == rhs;
 
-        // the proof is correct if all three checks pass
-        v1 && v2 && v3
+        v2 && v3
     }
 }
 
@@ -179,4 +343,83 @@ mod tests {
             assert!(proof_is_valid);
         }
     }
+
+    #[test]
+    fn it_should_verify_designated_re_encryption_proofs() {
+        // test setup
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        // the voter's designated verifier key pair
+        let x_v = Random::get_random_less_than(q);
+        let pk_v = params.g.modpow(&x_v, &params.p);
+
+        // 1. the voter encrypts his vote
+        let vote = BigUint::from(3u32);
+        let r0 = Random::get_random_less_than(q);
+        let ballot = ElGamal::encrypt(&vote, &r0, &pk);
+
+        // 2. the randomizer re-encrypts the ballot
+        let r1 = Random::get_random_less_than(q);
+        let ballot_prime = ElGamal::re_encrypt(&ballot, &r1, &pk);
+        let one = BigUint::one();
+        let c_one = ElGamal::encrypt(&one, &r1, &pk);
+
+        // 3. the randomizer generates a proof designated to the voter
+        let r2 = Random::get_random_less_than(q);
+        let h2 = Random::get_random_less_than(q);
+        let s2 = Random::get_random_less_than(q);
+        let proof =
+            ReEncryptionProof::generate_designated(&r1, &r2, &h2, &s2, &c_one, &pk, &pk_v);
+
+        // 4. the voter verifies the re-encryption proof
+        let proof_is_valid =
+            ReEncryptionProof::verify_designated(&pk, &pk_v, &proof, &ballot, &ballot_prime);
+        assert!(proof_is_valid);
+
+        // 5. the proof is not valid for a different designated verifier
+        let other_x_v = Random::get_random_less_than(q);
+        let other_pk_v = params.g.modpow(&other_x_v, &params.p);
+        let proof_is_valid =
+            ReEncryptionProof::verify_designated(&pk, &other_pk_v, &proof, &ballot, &ballot_prime);
+        assert!(!proof_is_valid);
+    }
+
+    #[test]
+    fn it_should_simulate_designated_re_encryption_proofs() {
+        // test setup
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        // the voter's designated verifier key pair
+        let x_v = Random::get_random_less_than(q);
+        let pk_v = params.g.modpow(&x_v, &params.p);
+
+        // the voter's real ballot
+        let r0 = Random::get_random_less_than(q);
+        let ballot = ElGamal::encrypt(&BigUint::from(1u32), &r0, &pk);
+
+        // a ballot which is NOT a re-encryption of the real ballot
+        let r1 = Random::get_random_less_than(q);
+        let fake_ballot = ElGamal::encrypt(&BigUint::from(2u32), &r1, &pk);
+
+        // the voter uses the secret key to simulate a proof
+        let challenge = Random::get_random_less_than(q);
+        let h1 = Random::get_random_less_than(q);
+        let w = Random::get_random_less_than(q);
+        let proof = ReEncryptionProof::simulate_designated(
+            &challenge,
+            &h1,
+            &w,
+            &ballot,
+            &fake_ballot,
+            &pk,
+            &x_v,
+        );
+
+        // the simulated proof verifies -> it doesn't convince a coercer
+        let proof_is_valid =
+            ReEncryptionProof::verify_designated(&pk, &pk_v, &proof, &ballot, &fake_ballot);
+        assert!(proof_is_valid);
+    }
 }
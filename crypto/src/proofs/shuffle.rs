@@ -1,4 +1,4 @@
-use crate::types::{BigT, BigY, ElGamalParams, ModuloOperations};
+use crate::types::{BigS, BigT, BigY, ElGamalParams, ModuloOperations};
 use crate::{
     helper::Helper,
     types::{Cipher, PermutationCommitment, PublicKey},
@@ -7,8 +7,15 @@ use alloc::{vec, vec::Vec};
 use num_bigint::BigUint;
 use num_traits::{One, Zero};
 
-#[derive(Clone, Eq, PartialEq, Debug, Hash)]
-pub struct ShuffleProof;
+/// Algorithm 8.47: The ShuffleProof
+#[allow(non_snake_case)]
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct ShuffleProof {
+    pub challenge: BigUint,                          // challenge
+    pub S: BigS,                                     // S
+    pub permutation_commitments: Vec<BigUint>,       // permutation_commitments
+    pub permutation_chain_commitments: Vec<BigUint>, // permutation_chain_commitments
+}
 
 impl ShuffleProof {
     /// Generates a commitment to a permutation by committing to the columns of the corresponding permutation matrix.
@@ -193,6 +200,175 @@ impl ShuffleProof {
         let value = Helper::hash_challenge_inputs(public_value, public_commitment);
         value % q
     }
+
+    /// CheckShuffleProof Algorithm 8.48 (CHVoteSpec 3.2)
+    ///
+    /// Checks the correctness of a shuffle proof generated by Algorithm 8.47.
+    /// The public values are the ElGamal encryptions e and e~ and
+    /// the public encryption key pk. Malformed proofs are invalid.
+    ///
+    /// Inputs:
+    /// - id: &[u8] "the id of the topic (vote question)"
+    /// - encryptions: Vec<Cipher>
+    /// - shuffled_encryptions: Vec<Cipher>
+    /// - pk: PublicKey
+    ///
+    /// The proof is invalid if an encryption or a commitment is not an element of G_q.
+    pub fn verify(
+        &self,
+        id: &[u8],
+        encryptions: Vec<Cipher>,
+        shuffled_encryptions: Vec<Cipher>,
+        pk: &PublicKey,
+    ) -> bool {
+        // the encryptions must be elements of G_q
+        let p = &pk.params.p;
+        let q = &pk.params.q();
+        if !encryptions
+            .iter()
+            .chain(shuffled_encryptions.iter())
+            .all(|cipher| Helper::is_valid_cipher(p, q, cipher))
+        {
+            return false;
+        }
+        self.verify_unchecked(id, encryptions, shuffled_encryptions, pk)
+    }
+
+    /// Verifies the proof like `verify` without checking that the encryptions are elements of G_q.
+    /// Only use it if the caller has already checked each cipher, e.g. when it was stored.
+    /// The commitments of the proof are always checked.
+    pub fn verify_unchecked(
+        &self,
+        id: &[u8],
+        encryptions: Vec<Cipher>,
+        shuffled_encryptions: Vec<Cipher>,
+        pk: &PublicKey,
+    ) -> bool {
+        let e = encryptions;
+        let e_tilde = shuffled_encryptions;
+        let challenge = &self.challenge;
+        let s = &self.S;
+        let vec_c = &self.permutation_commitments;
+        let vec_c_hat = &self.permutation_chain_commitments;
+
+        // the size of the shuffle (# of encrypted votes)
+        let size = e.len();
+        if size == 0
+            || e_tilde.len() != size
+            || vec_c.len() != size
+            || vec_c_hat.len() != size
+            || s.vec_s_hat.len() != size
+            || s.vec_s_tilde.len() != size
+        {
+            return false;
+        }
+        let params = &pk.params;
+        let g = &params.g;
+        let h = &params.h;
+        let p = &params.p;
+        let q = &params.q();
+
+        // the commitments must be elements of G_q
+        let is_element = |x: &BigUint| Helper::is_element_of_subgroup(p, q, x);
+        if !vec_c.iter().all(is_element) || !vec_c_hat.iter().all(is_element) {
+            return false;
+        }
+
+        // get {size} independent generators: vec_h
+        let vec_h = Helper::get_generators(id, p, size);
+
+        // get {size} challenges
+        // vec_u = get_challenges(size, hash(e, e_tilde, vec_c, pk))
+        let vec_u = Self::get_challenges(size, e.clone(), e_tilde.clone(), vec_c.clone(), pk);
+
+        // get c_flat = Π(c_i) / Π(vec_h_i) mod p
+        let prod_vec_c = vec_c.iter().fold(BigUint::one(), |prod, c| prod.modmul(c, p));
+        let prod_h = vec_h.iter().fold(BigUint::one(), |prod, gen| prod.modmul(gen, p));
+        let c_flat = match prod_vec_c.moddiv(&prod_h, p) {
+            Some(c_flat) => c_flat,
+            None => return false,
+        };
+
+        // get u = Π(vec_u_i) mod q
+        let u = vec_u.iter().fold(BigUint::one(), |product, u| product.modmul(u, q));
+
+        // get value c_hat = c_hat_n / h^u mod p
+        let c_hat = match vec_c_hat[size - 1].moddiv(&h.modpow(&u, p), p) {
+            Some(c_hat) => c_hat,
+            None => return false,
+        };
+
+        // get value c_tilde = Π(c_i^u_i) mod p
+        let c_tilde = Self::multiply_a_pow_b(vec_c, &vec_u, p);
+
+        // a_tilde = Π(a_i^u_i) mod p, b_tilde = Π(b_i^u_i) mod p
+        let vec_a: Vec<BigUint> = e.iter().map(|v| v.a.clone()).collect();
+        let vec_b: Vec<BigUint> = e.iter().map(|v| v.b.clone()).collect();
+        let a_tilde = Self::multiply_a_pow_b(&vec_a, &vec_u, p);
+        let b_tilde = Self::multiply_a_pow_b(&vec_b, &vec_u, p);
+
+        // vec_t_hat_i = c_hat_i^challenge * g^s_hat_i * c_hat_(i-1)^s_tilde_i mod p
+        // with c_hat_0 = h (the 2. public generator)
+        let mut vec_t_hat = Vec::new();
+        for i in 0..size {
+            let c_hat_i_minus_1 = if i == 0 { h } else { &vec_c_hat[i - 1] };
+            let t_hat_i = vec_c_hat[i]
+                .modpow(challenge, p)
+                .modmul(&g.modpow(&s.vec_s_hat[i], p), p)
+                .modmul(&c_hat_i_minus_1.modpow(&s.vec_s_tilde[i], p), p);
+            vec_t_hat.push(t_hat_i);
+        }
+
+        // t1 = c_flat^challenge * g^s1 mod p
+        let t1 = c_flat.modpow(challenge, p).modmul(&g.modpow(&s.s1, p), p);
+
+        // t2 = c_hat^challenge * g^s2 mod p
+        let t2 = c_hat.modpow(challenge, p).modmul(&g.modpow(&s.s2, p), p);
+
+        // t3 = c_tilde^challenge * g^s3 * Π(h_i^s_tilde_i) mod p
+        let prod_h_s_tilde = Self::multiply_a_pow_b(&vec_h, &s.vec_s_tilde, p);
+        let t3 = c_tilde
+            .modpow(challenge, p)
+            .modmul(&g.modpow(&s.s3, p), p)
+            .modmul(&prod_h_s_tilde, p);
+
+        // pk and g are swapped since our encryption contains (a,b) with a = g^r
+        // for an explanation see: Verifiable Re-Encryption Mixnets (Haenni, Locher, Koenig, Dubuis) page 9
+        // t4_1 = a_tilde^challenge * g^-s4 * Π(a_tilde_i^s_tilde_i) mod p
+        // t4_2 = b_tilde^challenge * pk^-s4 * Π(b_tilde_i^s_tilde_i) mod p
+        let g_pow_minus_s4 = match g.modpow(&s.s4, p).invmod(p) {
+            Some(value) => value,
+            None => return false,
+        };
+        let pk_pow_minus_s4 = match pk.h.modpow(&s.s4, p).invmod(p) {
+            Some(value) => value,
+            None => return false,
+        };
+        let vec_a_tilde: Vec<BigUint> = e_tilde.iter().map(|v| v.a.clone()).collect();
+        let vec_b_tilde: Vec<BigUint> = e_tilde.iter().map(|v| v.b.clone()).collect();
+        let t4_1 = a_tilde
+            .modpow(challenge, p)
+            .modmul(&g_pow_minus_s4, p)
+            .modmul(&Self::multiply_a_pow_b(&vec_a_tilde, &s.vec_s_tilde, p), p);
+        let t4_2 = b_tilde
+            .modpow(challenge, p)
+            .modmul(&pk_pow_minus_s4, p)
+            .modmul(&Self::multiply_a_pow_b(&vec_b_tilde, &s.vec_s_tilde, p), p);
+
+        // recompute the challenge from (y, t)
+        // public value y = (e, e_tilde, vec_c, vec_c_hat, public_key) -> public_key = component h of pk
+        // public commitment t = (t1, t2, t3, (t4_1, t4_2), (t_hat_0, ..., t_hat_(size-1)))
+        let public_value: BigY = (e, e_tilde, vec_c.clone(), vec_c_hat.clone(), &pk.h);
+        let public_commitment: BigT = (t1, t2, t3, t4_1, t4_2, vec_t_hat);
+        Self::get_challenge(public_value, public_commitment, q) == *challenge
+    }
+
+    /// Π(a_i^b_i) mod p
+    fn multiply_a_pow_b(a: &[BigUint], b: &[BigUint], modulus: &BigUint) -> BigUint {
+        a.iter().zip(b.iter()).fold(BigUint::one(), |prod, (a_i, b_i)| {
+            prod.modmul(&a_i.modpow(b_i, modulus), modulus)
+        })
+    }
 }
 
 #[cfg(test)]
@@ -546,4 +722,44 @@ mod tests {
             commitent_chain.randoms.len()
         );
     }
+
+    #[test]
+    fn it_should_reject_malformed_shuffle_proofs() {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let topic_id = "Topic1".as_bytes();
+        let cipher = Cipher {
+            a: BigUint::one(),
+            b: BigUint::one(),
+        };
+
+        // an empty proof
+        let proof = ShuffleProof::default();
+        assert!(!proof.verify(topic_id, vec![cipher.clone()], vec![cipher.clone()], &pk));
+        assert!(!proof.verify(topic_id, Vec::new(), Vec::new(), &pk));
+
+        // the vectors of the proof must match the size of the shuffle
+        let mut proof = ShuffleProof::default();
+        proof.permutation_commitments = vec![BigUint::one()];
+        proof.permutation_chain_commitments = vec![BigUint::one()];
+        proof.S.vec_s_hat = vec![BigUint::zero()];
+        proof.S.vec_s_tilde = vec![BigUint::zero()];
+        let e = vec![cipher.clone(), cipher.clone()];
+        assert!(!proof.verify(topic_id, e.clone(), e, &pk));
+
+        // the encryptions must be elements of G_q
+        let invalid = Cipher {
+            a: &pk.params.p - BigUint::one(),
+            b: BigUint::one(),
+        };
+        assert!(!proof.verify(topic_id, vec![cipher.clone()], vec![invalid.clone()], &pk));
+        assert!(!proof.verify(topic_id, vec![invalid], vec![cipher.clone()], &pk));
+
+        // the commitments must be elements of G_q, also if the encryptions are not checked
+        let mut proof = ShuffleProof::default();
+        proof.permutation_commitments = vec![&pk.params.p - BigUint::one()];
+        proof.permutation_chain_commitments = vec![BigUint::one()];
+        proof.S.vec_s_hat = vec![BigUint::zero()];
+        proof.S.vec_s_tilde = vec![BigUint::zero()];
+        assert!(!proof.verify_unchecked(topic_id, vec![cipher.clone()], vec![cipher], &pk));
+    }
 }
@@ -1,6 +1,8 @@
 use crate::{
+    blind::{BlindPrivateKey, BlindSignature},
     encryption::ElGamal,
-    types::{Cipher, PublicKey},
+    helper::Helper,
+    types::{Cipher, ElGamalParams, PublicKey},
 };
 use alloc::vec::Vec;
 use core::ops::{AddAssign, Sub};
@@ -183,6 +185,54 @@ impl Random {
         candidate
     }
 
+    /// Generates a safe prime p = 2q + 1 of exactly `bit_size` bits
+    ///
+    /// Arguments
+    /// * `bit_size` - size of the safe prime, at least 3
+    pub fn generate_safe_prime(bit_size: u64) -> BigUint {
+        assert!(bit_size >= 3, "a safe prime has at least 3 bits!");
+        let mut rng = rand::thread_rng();
+        let one = BigUint::one();
+
+        loop {
+            // q has bit_size - 1 bits and is odd
+            let q = rng.gen_biguint(bit_size - 1) | (&one << (bit_size - 2)) | &one;
+            let p = (q << 1) + &one;
+            if Helper::is_safe_prime(&p) {
+                return p;
+            }
+        }
+    }
+
+    /// Generates the public parameters of a safe-prime group of `bit_size` bits.
+    /// The generators are derived from the seed, see `Helper::derive_generators`.
+    ///
+    /// Arguments
+    /// * `bit_size` - size of the safe prime
+    /// * `seed` - the public seed of the generators
+    pub fn generate_safe_prime_group(bit_size: u64, seed: &[u8]) -> ElGamalParams {
+        let p = Self::generate_safe_prime(bit_size);
+        let (g, h) = Helper::derive_generators(seed, &p);
+        ElGamalParams { p, g, h }
+    }
+
+    /// Generates a RSA key to blind-sign messages
+    ///
+    /// Arguments
+    /// * `bit_size` - size of the modulus n = p * q
+    pub fn generate_blind_key(bit_size: u64) -> BlindPrivateKey {
+        loop {
+            let p = Self::generate_random_prime(bit_size / 2);
+            let q = Self::generate_random_prime(bit_size - bit_size / 2);
+            if p == q || (&p * &q).bits() != bit_size {
+                continue;
+            }
+            if let Some(key) = BlindSignature::key_from_primes(&p, &q) {
+                return key;
+            }
+        }
+    }
+
     /// Miller-Rabin Primality Test
     ///
     /// https://en.wikipedia.org/wiki/Miller-Rabin_primality_test
@@ -305,6 +355,37 @@ mod tests {
         Random::generate_permutation(&size);
     }
 
+    #[test]
+    fn it_should_generate_a_safe_prime() {
+        for bit_size in [3u64, 8, 64, 128].iter() {
+            let p = Random::generate_safe_prime(*bit_size);
+            assert_eq!(p.bits(), *bit_size);
+            assert!(Helper::is_safe_prime(&p));
+            assert!(Random::is_prime(&p, 20));
+        }
+    }
+
+    #[test]
+    fn it_should_generate_a_safe_prime_group() {
+        let seed = b"provotum";
+        let params = Random::generate_safe_prime_group(128, seed);
+        let q = params.q();
+        assert_eq!(params.p.bits(), 128);
+        assert!(Helper::is_element_of_subgroup(&params.p, &q, &params.g));
+        assert!(Helper::is_element_of_subgroup(&params.p, &q, &params.h));
+        assert_ne!(params.g, params.h);
+        assert!(Helper::verify_generators(&params, seed));
+        assert!(!Helper::verify_generators(&params, b"other seed"));
+
+        // the parameters can be used to create a key pair
+        let x = Random::get_random_less_than(&q);
+        let (pk, sk) = Helper::generate_key_pair(&params, &x);
+        let message = BigUint::from(1u32);
+        let r = Random::get_random_less_than(&q);
+        let cipher = ElGamal::encrypt_encode(&message, &r, &pk);
+        assert_eq!(ElGamal::decrypt_decode(&cipher, &sk), message);
+    }
+
     #[test]
     fn it_should_generate_a_permutation_for_three_numbers() {
         let size = 3;
@@ -112,6 +112,17 @@ pub type BigT = (
     Vec<BigUint>, // vec_t_hat
 );
 
+/// Algorithm 8.47: The s value of the ShuffleProof
+#[derive(Default, Clone, PartialEq, Eq, Debug)]
+pub struct BigS {
+    pub s1: BigUint,               // s1
+    pub s2: BigUint,               // s2
+    pub s3: BigUint,               // s3
+    pub s4: BigUint,               // s4
+    pub vec_s_hat: Vec<BigUint>,   // vec_s_hat
+    pub vec_s_tilde: Vec<BigUint>, // vec_s_tilde
+}
+
 pub trait ModuloOperations {
     /// Calculates the modular multiplicative of a BigUint: result = self * rhs % modulus.
     fn modmul(&self, rhs: &Self, modulus: &Self) -> Self;
@@ -168,7 +168,12 @@ fn setup_shuffle<T: Trait>(
     for cipher in ciphers.iter() {
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher.clone())];
         let ballot: Ballot = Ballot { answers };
-        PalletMixnet::<T>::cast_ballot(voter.clone().into(), vote_id.clone(), ballot)?;
+        PalletMixnet::<T>::cast_ballot(
+            voter.clone().into(),
+            vote_id.clone(),
+            ballot,
+            Vec::new(),
+        )?;
     }
 
     // type conversion
@@ -314,7 +319,12 @@ fn setup_vote_with_distributed_keys<T: Trait>(
     for cipher in ciphers {
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
         let ballot: Ballot = Ballot { answers };
-        PalletMixnet::<T>::cast_ballot(voter.clone().into(), vote_id.clone(), ballot)?;
+        PalletMixnet::<T>::cast_ballot(
+            voter.clone().into(),
+            vote_id.clone(),
+            ballot,
+            Vec::new(),
+        )?;
     }
 
     set_vote_phase::<T>(vote_id.clone(), VotePhase::Tallying)?;
@@ -552,7 +562,7 @@ benchmarks! {
         let answers: Vec<(TopicId, Cipher)> = vec![(topic_id, cipher)];
         let ballot: Ballot = Ballot { answers };
     }: {
-        let _result = PalletMixnet::<T>::cast_ballot(voter.clone().into(), vote_id.clone(), ballot.clone())?;
+        let _result = PalletMixnet::<T>::cast_ballot(voter.clone().into(), vote_id.clone(), ballot.clone(), Vec::new())?;
     } verify {
         let ballot_: Ballot = Ballots::<T>::get(vote_id, account);
         ensure!(ballot == ballot_, "ballots are not the same!");
@@ -34,14 +34,16 @@ pub fn combine_shares<T: Trait>(
         .map(|share| BigUint::from_bytes_be(share))
         .collect::<Vec<BigUint>>();
 
+    let group = params.group.clone();
     let base: ElGamalPK = ElGamalPK {
         h: BigUint::one(),
         params: params.into(),
     };
 
-    // combine the shares into a single key
+    // combine the shares into a single key, the key keeps the name of the group
     let new_pk: ElGamalPK = base.combine_public_keys_bigunits(&pk_shares_biguint);
-    let pk: SubstratePK = new_pk.into();
+    let mut pk: SubstratePK = new_pk.into();
+    pk.params.group = group;
     PublicKey::insert(vote_id.to_owned(), pk.clone());
     debug::info!("public_key successfully generated!");
 
@@ -1,14 +1,16 @@
 use crate::helpers::params::get_public_params;
 use crate::types::{
-    Cipher, DecryptedShare, DecryptedShareProof, NrOfShuffles, PublicKeyShare,
-    PublicKeyShareProof, PublicParameters, TopicId, VoteId, Wrapper,
+    Cipher, DecryptedShare, DecryptedShareProof, DecryptionPayload, NrOfShuffles,
+    PublicKeyShare, PublicKeyShareProof, TopicId, VoteId, Wrapper,
 };
 use crate::{
-    Ciphers, DecryptedShares, Error, PublicKeyShareBySealer, PublicKeyShares, Trait,
+    Ciphers, DecryptedShares, DecryptionProofs, Error, PublicKeyShareBySealer,
+    PublicKeyShares, Trait,
 };
 use codec::Encode;
+use crypto::helper::Helper;
 use crypto::proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof};
-use crypto::types::Cipher as BigCipher;
+use crypto::types::{Cipher as BigCipher, ElGamalParams};
 use frame_support::{
     debug, ensure,
     storage::{StorageDoubleMap, StorageMap},
@@ -30,14 +32,21 @@ pub fn verify_proof_and_store_keygen_share<T: Trait>(
     pk_share: PublicKeyShare,
 ) -> Result<(), Error<T>> {
     // get the public parameters
-    let params: PublicParameters = get_public_params::<T>(&vote_id)?;
+    let params: ElGamalParams = get_public_params::<T>(&vote_id)?.into();
+
+    // ensure that the public key share is an element of the subgroup of order q,
+    // it is checked once when it is stored, the verifiers don't check it again
+    let pk: BigUint = BigUint::from_bytes_be(&pk_share.pk);
+    ensure!(
+        Helper::is_element_of_subgroup(&params.p, &params.q(), &pk),
+        Error::<T>::PublicKeyShareNotInGroup
+    );
 
     // verify the public key share proof
     let sealer_id = who.encode();
     let proof: PublicKeyShareProof = pk_share.proof.clone();
-    let pk: BigUint = BigUint::from_bytes_be(&pk_share.pk);
     let proof_valid =
-        KeyGenerationProof::verify(&params.into(), &pk, &proof.into(), &sealer_id);
+        KeyGenerationProof::verify_unchecked(&params, &pk, &proof.into(), &sealer_id);
     ensure!(proof_valid, Error::<T>::PublicKeyShareProofError);
 
     // store the public key share
@@ -59,7 +68,7 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
 ) -> Result<(), Error<T>> {
     // get the public parameters and the public key share of the sealer
     let sealer_id: &[u8] = &who.encode();
-    let params: PublicParameters = get_public_params::<T>(vote_id)?;
+    let params: ElGamalParams = get_public_params::<T>(vote_id)?.into();
     let sealer_pk_share: PublicKeyShare = get_public_keyshare::<T>(vote_id, &who)?;
     let sealer_pk: BigUint = BigUint::from_bytes_be(&sealer_pk_share.pk);
 
@@ -76,6 +85,16 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
         .map(|s| BigUint::from_bytes_be(s))
         .collect::<Vec<BigUint>>();
 
+    // ensure that all decrypted shares are elements of the subgroup of order q,
+    // the decrypted ciphers and the public key share were checked when they were stored
+    let q = params.q();
+    ensure!(
+        decrypted_shares
+            .iter()
+            .all(|share| Helper::is_element_of_subgroup(&params.p, &q, share)),
+        Error::<T>::DecryptedShareNotInGroup
+    );
+
     debug::info!("params: {:?}", &params);
     debug::info!("sealer_pk: {:?}", &sealer_pk);
     debug::info!("proof: {:?}", &proof);
@@ -84,10 +103,10 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
     debug::info!("sealer_id: {:?}", &sealer_id);
 
     // verify the proof using the sealer's public key share
-    let is_valid: bool = DecryptionProof::verify(
-        &params.into(),
+    let is_valid: bool = DecryptionProof::verify_unchecked(
+        &params,
         &sealer_pk,
-        &proof.into(),
+        &proof.clone().into(),
         big_ciphers,
         decrypted_shares,
         sealer_id,
@@ -107,5 +126,14 @@ pub fn verify_proof_and_store_decrypted_share<T: Trait>(
 
     // store the decrypted shares per topic and sealer
     DecryptedShares::<T>::insert(topic_id, &who, stored);
+
+    // store the proof for verification (audit trail), it covers the stored decrypted shares
+    let mut payloads: Vec<DecryptionPayload> =
+        DecryptionProofs::<T>::get::<&TopicId, &T::AccountId>(topic_id, &who);
+    payloads.push(DecryptionPayload {
+        nr_of_shuffles: *nr_of_shuffles,
+        proof,
+    });
+    DecryptionProofs::<T>::insert(topic_id, &who, payloads);
     Ok(())
 }
@@ -1,5 +1,5 @@
 use crate::{
-    types::{VoteId, VotePhase},
+    types::{TopicAnswer, VoteId, VotePhase},
     Error, Module, Trait, Votes,
 };
 use frame_support::{debug, ensure, storage::StorageMap};
@@ -41,6 +41,32 @@ pub fn ensure_sealer<T: Trait>(account_id: &T::AccountId) -> Result<(), Error<T>
     }
 }
 
+pub fn ensure_relayer<T: Trait>(account_id: &T::AccountId) -> Result<(), Error<T>> {
+    let relayers = Module::<T>::relayers();
+    match relayers.contains(account_id) {
+        true => Ok(()),
+        false => {
+            debug::info!("Requester: {:?} is not a relayer!", account_id);
+            Err(Error::<T>::NotARelayer)
+        }
+    }
+}
+
+/// Verifies that the answers are not empty and each value and label is used once.
+pub fn ensure_valid_answers<T: Trait>(answers: &[TopicAnswer]) -> Result<(), Error<T>> {
+    ensure!(!answers.is_empty(), Error::<T>::TopicAnswersInvalid);
+    for (index, (value, label)) in answers.iter().enumerate() {
+        let duplicate = answers[..index].iter().any(|(other_value, other_label)| {
+            other_value == value || other_label == label
+        });
+        ensure!(
+            !label.is_empty() && !duplicate,
+            Error::<T>::TopicAnswersInvalid
+        );
+    }
+    Ok(())
+}
+
 pub fn ensure_vote_exists<T: Trait>(vote_id: &VoteId) -> Result<(), Error<T>> {
     // check that the vote_id exists
     ensure!(
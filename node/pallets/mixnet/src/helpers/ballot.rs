@@ -1,13 +1,24 @@
-use crate::types::{Ballot, Cipher, VoteId};
-use crate::{Ballots, Ciphers, Trait};
-use frame_support::storage::StorageDoubleMap;
+use crate::helpers::{
+    assertions::{ensure_vote_exists, ensure_vote_phase},
+    params::get_public_params,
+    signature::ensure_randomizer_signatures,
+};
+use crate::types::{Ballot, Cipher, RandomizerSignature, VoteId, VotePhase};
+use crate::{
+    Ballots, Ciphers, Error, Module, RandomizerSignatureRequired, RawEvent, Trait,
+};
+use crypto::{helper::Helper, types::ElGamalParams};
+use frame_support::{
+    debug, dispatch::DispatchResult, ensure, storage::StorageDoubleMap,
+    storage::StorageMap,
+};
 use sp_std::vec::Vec;
 
 const INITIAL_NUMBER_OF_SHUFFLES: u8 = 0;
 
 pub fn store_ballot<T: Trait>(from: &T::AccountId, vote_id: &VoteId, ballot: Ballot) {
     // TODO: perform ballot duplication check
-    // TODO: perform voter double vote cast check
+    // TODO: perform voter double vote cast check (relayed ballots are checked by relay_ballot)
 
     // store the encrypted ballot
     Ballots::<T>::insert(vote_id, from, ballot.clone());
@@ -23,3 +34,38 @@ pub fn store_ballot<T: Trait>(from: &T::AccountId, vote_id: &VoteId, ballot: Bal
         Ciphers::insert(&topic_id, INITIAL_NUMBER_OF_SHUFFLES, ciphers);
     }
 }
+
+impl<T: Trait> Module<T> {
+    /// Stores the ballot of the voter, shared by `cast_ballot` and `relay_ballot`.
+    pub fn submit_ballot(
+        voter: T::AccountId,
+        vote_id: VoteId,
+        ballot: Ballot,
+        signatures: Vec<RandomizerSignature>,
+    ) -> DispatchResult {
+        ensure_vote_exists::<T>(&vote_id)?;
+        ensure_vote_phase::<T>(&vote_id, VotePhase::Voting)?;
+
+        // ensure that the ballot passed through a registered randomizer
+        if RandomizerSignatureRequired::get(&vote_id) {
+            ensure_randomizer_signatures::<T>(&voter, &vote_id, &ballot, &signatures)?;
+        }
+
+        // ensure that all ciphers are elements of the subgroup of order q,
+        // each cipher is checked once when it is stored, the verifiers don't check them again
+        let params: ElGamalParams = get_public_params::<T>(&vote_id)?.into();
+        let q = params.q();
+        let is_valid = ballot.answers.iter().all(|(_, cipher)| {
+            Helper::is_valid_cipher(&params.p, &q, &cipher.clone().into())
+        });
+        ensure!(is_valid, Error::<T>::BallotCipherNotInGroup);
+
+        // store the ballot
+        store_ballot::<T>(&voter, &vote_id, ballot.clone());
+
+        // notify that the ballot has been submitted and stored
+        debug::info!("stored ballot for vote_id: {:?}", vote_id);
+        Self::deposit_event(RawEvent::BallotSubmitted(voter, vote_id, ballot));
+        Ok(())
+    }
+}
@@ -5,3 +5,4 @@ pub mod math;
 pub mod params;
 pub mod phase;
 pub mod random;
+pub mod signature;
@@ -3,7 +3,44 @@ use crate::{
     types::{PublicKey as SubstratePK, PublicParameters, Vote, VoteId},
     Error, PublicKey, Trait, Votes,
 };
-use frame_support::storage::StorageMap;
+use crypto::{groups::NamedGroup, helper::Helper, types::ElGamalParams};
+use frame_support::{
+    ensure,
+    storage::StorageMap,
+    traits::Get,
+    weights::{constants::WEIGHT_PER_MILLIS, Weight},
+};
+use num_traits::One;
+
+/// the weight of all calls which don't verify any parameters or proofs
+pub const BASE_WEIGHT: Weight = 10_000;
+
+/// the weight of the validation of a custom 2048bit modulus, i.e. the safe prime test
+/// (~0.8s native release build), doubled to cover the slower wasm execution
+pub const CUSTOM_MODULUS_WEIGHT: Weight = 1_600 * WEIGHT_PER_MILLIS;
+
+/// The weight of `create_vote`, dominated by the validation of custom parameters.
+/// The cost of the safe prime test grows with the cube of the modulus size,
+/// the named groups are not tested. A custom modulus larger than `T::MaxCustomModulusBits`
+/// is refused before it is tested (see: `ensure_valid_public_parameters`).
+pub fn create_vote_weight<T: Trait>(params: &PublicParameters) -> Weight {
+    if params.group.is_some() || is_modulus_too_large::<T>(params) {
+        return BASE_WEIGHT;
+    }
+    // the size of the modulus in units of 256bit (rounded up), a 2048bit modulus has 8 units
+    let units = (params.p.len() as u64 + 31) >> 5;
+    let per_unit = CUSTOM_MODULUS_WEIGHT / 512;
+    units
+        .saturating_pow(3)
+        .saturating_mul(per_unit)
+        .saturating_add(BASE_WEIGHT)
+}
+
+/// Whether the custom modulus exceeds `T::MaxCustomModulusBits`.
+/// The encoded size is used, the same size the weight is derived from.
+fn is_modulus_too_large<T: Trait>(params: &PublicParameters) -> bool {
+    params.p.len() as u64 * 8 > T::MaxCustomModulusBits::get() as u64
+}
 
 /// all functions related to key generation and decrypted share operations
 pub fn get_public_params<T: Trait>(
@@ -19,3 +56,54 @@ pub fn get_public_params<T: Trait>(
 pub fn get_public_key<T: Trait>(vote_id: &VoteId) -> Result<SubstratePK, Error<T>> {
     PublicKey::get(vote_id).ok_or(Error::<T>::PublicKeyNotExistsError)
 }
+
+/// Resolves the parameters of a named group, p, g and h are filled in if they are empty.
+/// The parameters of a custom group are returned unchanged.
+pub fn resolve_public_parameters<T: Trait>(
+    params: PublicParameters,
+) -> Result<PublicParameters, Error<T>> {
+    let group = match &params.group {
+        Some(name) => NamedGroup::from_name(name).ok_or(Error::<T>::UnknownGroup)?,
+        None => return Ok(params),
+    };
+    let resolved: PublicParameters = group.into();
+    let is_empty = params.p.is_empty() && params.g.is_empty() && params.h.is_empty();
+    ensure!(
+        is_empty || params == resolved,
+        Error::<T>::GroupParametersMismatch
+    );
+    Ok(resolved)
+}
+
+/// Verifies that the modulus p is a safe prime of at least `T::MinModulusBits` bits
+/// and that g and h are distinct generators of the subgroup of order q.
+pub fn ensure_valid_public_parameters<T: Trait>(
+    params: &PublicParameters,
+) -> Result<(), Error<T>> {
+    let is_named_group = params.group.is_some();
+    let is_too_large = is_modulus_too_large::<T>(params);
+    let params: ElGamalParams = params.clone().into();
+    ensure!(
+        params.p.bits() >= T::MinModulusBits::get() as u64,
+        Error::<T>::PublicParametersTooWeak
+    );
+
+    // the named groups are resolved by the pallet and checked by the tests of the crypto crate
+    if is_named_group {
+        return Ok(());
+    }
+    ensure!(!is_too_large, Error::<T>::ModulusTooLarge);
+    ensure!(
+        Helper::is_safe_prime(&params.p),
+        Error::<T>::ModulusNotASafePrime
+    );
+
+    let q = params.q();
+    let is_generator =
+        |x| Helper::is_element_of_subgroup(&params.p, &q, x) && !x.is_one();
+    ensure!(
+        is_generator(&params.g) && is_generator(&params.h) && params.g != params.h,
+        Error::<T>::InvalidGenerators
+    );
+    Ok(())
+}
@@ -0,0 +1,111 @@
+use crate::types::{
+    credential_message, randomizer_message, relay_message, Ballot, CredentialKey,
+    RandomizerSignature, VoteId, VoterCredential, VoterToken,
+};
+use crate::{CredentialKeys, Error, Module, SpentTokens, Trait};
+use codec::Decode;
+use crypto::blind::BlindSignature;
+use frame_support::{
+    debug, ensure,
+    storage::{StorageDoubleMap, StorageMap},
+    traits::Get,
+};
+use num_bigint::BigUint;
+use sp_std::vec::Vec;
+
+/// Verifies that every answer of the ballot has been re-encrypted by a registered randomizer.
+/// The i-th signature must cover the i-th answer of the ballot.
+pub fn ensure_randomizer_signatures<T: Trait>(
+    voter: &T::AccountId,
+    vote_id: &VoteId,
+    ballot: &Ballot,
+    signatures: &Vec<RandomizerSignature>,
+) -> Result<(), Error<T>> {
+    ensure!(
+        signatures.len() == ballot.answers.len(),
+        Error::<T>::RandomizerSignatureMissing
+    );
+
+    let randomizer_keys = Module::<T>::randomizer_keys();
+    for ((_, cipher), signature) in ballot.answers.iter().zip(signatures.iter()) {
+        // only signatures of registered randomizers are accepted
+        ensure!(
+            randomizer_keys.contains(&signature.signer),
+            Error::<T>::RandomizerNotRegistered
+        );
+
+        // the signature must cover: the vote, the voter and the re-encrypted cipher
+        let message = randomizer_message(vote_id, voter, cipher);
+        if !sp_io::crypto::sr25519_verify(
+            &signature.signature,
+            &message,
+            &signature.signer,
+        ) {
+            debug::info!("invalid randomizer signature for voter: {:?}", voter);
+            return Err(Error::<T>::RandomizerSignatureInvalid);
+        }
+    }
+    Ok(())
+}
+
+/// Verifies the credential of a relayed ballot, returns the account of the voter.
+/// The voter must have signed the vote and the ballot.
+pub fn ensure_voter_credential<T: Trait>(
+    vote_id: &VoteId,
+    ballot: &Ballot,
+    credential: &VoterCredential,
+) -> Result<T::AccountId, Error<T>> {
+    let message = relay_message(vote_id, ballot);
+    if !sp_io::crypto::sr25519_verify(&credential.signature, &message, &credential.voter)
+    {
+        debug::info!("invalid credential of voter: {:?}", credential.voter);
+        return Err(Error::<T>::VoterCredentialInvalid);
+    }
+
+    // the voter's account is derived from its public key
+    T::AccountId::decode(&mut &credential.voter.0[..]).map_err(|_| Error::<T>::ParseError)
+}
+
+/// Verifies that the modulus n of the credential key has at least `T::MinCredentialKeyBits` bits
+/// and that the public exponent is in 1 < e < n, with e = 1 anyone could forge a token.
+pub fn ensure_valid_credential_key<T: Trait>(
+    key: &CredentialKey,
+) -> Result<(), Error<T>> {
+    let n = BigUint::from_bytes_be(&key.n);
+    let e = BigUint::from_bytes_be(&key.e);
+    ensure!(
+        n.bits() >= T::MinCredentialKeyBits::get() as u64,
+        Error::<T>::CredentialKeyTooWeak
+    );
+    ensure!(
+        e > BigUint::from(1u32) && e < n,
+        Error::<T>::CredentialKeyTooWeak
+    );
+    Ok(())
+}
+
+/// Verifies the voter token presented by a one-time account, returns the token to mark it as spent.
+/// The token must be the voting authority's signature over the vote and the account and unspent.
+pub fn ensure_voter_token<T: Trait>(
+    account: &T::AccountId,
+    vote_id: &VoteId,
+    token: &VoterToken,
+) -> Result<VoterToken, Error<T>> {
+    let key =
+        CredentialKeys::get(vote_id).ok_or(Error::<T>::CredentialKeyDoesNotExist)?;
+
+    // leading zeros don't change the signature, only the canonical encoding is recorded
+    let signature = BigUint::from_bytes_be(token);
+    let token = signature.to_bytes_be();
+    ensure!(
+        !SpentTokens::contains_key(vote_id, &token),
+        Error::<T>::VoterTokenAlreadySpent
+    );
+
+    let message = credential_message(vote_id, account);
+    if !BlindSignature::verify(&key.into(), &message, &signature) {
+        debug::info!("invalid voter token of account: {:?}", account);
+        return Err(Error::<T>::VoterTokenInvalid);
+    }
+    Ok(token)
+}
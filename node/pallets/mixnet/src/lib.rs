@@ -36,20 +36,26 @@ use crate::dkg::{
 };
 use crate::helpers::{
     assertions::{
-        ensure_not_a_voting_authority, ensure_sealer, ensure_vote_does_not_exist,
-        ensure_vote_exists, ensure_vote_phase, ensure_voting_authority,
+        ensure_not_a_voting_authority, ensure_relayer, ensure_sealer, ensure_valid_answers,
+        ensure_vote_does_not_exist, ensure_vote_exists, ensure_vote_phase,
+        ensure_voting_authority,
+    },
+    params::{
+        create_vote_weight, ensure_valid_public_parameters, resolve_public_parameters,
     },
-    ballot::store_ballot,
     phase::set_phase,
+    signature::{ensure_valid_credential_key, ensure_voter_credential, ensure_voter_token},
 };
 use crate::types::{
-    Ballot, Cipher, DecryptedShare, DecryptedShareProof, NrOfShuffles,
-    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, ShufflePayload,
-    ShuffleState, Title, Topic, TopicId, TopicResult, Vote, VoteId, VotePhase,
+    Ballot, Cipher, CredentialKey, DecryptedShare, DecryptedShareProof, DecryptionPayload,
+    NrOfShuffles,
+    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, RandomizerKey,
+    RandomizerSignature, ShufflePayload, ShuffleState, Title, Topic, TopicAnswer, TopicId,
+    TopicResult, Vote, VoteId, VoterCredential, VotePhase, VoterToken,
 };
 use frame_support::{
     debug, decl_error, decl_event, decl_module, decl_storage, dispatch::DispatchResult,
-    storage::StorageMap, storage::StorageValue, traits::Get, weights::Pays,
+    ensure, storage::StorageMap, storage::StorageValue, traits::Get, weights::Pays,
 };
 use frame_system::{
     ensure_signed,
@@ -71,6 +77,15 @@ pub trait Trait:
     // Wait period between automated fetches. Set to 0 disable this feature.
     //   Then you need to manucally kickoff pricefetch
     type BlockDuration: Get<Self::BlockNumber>;
+
+    /// The minimum size of the safe prime p of a vote's public parameters in bits.
+    type MinModulusBits: Get<u32>;
+
+    /// The maximum size of a custom safe prime p in bits, the safe prime test must fit into a block.
+    type MaxCustomModulusBits: Get<u32>;
+
+    /// The minimum size of the RSA modulus n of a vote's credential key in bits.
+    type MinCredentialKeyBits: Get<u32>;
 }
 
 decl_storage! {
@@ -93,6 +108,9 @@ decl_storage! {
         /// Maps a voteId to a topic (topicId, question)
         Topics get(fn topics): map hasher(blake2_128_concat) VoteId => Vec<Topic>;
 
+        /// Maps a topicId to the allowed answers (value, label)
+        Answers get(fn answers): map hasher(blake2_128_concat) TopicId => Vec<TopicAnswer>;
+
         /// Maps an voter and a vote to a ballot. Used to verify if a voter has already voted.
         Ballots get(fn ballots): double_map hasher(blake2_128_concat) VoteId, hasher(blake2_128_concat) T::AccountId => Ballot;
 
@@ -111,6 +129,9 @@ decl_storage! {
         /// Maps a sealer and a topic to a vector of decrypted shares.
         DecryptedShares get(fn decrypted_shares): double_map hasher(blake2_128_concat) TopicId, hasher(blake2_128_concat) T::AccountId  => Vec<Vec<u8>>;
 
+        /// Maps a sealer and a topic to the proofs of the submitted decrypted shares (audit trail)
+        DecryptionProofs get(fn decryption_proofs): double_map hasher(blake2_128_concat) TopicId, hasher(blake2_128_concat) T::AccountId => Vec<DecryptionPayload>;
+
         /// Stores the public key of a sealer together with its Schnorr proof.
         PublicKeyShares get(fn key_shares): map hasher(blake2_128_concat) VoteId => Vec<PublicKeyShare>;
 
@@ -119,6 +140,21 @@ decl_storage! {
 
         /// Maps a vote to a public key (the vote's/system's public key) used to encrypt ballots.
         PublicKey get(fn public_key): map hasher(blake2_128_concat) VoteId => Option<SubstratePK>;
+
+        /// Set of all registered randomizer keys (sr25519) used to verify the randomizer's signatures on ballots.
+        RandomizerKeys get(fn randomizer_keys): Vec<RandomizerKey>;
+
+        /// Maps a vote to whether a ballot requires a valid randomizer signature per answer.
+        RandomizerSignatureRequired get(fn randomizer_signature_required): map hasher(blake2_128_concat) VoteId => bool;
+
+        /// The accounts allowed to submit ballots on behalf of voters
+        Relayers get(fn relayers): Vec<T::AccountId>;
+
+        /// The voting authority's key to blind-sign voter tokens, ballots require a token if set
+        CredentialKeys get(fn credential_key): map hasher(blake2_128_concat) VoteId => Option<CredentialKey>;
+
+        /// The voter tokens which have been used to cast a ballot
+        SpentTokens get(fn spent_token): double_map hasher(blake2_128_concat) VoteId, hasher(blake2_128_concat) VoterToken => bool;
     }
 }
 
@@ -157,6 +193,27 @@ decl_event!(
 
         /// A decrypted share was submitted for a vote. [paritial decryptions with its proof]
         ShuffleProofSubmitted(TopicId, AccountId),
+
+        /// A voting authority registered a randomizer key. [who, randomizer key]
+        RandomizerKeyStored(AccountId, RandomizerKey),
+
+        /// A voting authority removed a randomizer key. [who, randomizer key]
+        RandomizerKeyRemoved(AccountId, RandomizerKey),
+
+        /// A voting authority changed whether ballots require a randomizer signature. [vote_id, required]
+        RandomizerSignatureRequirementChanged(VoteId, bool),
+
+        /// A voting authority registered a relayer. [who, relayer]
+        RelayerStored(AccountId, AccountId),
+
+        /// A voting authority removed a relayer. [who, relayer]
+        RelayerRemoved(AccountId, AccountId),
+
+        /// A voting authority set the key to blind-sign voter tokens. [vote_id, key]
+        CredentialKeyStored(VoteId, CredentialKey),
+
+        /// A voting authority set the allowed answers of a topic. [vote_id, topic_id]
+        TopicAnswersStored(VoteId, TopicId),
     }
 );
 
@@ -248,7 +305,91 @@ decl_error! {
         ShuffleStateIncorrect,
 
         /// Error returned when shuffle is submitted for (vote_id, topic_id) which is already completed
-        ShuffleAlreadyCompleted
+        ShuffleAlreadyCompleted,
+
+        // Error returned when a randomizer key is registered twice
+        RandomizerKeyAlreadyExists,
+
+        // Error returned when a randomizer key to remove is not registered
+        RandomizerKeyDoesNotExist,
+
+        // Error returned when the ballot doesn't contain a randomizer signature for each answer
+        RandomizerSignatureMissing,
+
+        // Error returned when the ballot was signed by a randomizer which is not registered
+        RandomizerNotRegistered,
+
+        // Error returned when the randomizer signature doesn't verify
+        RandomizerSignatureInvalid,
+
+        // Error returned when a relayer is registered twice
+        RelayerAlreadyExists,
+
+        // Error returned when a relayer to remove is not registered
+        RelayerDoesNotExist,
+
+        // Error returned when the requester is not a registered relayer
+        NotARelayer,
+
+        // Error returned when the voter's signature of a relayed ballot doesn't verify
+        VoterCredentialInvalid,
+
+        // Error returned when the vote doesn't accept anonymous ballots
+        CredentialKeyDoesNotExist,
+
+        // Error returned when the ballot of a vote with a credential key is cast without a token
+        VoterTokenRequired,
+
+        // Error returned when the voter token doesn't verify for the one-time account
+        VoterTokenInvalid,
+
+        // Error returned when the voter token has already been used to cast a ballot
+        VoterTokenAlreadySpent,
+
+        // Error returned when the modulus of the credential key is smaller than MinCredentialKeyBits or the exponent is not in 1 < e < n
+        CredentialKeyTooWeak,
+
+        // Error returned when a vote would require both a voter token and randomizer signatures
+        CredentialKeyWithRandomizerSignature,
+
+        // Error returned when the topic is not part of the vote
+        TopicDoesNotExist,
+
+        // Error returned when the answers are empty or contain a value or label twice
+        TopicAnswersInvalid,
+
+        // Error returned when the modulus p of the public parameters is smaller than MinModulusBits
+        PublicParametersTooWeak,
+
+        // Error returned when the custom modulus p of the public parameters is larger than MaxCustomModulusBits
+        ModulusTooLarge,
+
+        // Error returned when the modulus p of the public parameters is not a safe prime
+        ModulusNotASafePrime,
+
+        // Error returned when g or h are not distinct generators of the subgroup of order q
+        InvalidGenerators,
+
+        // Error returned when the public parameters name an unknown group
+        UnknownGroup,
+
+        // Error returned when p, g or h differ from the parameters of the named group
+        GroupParametersMismatch,
+
+        // Error returned when a cipher of a ballot is not in the subgroup of order q
+        BallotCipherNotInGroup,
+
+        // Error returned when a public key share is not in the subgroup of order q
+        PublicKeyShareNotInGroup,
+
+        // Error returned when a shuffled cipher is not in the subgroup of order q
+        ShuffledCipherNotInGroup,
+
+        // Error returned when a decrypted share is not in the subgroup of order q
+        DecryptedShareNotInGroup,
+
+        // Error returned when a ballot has already been relayed for the voter
+        BallotAlreadyCast
     }
 }
 
@@ -335,10 +476,13 @@ decl_module! {
 
         /// Create a vote and store public crypto parameters.
         /// Can only be called from a voting authority.
-        #[weight = (10000, Pays::No)]
+        /// The weight grows with the size of a custom modulus, see `create_vote_weight`.
+        #[weight = (create_vote_weight::<T>(params), Pays::No)]
         fn create_vote(origin, vote_id: VoteId, title: Title, params: PublicParameters, topics: Vec<Topic>, batch_size: u64) -> DispatchResult {
             let who: T::AccountId = ensure_signed(origin)?;
             ensure_voting_authority::<T>(&who)?;
+            let params = resolve_public_parameters::<T>(params)?;
+            ensure_valid_public_parameters::<T>(&params)?;
 
             // create new vote
             let vote = Vote::<T::AccountId> {
@@ -404,23 +548,177 @@ decl_module! {
             Ok(())
         }
 
+        /// Set the allowed answers of a question.
+        /// Can only be called from a voting authority before the voting phase starts.
+        #[weight = (10000, Pays::No)]
+        fn store_answers(origin, vote_id: VoteId, topic_id: TopicId, answers: Vec<TopicAnswer>) -> DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+            ensure!(Topics::get(&vote_id).iter().any(|topic| topic.0 == topic_id), Error::<T>::TopicDoesNotExist);
+            ensure_valid_answers::<T>(&answers)?;
+
+            Answers::insert(&topic_id, answers);
+
+            debug::info!("stored answers of question: {:?}", topic_id);
+            Self::deposit_event(RawEvent::TopicAnswersStored(vote_id, topic_id));
+            Ok(())
+        }
+
+        /// Register the sr25519 key of a randomizer.
+        /// Can only be called from a voting authority.
         #[weight = (10000, Pays::No)]
-        pub fn cast_ballot(origin, vote_id: VoteId, ballot: Ballot) -> DispatchResult {
+        fn store_randomizer_key(origin, key: RandomizerKey) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+
+            let mut keys: Vec<RandomizerKey> = RandomizerKeys::get();
+            ensure!(!keys.contains(&key), Error::<T>::RandomizerKeyAlreadyExists);
+            keys.push(key.clone());
+            RandomizerKeys::put(keys);
+
+            debug::info!("stored randomizer key: {:?}", key);
+            Self::deposit_event(RawEvent::RandomizerKeyStored(who, key));
+            Ok(())
+        }
+
+        /// Remove the sr25519 key of a randomizer.
+        /// Can only be called from a voting authority.
+        #[weight = (10000, Pays::No)]
+        fn remove_randomizer_key(origin, key: RandomizerKey) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+
+            let mut keys: Vec<RandomizerKey> = RandomizerKeys::get();
+            ensure!(keys.contains(&key), Error::<T>::RandomizerKeyDoesNotExist);
+            keys.retain(|k| k != &key);
+            RandomizerKeys::put(keys);
+
+            debug::info!("removed randomizer key: {:?}", key);
+            Self::deposit_event(RawEvent::RandomizerKeyRemoved(who, key));
+            Ok(())
+        }
+
+        /// Set whether the ballots of a vote require a valid randomizer signature.
+        /// Can only be called from a voting authority before the voting phase starts.
+        #[weight = (10000, Pays::No)]
+        fn set_randomizer_signature_required(origin, vote_id: VoteId, required: bool) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+
+            // the randomizer signature is bound to the account casting the ballot,
+            // the randomizer would learn the one-time account of the voter
+            ensure!(
+                !required || !CredentialKeys::contains_key(&vote_id),
+                Error::<T>::CredentialKeyWithRandomizerSignature
+            );
+            RandomizerSignatureRequired::insert(&vote_id, required);
+
+            debug::info!("randomizer signature required: {:?} for vote: {:?}", required, vote_id);
+            Self::deposit_event(RawEvent::RandomizerSignatureRequirementChanged(vote_id, required));
+            Ok(())
+        }
+
+        /// Cast a ballot.
+        /// If the vote requires it, each answer must be signed by a registered randomizer.
+        #[weight = (10000, Pays::No)]
+        pub fn cast_ballot(origin, vote_id: VoteId, ballot: Ballot, signatures: Vec<RandomizerSignature>) -> DispatchResult {
           let who = ensure_signed(origin)?;
-          ensure_vote_exists::<T>(&vote_id)?;
-          ensure_vote_phase::<T>(&vote_id, VotePhase::Voting)?;
 
           // TODO: ensure that it is a legit voter -> in some other project where identity management is considered
+          ensure!(!CredentialKeys::contains_key(&vote_id), Error::<T>::VoterTokenRequired);
+          Self::submit_ballot(who, vote_id, ballot, signatures)
+        }
 
-          // store the ballot
-          store_ballot::<T>(&who, &vote_id, ballot.clone());
+        /// Cast a ballot from a one-time account.
+        /// The token is the voting authority's unblinded signature over the vote and the account.
+        /// Each token can only be used once.
+        #[weight = (10000, Pays::No)]
+        pub fn cast_anonymous_ballot(origin, vote_id: VoteId, ballot: Ballot, signatures: Vec<RandomizerSignature>, token: VoterToken) -> DispatchResult {
+          let who = ensure_signed(origin)?;
+          let token = ensure_voter_token::<T>(&who, &vote_id, &token)?;
+          Self::submit_ballot(who, vote_id.clone(), ballot, signatures)?;
 
-          // notify that the ballot has been submitted and stored
-          debug::info!("stored ballot for vote_id: {:?}", vote_id);
-          Self::deposit_event(RawEvent::BallotSubmitted(who, vote_id, ballot));
+          // the token cannot be used for a second ballot
+          SpentTokens::insert(&vote_id, token, true);
           Ok(())
         }
 
+        /// Set the key used to blind-sign the voter tokens of a vote.
+        /// Can only be called from a voting authority before the voting phase starts.
+        #[weight = (10000, Pays::No)]
+        fn store_credential_key(origin, vote_id: VoteId, key: CredentialKey) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+            ensure_vote_exists::<T>(&vote_id)?;
+            ensure_vote_phase::<T>(&vote_id, VotePhase::KeyGeneration)?;
+            ensure_valid_credential_key::<T>(&key)?;
+            ensure!(
+                !RandomizerSignatureRequired::get(&vote_id),
+                Error::<T>::CredentialKeyWithRandomizerSignature
+            );
+
+            CredentialKeys::insert(&vote_id, key.clone());
+
+            debug::info!("stored credential key for vote: {:?}", vote_id);
+            Self::deposit_event(RawEvent::CredentialKeyStored(vote_id, key));
+            Ok(())
+        }
+
+        /// Cast a ballot on behalf of a voter.
+        /// Can only be called from a registered relayer, the voter must have signed the ballot.
+        #[weight = (10000, Pays::No)]
+        pub fn relay_ballot(origin, vote_id: VoteId, ballot: Ballot, signatures: Vec<RandomizerSignature>, credential: VoterCredential) -> DispatchResult {
+          let who = ensure_signed(origin)?;
+          ensure_relayer::<T>(&who)?;
+          ensure!(!CredentialKeys::contains_key(&vote_id), Error::<T>::VoterTokenRequired);
+
+          // the ballot is stored as the voter's, not the relayer's
+          let voter = ensure_voter_credential::<T>(&vote_id, &ballot, &credential)?;
+
+          // the credential has no nonce, a relayed ballot could be replayed
+          ensure!(!Ballots::<T>::contains_key(&vote_id, &voter), Error::<T>::BallotAlreadyCast);
+          debug::info!("relayer: {:?} submits ballot of voter: {:?}", who, voter);
+          Self::submit_ballot(voter, vote_id, ballot, signatures)
+        }
+
+        /// Register an account which can relay the ballots of voters.
+        /// Can only be called from a voting authority.
+        #[weight = (10000, Pays::No)]
+        fn store_relayer(origin, relayer: T::AccountId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+
+            let mut relayers: Vec<T::AccountId> = Relayers::<T>::get();
+            ensure!(!relayers.contains(&relayer), Error::<T>::RelayerAlreadyExists);
+            relayers.push(relayer.clone());
+            Relayers::<T>::put(relayers);
+
+            debug::info!("stored relayer: {:?}", relayer);
+            Self::deposit_event(RawEvent::RelayerStored(who, relayer));
+            Ok(())
+        }
+
+        /// Remove a relayer.
+        /// Can only be called from a voting authority.
+        #[weight = (10000, Pays::No)]
+        fn remove_relayer(origin, relayer: T::AccountId) -> DispatchResult {
+            let who: T::AccountId = ensure_signed(origin)?;
+            ensure_voting_authority::<T>(&who)?;
+
+            let mut relayers: Vec<T::AccountId> = Relayers::<T>::get();
+            ensure!(relayers.contains(&relayer), Error::<T>::RelayerDoesNotExist);
+            relayers.retain(|r| r != &relayer);
+            Relayers::<T>::put(relayers);
+
+            debug::info!("removed relayer: {:?}", relayer);
+            Self::deposit_event(RawEvent::RelayerRemoved(who, relayer));
+            Ok(())
+        }
+
         /// Test function to check signer.
         #[weight = (10_000, Pays::No)]
         fn submit_shuffled_votes_and_proof(origin, vote_id: VoteId, topic_id: TopicId, payload: ShufflePayload) -> DispatchResult {
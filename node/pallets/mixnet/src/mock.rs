@@ -130,6 +130,11 @@ pub type System = frame_system::Module<TestRuntime>;
 // Mock Implementation of pallet_mixnet
 parameter_types! {
     pub const TestBlockDuration: u64 = 1;
+    // the tiny test system uses a 6-bit modulus
+    pub const TestMinModulusBits: u32 = 6;
+    pub const TestMaxCustomModulusBits: u32 = 2048;
+    // the test credential key uses a 92-bit modulus
+    pub const TestMinCredentialKeyBits: u32 = 64;
 }
 
 impl pallet_mixnet::Trait for TestRuntime {
@@ -137,6 +142,9 @@ impl pallet_mixnet::Trait for TestRuntime {
     type Event = TestEvent;
     type AuthorityId = pallet_mixnet::keys::TestAuthId;
     type BlockDuration = TestBlockDuration;
+    type MinModulusBits = TestMinModulusBits;
+    type MaxCustomModulusBits = TestMaxCustomModulusBits;
+    type MinCredentialKeyBits = TestMinCredentialKeyBits;
 }
 
 pub type OffchainModule = pallet_mixnet::Module<TestRuntime>;
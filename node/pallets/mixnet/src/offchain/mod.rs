@@ -57,7 +57,7 @@ impl<T: Trait> Module<T> {
 
         return send_signed::<T>(
             signer,
-            Call::cast_ballot(vote_id.clone(), ballot.clone()),
+            Call::cast_ballot(vote_id.clone(), ballot.clone(), Vec::new()),
         );
     }
 
@@ -11,6 +11,7 @@ use crate::{
 };
 use crate::{Ciphers, Error, Module, ShuffleProofs, ShuffleStateStore, Trait};
 use alloc::vec::Vec;
+use crypto::helper::Helper;
 use crypto::types::{Cipher as BigCipher, PublicKey as ElGamalPK};
 use frame_support::{
     ensure,
@@ -74,6 +75,16 @@ impl<T: Trait> Module<T> {
         let big_shuffled_ciphers: Vec<BigCipher> =
             Wrapper(shuffled_ciphers.clone()).into();
 
+        // ensure that all shuffled ciphers are elements of the subgroup of order q,
+        // each cipher is checked once when it is stored, the verifier doesn't check them again
+        let q = pk.params.q();
+        ensure!(
+            big_shuffled_ciphers
+                .iter()
+                .all(|cipher| Helper::is_valid_cipher(&pk.params.p, &q, cipher)),
+            Error::<T>::ShuffledCipherNotInGroup
+        );
+
         // get the required range of ciphers
         let slice: Vec<BigCipher> =
             get_slice::<T, BigCipher>(big_ciphers, start_position, batch_size);
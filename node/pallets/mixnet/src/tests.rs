@@ -1,12 +1,16 @@
+use crate::helpers::params::{create_vote_weight, BASE_WEIGHT, CUSTOM_MODULUS_WEIGHT};
 use crate::mock::*;
 use crate::types::{
-    Ballot, Cipher, PublicKey as SubstratePK, PublicParameters, ShufflePayload,
-    ShuffleProof as Proof, VotePhase, Wrapper,
+    credential_message, randomizer_message, relay_message, Ballot, Cipher,
+    PublicKey as SubstratePK, PublicParameters, RandomizerSignature, ShufflePayload,
+    ShuffleProof as Proof, VotePhase, VoterCredential, Wrapper,
 };
 use crate::*;
 use codec::Decode;
 use crypto::{
+    blind::{BlindPrivateKey, BlindSignature},
     encryption::ElGamal,
+    groups::NamedGroup,
     helper::Helper,
     proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof},
     types::{
@@ -14,10 +18,11 @@ use crypto::{
         PublicKey as ElGamalPK,
     },
 };
-use frame_support::{assert_err, assert_ok};
+use frame_support::{assert_err, assert_ok, weights::GetDispatchInfo};
 use hex_literal::hex;
 use num_bigint::BigUint;
-use num_traits::Zero;
+use num_traits::{One, Zero};
+use sp_core::{sr25519, Pair};
 use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 
 const NR_OF_SHUFFLES: u8 = 0;
@@ -164,7 +169,8 @@ fn setup_ciphers(vote_id: &VoteId, topic_id: &TopicId, pk: &ElGamalPK, encoded:
         assert_ok!(OffchainModule::cast_ballot(
             voter.clone(),
             vote_id.clone(),
-            ballot
+            ballot,
+            Vec::new()
         ));
     }
 }
@@ -366,6 +372,226 @@ fn test_create_vote_works() {
     });
 }
 
+fn create_vote_with_params(params: PublicParameters) -> DispatchResult {
+    let who = get_voting_authority();
+    let vote_id = "20201212".as_bytes().to_vec();
+    let vote_title = "Popular Vote of 12.12.2020".as_bytes().to_vec();
+    let topic: Topic = (
+        "20201212-01".as_bytes().to_vec(),
+        "Moritz for President?".as_bytes().to_vec(),
+    );
+    OffchainModule::create_vote(who, vote_id, vote_title, params, vec![topic], 2)
+}
+
+#[test]
+fn test_create_vote_with_derived_generators_works() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (g, h) = Helper::derive_generators(b"20201212", &params.p);
+        let params = ElGamalParams { p: params.p, g, h };
+        assert_ok!(create_vote_with_params(params.into()));
+    });
+}
+
+#[test]
+fn test_create_vote_with_named_group_works() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // only the name is submitted, p, g and h are resolved by the pallet
+        let params = PublicParameters {
+            group: Some(b"modp2048".to_vec()),
+            ..Default::default()
+        };
+        assert_ok!(create_vote_with_params(params));
+
+        let vote_id = "20201212".as_bytes().to_vec();
+        let vote = OffchainModule::votes(vote_id);
+        let expected: PublicParameters = NamedGroup::Modp2048.into();
+        assert_eq!(vote.params, expected);
+        assert_eq!(vote.params.group, Some(b"modp2048".to_vec()));
+    });
+}
+
+#[test]
+fn test_create_vote_weight() {
+    let named: PublicParameters = NamedGroup::Modp2048.into();
+    let (custom, _, _) = Helper::setup_lg_system();
+    let custom: PublicParameters = custom.into();
+    let (small, _, _) = Helper::setup_md_system();
+    let small: PublicParameters = small.into();
+
+    // the named groups are not tested, a custom modulus is
+    assert_eq!(create_vote_weight::<TestRuntime>(&named), BASE_WEIGHT);
+    assert_eq!(
+        create_vote_weight::<TestRuntime>(&custom),
+        BASE_WEIGHT + CUSTOM_MODULUS_WEIGHT
+    );
+    // 1024bit: (4 / 8)^3 = 1/8 of the weight of 2048bit
+    assert_eq!(
+        create_vote_weight::<TestRuntime>(&small),
+        BASE_WEIGHT + CUSTOM_MODULUS_WEIGHT / 8
+    );
+
+    // the weight is derived from the submitted call
+    let call = Call::<TestRuntime>::create_vote(
+        b"vote".to_vec(),
+        b"title".to_vec(),
+        custom,
+        vec![],
+        30,
+    );
+    assert_eq!(
+        call.get_dispatch_info().weight,
+        BASE_WEIGHT + CUSTOM_MODULUS_WEIGHT
+    );
+}
+
+#[test]
+fn test_create_vote_custom_modulus_too_large() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // a custom 3072bit modulus exceeds MaxCustomModulusBits (2048bit)
+        let (large, _, _) = Helper::setup_xl_system();
+        let large: PublicParameters = large.into();
+        assert_err!(
+            create_vote_with_params(large.clone()),
+            Error::<TestRuntime>::ModulusTooLarge
+        );
+
+        // it is refused before the safe prime test, the weight stays within the block limit
+        assert_eq!(create_vote_weight::<TestRuntime>(&large), BASE_WEIGHT);
+
+        // a modulus padded with leading zeros is refused as well
+        let (custom, _, _) = Helper::setup_lg_system();
+        let mut padded: PublicParameters = custom.into();
+        padded.p.insert(0, 0u8);
+        assert_err!(
+            create_vote_with_params(padded),
+            Error::<TestRuntime>::ModulusTooLarge
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_with_resolved_named_group_works() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        assert_ok!(create_vote_with_params(NamedGroup::Ffdhe3072.into()));
+    });
+}
+
+#[test]
+fn test_create_vote_unknown_group() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let params = PublicParameters {
+            group: Some(b"modp1024".to_vec()),
+            ..Default::default()
+        };
+        assert_err!(
+            create_vote_with_params(params),
+            Error::<TestRuntime>::UnknownGroup
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_group_parameters_mismatch() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // the parameters of a different group
+        let (params, _, _) = Helper::setup_lg_system();
+        let mut params: PublicParameters = params.into();
+        params.group = Some(b"modp2048".to_vec());
+        assert_err!(
+            create_vote_with_params(params),
+            Error::<TestRuntime>::GroupParametersMismatch
+        );
+
+        // a different generator h
+        let mut params: PublicParameters = NamedGroup::Modp2048.into();
+        params.h = BigUint::from(4u32).to_bytes_be();
+        assert_err!(
+            create_vote_with_params(params),
+            Error::<TestRuntime>::GroupParametersMismatch
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_too_weak_params() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // 23 = 2 * 11 + 1 is a safe prime of 5 bits
+        let params = ElGamalParams {
+            p: BigUint::from(23u32),
+            g: BigUint::from(4u32),
+            h: BigUint::from(9u32),
+        };
+        assert_err!(
+            create_vote_with_params(params.into()),
+            Error::<TestRuntime>::PublicParametersTooWeak
+        );
+    });
+}
+
+#[test]
+fn test_create_vote_modulus_not_a_safe_prime() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+
+        // p + 1 is even, 97 and 2^61 - 1 are primes but not safe primes
+        let not_safe = [
+            &params.p + BigUint::one(),
+            BigUint::from(97u32),
+            BigUint::from(2_305_843_009_213_693_951u64),
+        ];
+        for p in not_safe.iter() {
+            let params = ElGamalParams {
+                p: p.clone(),
+                g: params.g.clone(),
+                h: params.h.clone(),
+            };
+            assert_err!(
+                create_vote_with_params(params.into()),
+                Error::<TestRuntime>::ModulusNotASafePrime
+            );
+        }
+    });
+}
+
+#[test]
+fn test_create_vote_invalid_generators() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let p = params.p.clone();
+        let p_less_one = &p - BigUint::one();
+
+        // 1, 0, p, p - 1 (of order 2) and g == h are rejected
+        let invalid = [
+            (BigUint::one(), params.h.clone()),
+            (params.g.clone(), BigUint::zero()),
+            (p.clone(), params.h.clone()),
+            (params.g.clone(), p_less_one),
+            (params.g.clone(), params.g.clone()),
+        ];
+        for (g, h) in invalid.iter() {
+            let params = ElGamalParams {
+                p: p.clone(),
+                g: g.clone(),
+                h: h.clone(),
+            };
+            assert_err!(
+                create_vote_with_params(params.into()),
+                Error::<TestRuntime>::InvalidGenerators
+            );
+        }
+    });
+}
+
 #[test]
 fn test_store_question_not_a_voting_authority() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -465,7 +691,8 @@ fn test_cast_ballot_no_vote_exists() {
             OffchainModule::cast_ballot(
                 Origin::signed(acct),
                 vote_id.clone(),
-                ballot.clone()
+                ballot.clone(),
+                Vec::new()
             ),
             Error::<TestRuntime>::VoteDoesNotExist
         );
@@ -502,7 +729,8 @@ fn test_cast_ballot_works_encoded() {
         assert_ok!(OffchainModule::cast_ballot(
             Origin::signed(acct),
             vote_id.clone(),
-            ballot.clone()
+            ballot.clone(),
+            Vec::new()
         ));
         let ballot_from_chain = OffchainModule::ballots(vote_id.clone(), acct);
         // A encrypted ballot is inserted to Ballots vec
@@ -527,7 +755,8 @@ fn test_cast_ballot_works_encoded() {
         assert_ok!(OffchainModule::cast_ballot(
             Origin::signed(acct),
             vote_id.clone(),
-            ballot.clone()
+            ballot.clone(),
+            Vec::new()
         ));
         // A encrypted ballot is inserted to Ballots vec
         assert_eq!(OffchainModule::ballots(vote_id, acct), ballot2.clone());
@@ -568,7 +797,8 @@ fn test_cast_ballot_works() {
         assert_ok!(OffchainModule::cast_ballot(
             Origin::signed(acct),
             vote_id.clone(),
-            ballot.clone()
+            ballot.clone(),
+            Vec::new()
         ));
         let ballot_from_chain = OffchainModule::ballots(vote_id.clone(), acct);
         // A encrypted ballot is inserted to Ballots vec
@@ -593,7 +823,8 @@ fn test_cast_ballot_works() {
         assert_ok!(OffchainModule::cast_ballot(
             Origin::signed(acct),
             vote_id.clone(),
-            ballot.clone()
+            ballot.clone(),
+            Vec::new()
         ));
         // A encrypted ballot is inserted to Ballots vec
         assert_eq!(OffchainModule::ballots(vote_id, acct), ballot2.clone());
@@ -606,6 +837,598 @@ fn test_cast_ballot_works() {
     });
 }
 
+#[test]
+fn test_cast_ballot_cipher_not_in_group() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let p = params.p.clone();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        let acct: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+
+        // p - 1 has order 2, 0 and p + b are out of range
+        let r = BigUint::from(1234u32);
+        let cipher = ElGamal::encrypt_encode(&BigUint::from(1u32), &r, &pk);
+        let invalid = vec![
+            BigCipher {
+                a: &p - BigUint::one(),
+                b: cipher.b.clone(),
+            },
+            BigCipher {
+                a: cipher.a.clone(),
+                b: BigUint::zero(),
+            },
+            BigCipher {
+                a: cipher.a.clone(),
+                b: &cipher.b + &p,
+            },
+        ];
+        for cipher in invalid {
+            let answers = vec![(topic_id.clone(), cipher.into())];
+            assert_err!(
+                OffchainModule::cast_ballot(
+                    Origin::signed(acct),
+                    vote_id.clone(),
+                    Ballot { answers },
+                    Vec::new()
+                ),
+                Error::<TestRuntime>::BallotCipherNotInGroup
+            );
+        }
+        assert!(OffchainModule::ciphers(topic_id, NR_OF_SHUFFLES).is_empty());
+    });
+}
+
+fn setup_randomizer(seed: u8) -> sr25519::Pair {
+    let randomizer = sr25519::Pair::from_seed(&[seed; 32]);
+    assert_ok!(OffchainModule::store_randomizer_key(
+        get_voting_authority(),
+        randomizer.public()
+    ));
+    randomizer
+}
+
+fn sign_ballot(
+    randomizer: &sr25519::Pair,
+    vote_id: &VoteId,
+    voter: &<TestRuntime as frame_system::Trait>::AccountId,
+    ballot: &Ballot,
+) -> Vec<RandomizerSignature> {
+    ballot
+        .answers
+        .iter()
+        .map(|(_, cipher)| RandomizerSignature {
+            signer: randomizer.public(),
+            signature: randomizer.sign(&randomizer_message(vote_id, voter, cipher)),
+        })
+        .collect()
+}
+
+#[test]
+fn test_store_and_remove_randomizer_key() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let randomizer = setup_randomizer(1u8);
+        assert_eq!(OffchainModule::randomizer_keys(), vec![randomizer.public()]);
+
+        // the same key cannot be registered twice
+        assert_err!(
+            OffchainModule::store_randomizer_key(
+                get_voting_authority(),
+                randomizer.public()
+            ),
+            Error::<TestRuntime>::RandomizerKeyAlreadyExists
+        );
+
+        // only a voting authority can register a randomizer key
+        let (sealer, _, _) = get_sealer_bob();
+        let other = sr25519::Pair::from_seed(&[2u8; 32]);
+        assert_err!(
+            OffchainModule::store_randomizer_key(sealer, other.public()),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+
+        // remove the key
+        assert_ok!(OffchainModule::remove_randomizer_key(
+            get_voting_authority(),
+            randomizer.public()
+        ));
+        assert!(OffchainModule::randomizer_keys().is_empty());
+        assert_err!(
+            OffchainModule::remove_randomizer_key(
+                get_voting_authority(),
+                randomizer.public()
+            ),
+            Error::<TestRuntime>::RandomizerKeyDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn test_set_randomizer_signature_required_wrong_phase() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+
+        // setup_vote already changes the phase to: Voting
+        let (vote_id, _) = setup_vote(params.into());
+        assert_err!(
+            OffchainModule::set_randomizer_signature_required(
+                get_voting_authority(),
+                vote_id,
+                true
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+    });
+}
+
+#[test]
+fn test_cast_ballot_requires_randomizer_signature() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // Setup Public Key
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        // Setup Vote -> require a randomizer signature
+        let (vote_id, topic_id) = setup_vote(params.into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_ok!(OffchainModule::set_randomizer_signature_required(
+            get_voting_authority(),
+            vote_id.clone(),
+            true
+        ));
+        assert!(OffchainModule::randomizer_signature_required(&vote_id));
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+        let randomizer = setup_randomizer(1u8);
+
+        // Create the voter + ballot
+        let acct: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
+        let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
+        let cipher: Cipher =
+            ElGamal::encrypt_encode(&BigUint::from(1u32), &r, &pk).into();
+        let ballot: Ballot = Ballot {
+            answers: vec![(topic_id.clone(), cipher.clone())],
+        };
+
+        // a ballot without signature is rejected
+        assert_err!(
+            OffchainModule::cast_ballot(
+                Origin::signed(acct),
+                vote_id.clone(),
+                ballot.clone(),
+                Vec::new()
+            ),
+            Error::<TestRuntime>::RandomizerSignatureMissing
+        );
+
+        // a ballot signed by an unknown randomizer is rejected
+        let unknown = sr25519::Pair::from_seed(&[2u8; 32]);
+        assert_err!(
+            OffchainModule::cast_ballot(
+                Origin::signed(acct),
+                vote_id.clone(),
+                ballot.clone(),
+                sign_ballot(&unknown, &vote_id, &acct, &ballot)
+            ),
+            Error::<TestRuntime>::RandomizerNotRegistered
+        );
+
+        // a ballot signed for another voter is rejected
+        let (_, bob, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::cast_ballot(
+                Origin::signed(acct),
+                vote_id.clone(),
+                ballot.clone(),
+                sign_ballot(&randomizer, &vote_id, &bob, &ballot)
+            ),
+            Error::<TestRuntime>::RandomizerSignatureInvalid
+        );
+
+        // a ballot signed by a registered randomizer is accepted
+        assert_ok!(OffchainModule::cast_ballot(
+            Origin::signed(acct),
+            vote_id.clone(),
+            ballot.clone(),
+            sign_ballot(&randomizer, &vote_id, &acct, &ballot)
+        ));
+        assert_eq!(OffchainModule::ballots(vote_id, acct), ballot);
+        assert_eq!(
+            OffchainModule::ciphers(topic_id, NR_OF_SHUFFLES),
+            vec![cipher]
+        );
+    });
+}
+
+fn setup_relayer(seed: u8) -> <TestRuntime as frame_system::Trait>::AccountId {
+    let relayer = sr25519::Pair::from_seed(&[seed; 32]).public();
+    assert_ok!(OffchainModule::store_relayer(
+        get_voting_authority(),
+        relayer
+    ));
+    relayer
+}
+
+fn sign_credential(
+    voter: &sr25519::Pair,
+    vote_id: &VoteId,
+    ballot: &Ballot,
+) -> VoterCredential {
+    VoterCredential {
+        voter: voter.public(),
+        signature: voter.sign(&relay_message(vote_id, ballot)),
+    }
+}
+
+#[test]
+fn test_store_and_remove_relayer() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let relayer = setup_relayer(3u8);
+        assert_eq!(OffchainModule::relayers(), vec![relayer]);
+
+        // the same relayer cannot be registered twice
+        assert_err!(
+            OffchainModule::store_relayer(get_voting_authority(), relayer),
+            Error::<TestRuntime>::RelayerAlreadyExists
+        );
+
+        // only a voting authority can register a relayer
+        let (sealer, bob, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::store_relayer(sealer, bob),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+
+        // remove the relayer
+        assert_ok!(OffchainModule::remove_relayer(
+            get_voting_authority(),
+            relayer
+        ));
+        assert!(OffchainModule::relayers().is_empty());
+        assert_err!(
+            OffchainModule::remove_relayer(get_voting_authority(), relayer),
+            Error::<TestRuntime>::RelayerDoesNotExist
+        );
+    });
+}
+
+#[test]
+fn test_relay_ballot() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // Setup Public Key
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        // Setup Vote -> require a randomizer signature
+        let (vote_id, topic_id) = setup_vote(params.into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        assert_ok!(OffchainModule::set_randomizer_signature_required(
+            get_voting_authority(),
+            vote_id.clone(),
+            true
+        ));
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+        let randomizer = setup_randomizer(1u8);
+        let relayer = setup_relayer(3u8);
+
+        // Create the voter + ballot, the voter has no account on chain
+        let voter = sr25519::Pair::from_seed(&[4u8; 32]);
+        let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
+        let cipher: Cipher =
+            ElGamal::encrypt_encode(&BigUint::from(1u32), &r, &pk).into();
+        let ballot: Ballot = Ballot {
+            answers: vec![(topic_id.clone(), cipher.clone())],
+        };
+        let signatures = sign_ballot(&randomizer, &vote_id, &voter.public(), &ballot);
+        let credential = sign_credential(&voter, &vote_id, &ballot);
+
+        // only a registered relayer can submit the ballot
+        let (sealer, _, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::relay_ballot(
+                sealer,
+                vote_id.clone(),
+                ballot.clone(),
+                signatures.clone(),
+                credential.clone()
+            ),
+            Error::<TestRuntime>::NotARelayer
+        );
+
+        // a credential signed for another ballot is rejected
+        let other_ballot = Ballot {
+            answers: vec![(topic_id.clone(), Cipher::default())],
+        };
+        assert_err!(
+            OffchainModule::relay_ballot(
+                Origin::signed(relayer),
+                vote_id.clone(),
+                ballot.clone(),
+                signatures.clone(),
+                sign_credential(&voter, &vote_id, &other_ballot)
+            ),
+            Error::<TestRuntime>::VoterCredentialInvalid
+        );
+
+        // the randomizer signatures must be issued for the voter, not the relayer
+        assert_err!(
+            OffchainModule::relay_ballot(
+                Origin::signed(relayer),
+                vote_id.clone(),
+                ballot.clone(),
+                sign_ballot(&randomizer, &vote_id, &relayer, &ballot),
+                credential.clone()
+            ),
+            Error::<TestRuntime>::RandomizerSignatureInvalid
+        );
+
+        // the ballot is stored as the voter's
+        assert_ok!(OffchainModule::relay_ballot(
+            Origin::signed(relayer),
+            vote_id.clone(),
+            ballot.clone(),
+            signatures.clone(),
+            credential.clone()
+        ));
+        assert_eq!(OffchainModule::ballots(&vote_id, voter.public()), ballot);
+        assert!(!Ballots::<TestRuntime>::contains_key(&vote_id, relayer));
+        assert_eq!(
+            OffchainModule::ciphers(topic_id.clone(), NR_OF_SHUFFLES),
+            vec![cipher.clone()]
+        );
+
+        // relaying the same credential a second time is rejected
+        assert_err!(
+            OffchainModule::relay_ballot(
+                Origin::signed(relayer),
+                vote_id.clone(),
+                ballot,
+                signatures,
+                credential
+            ),
+            Error::<TestRuntime>::BallotAlreadyCast
+        );
+        assert_eq!(
+            OffchainModule::ciphers(topic_id, NR_OF_SHUFFLES),
+            vec![cipher]
+        );
+    });
+}
+
+fn setup_credential_key(vote_id: &VoteId) -> BlindPrivateKey {
+    // two Mersenne primes: 2^61 - 1, 2^31 - 1
+    let p = BigUint::from(2_305_843_009_213_693_951u64);
+    let q = BigUint::from(2_147_483_647u64);
+    let key = BlindSignature::key_from_primes(&p, &q).unwrap();
+    assert_ok!(OffchainModule::store_credential_key(
+        get_voting_authority(),
+        vote_id.clone(),
+        key.public.clone().into()
+    ));
+    key
+}
+
+fn issue_token(
+    key: &BlindPrivateKey,
+    vote_id: &VoteId,
+    account: &<TestRuntime as frame_system::Trait>::AccountId,
+) -> Vec<u8> {
+    // the voting authority only sees the blinded message
+    let message = credential_message(vote_id, account);
+    let r = BigUint::from(12_345u32);
+    let blinded = BlindSignature::blind(&key.public, &message, &r);
+    let blind_signature = BlindSignature::sign(key, &blinded);
+    BlindSignature::unblind(&key.public, &blind_signature, &r)
+        .unwrap()
+        .to_bytes_be()
+}
+
+#[test]
+fn test_store_credential_key() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+
+        // the key can only be set before the voting phase
+        let key: CredentialKey = Default::default();
+        assert_err!(
+            OffchainModule::store_credential_key(
+                get_voting_authority(),
+                vote_id.clone(),
+                key.clone()
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        let (sealer, _, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::store_credential_key(sealer, vote_id.clone(), key),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+
+        // e = 1 would let anyone forge a token, the modulus must not be too small
+        let weak_keys = vec![
+            CredentialKey {
+                n: BigUint::from(2_305_843_009_213_693_951u64).to_bytes_be(),
+                e: vec![1u8],
+            },
+            CredentialKey {
+                n: BigUint::from(2_147_483_647u64).to_bytes_be(),
+                e: vec![3u8],
+            },
+        ];
+        for key in weak_keys {
+            assert_err!(
+                OffchainModule::store_credential_key(
+                    get_voting_authority(),
+                    vote_id.clone(),
+                    key
+                ),
+                Error::<TestRuntime>::CredentialKeyTooWeak
+            );
+        }
+
+        let key = setup_credential_key(&vote_id);
+        assert_eq!(
+            OffchainModule::credential_key(&vote_id),
+            Some(key.public.into())
+        );
+    });
+}
+
+#[test]
+fn test_credential_key_with_randomizer_signature() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+
+        // the randomizer would learn the one-time account of the voter
+        let key = setup_credential_key(&vote_id);
+        assert_err!(
+            OffchainModule::set_randomizer_signature_required(
+                get_voting_authority(),
+                vote_id.clone(),
+                true
+            ),
+            Error::<TestRuntime>::CredentialKeyWithRandomizerSignature
+        );
+
+        let (params, _, _) = Helper::setup_sm_system();
+        let other_vote_id = b"other vote".to_vec();
+        assert_ok!(OffchainModule::create_vote(
+            get_voting_authority(),
+            other_vote_id.clone(),
+            b"Other Vote".to_vec(),
+            params.into(),
+            Vec::new(),
+            2
+        ));
+        assert_ok!(OffchainModule::set_randomizer_signature_required(
+            get_voting_authority(),
+            other_vote_id.clone(),
+            true
+        ));
+        assert_err!(
+            OffchainModule::store_credential_key(
+                get_voting_authority(),
+                other_vote_id,
+                key.public.into()
+            ),
+            Error::<TestRuntime>::CredentialKeyWithRandomizerSignature
+        );
+    });
+}
+
+#[test]
+fn test_cast_anonymous_ballot() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        // Setup Public Key
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+
+        // Setup Vote -> require a voter token
+        let (vote_id, topic_id) = setup_vote(params.into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        let key = setup_credential_key(&vote_id);
+        set_vote_phase(vote_id.clone(), VotePhase::Voting);
+
+        // Create the one-time account + ballot
+        let account = sr25519::Pair::from_seed(&[5u8; 32]).public();
+        let r = OffchainModule::get_random_biguint_less_than(q).unwrap();
+        let cipher: Cipher =
+            ElGamal::encrypt_encode(&BigUint::from(1u32), &r, &pk).into();
+        let ballot: Ballot = Ballot {
+            answers: vec![(topic_id.clone(), cipher.clone())],
+        };
+        let token = issue_token(&key, &vote_id, &account);
+
+        // ballots without a token are rejected
+        assert_err!(
+            OffchainModule::cast_ballot(
+                Origin::signed(account),
+                vote_id.clone(),
+                ballot.clone(),
+                Vec::new()
+            ),
+            Error::<TestRuntime>::VoterTokenRequired
+        );
+
+        // the token is bound to the one-time account
+        let other = sr25519::Pair::from_seed(&[6u8; 32]).public();
+        assert_err!(
+            OffchainModule::cast_anonymous_ballot(
+                Origin::signed(other),
+                vote_id.clone(),
+                ballot.clone(),
+                Vec::new(),
+                token.clone()
+            ),
+            Error::<TestRuntime>::VoterTokenInvalid
+        );
+
+        assert_ok!(OffchainModule::cast_anonymous_ballot(
+            Origin::signed(account),
+            vote_id.clone(),
+            ballot.clone(),
+            Vec::new(),
+            token.clone()
+        ));
+        assert_eq!(OffchainModule::ballots(&vote_id, account), ballot);
+        assert!(OffchainModule::spent_token(&vote_id, &token));
+
+        // the token can only be used once, also with a different encoding
+        let mut padded = vec![0u8];
+        padded.extend(token.iter());
+        assert_err!(
+            OffchainModule::cast_anonymous_ballot(
+                Origin::signed(account),
+                vote_id.clone(),
+                ballot.clone(),
+                Vec::new(),
+                padded
+            ),
+            Error::<TestRuntime>::VoterTokenAlreadySpent
+        );
+        assert_eq!(
+            OffchainModule::ciphers(topic_id, NR_OF_SHUFFLES),
+            vec![cipher]
+        );
+    });
+}
+
+#[test]
+fn test_cast_anonymous_ballot_without_credential_key() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.into());
+        let account = sr25519::Pair::from_seed(&[5u8; 32]).public();
+        assert_err!(
+            OffchainModule::cast_anonymous_ballot(
+                Origin::signed(account),
+                vote_id,
+                Ballot::default(),
+                Vec::new(),
+                vec![1u8]
+            ),
+            Error::<TestRuntime>::CredentialKeyDoesNotExist
+        );
+    });
+}
+
 #[test]
 fn test_offchain_signed_tx_encoded() {
     let (mut t, pool_state, _) = ExternalityBuilder::build();
@@ -636,7 +1459,10 @@ fn test_offchain_signed_tx_encoded() {
         assert!(pool_state.read().transactions.is_empty());
         let tx = TestExtrinsic::decode(&mut &*tx).unwrap();
         assert_eq!(tx.signature.unwrap().0, 0);
-        assert_eq!(tx.call, Call::cast_ballot(vote_id, ballot.clone()));
+        assert_eq!(
+            tx.call,
+            Call::cast_ballot(vote_id, ballot.clone(), Vec::new())
+        );
     });
 }
 
@@ -838,7 +1664,8 @@ fn store_small_dummy_vote_works_encoded() {
         let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
         let voter = Origin::signed(account);
 
-        let vote_submission_result = OffchainModule::cast_ballot(voter, vote_id, ballot);
+        let vote_submission_result =
+            OffchainModule::cast_ballot(voter, vote_id, ballot, Vec::new());
         assert_ok!(vote_submission_result);
 
         // fetch the submitted ballot
@@ -883,7 +1710,8 @@ fn store_small_dummy_vote_works() {
         let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
         let voter = Origin::signed(account);
 
-        let vote_submission_result = OffchainModule::cast_ballot(voter, vote_id, ballot);
+        let vote_submission_result =
+            OffchainModule::cast_ballot(voter, vote_id, ballot, Vec::new());
         assert_ok!(vote_submission_result);
 
         // fetch the submitted ballot
@@ -928,7 +1756,8 @@ fn store_real_size_vote_works_encoded() {
         let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
         let voter = Origin::signed(account);
 
-        let vote_submission_result = OffchainModule::cast_ballot(voter, vote_id, ballot);
+        let vote_submission_result =
+            OffchainModule::cast_ballot(voter, vote_id, ballot, Vec::new());
         assert_ok!(vote_submission_result);
 
         // fetch the submitted ballot
@@ -973,7 +1802,8 @@ fn store_real_size_vote_works() {
         let account: <TestRuntime as frame_system::Trait>::AccountId = Default::default();
         let voter = Origin::signed(account);
 
-        let vote_submission_result = OffchainModule::cast_ballot(voter, vote_id, ballot);
+        let vote_submission_result =
+            OffchainModule::cast_ballot(voter, vote_id, ballot, Vec::new());
         assert_ok!(vote_submission_result);
 
         // fetch the submitted ballot
@@ -1034,8 +1864,12 @@ fn test_shuffle_ciphers_encoded() {
             let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
             let ballot: Ballot = Ballot { answers };
 
-            let vote_submission_result =
-                OffchainModule::cast_ballot(voter.clone(), vote_id.clone(), ballot);
+            let vote_submission_result = OffchainModule::cast_ballot(
+                voter.clone(),
+                vote_id.clone(),
+                ballot,
+                Vec::new(),
+            );
             assert_ok!(vote_submission_result);
         }
 
@@ -1106,8 +1940,12 @@ fn test_shuffle_ciphers() {
             let answers: Vec<(TopicId, Cipher)> = vec![(topic_id.clone(), cipher)];
             let ballot: Ballot = Ballot { answers };
 
-            let vote_submission_result =
-                OffchainModule::cast_ballot(voter.clone(), vote_id.clone(), ballot);
+            let vote_submission_result = OffchainModule::cast_ballot(
+                voter.clone(),
+                vote_id.clone(),
+                ballot,
+                Vec::new(),
+            );
             assert_ok!(vote_submission_result);
         }
 
@@ -1471,6 +2309,32 @@ fn test_store_public_key_share() {
     });
 }
 
+#[test]
+fn test_store_public_key_share_not_in_group() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, _) = setup_vote(params.clone().into());
+        let (who, account_id, sealer_id) = get_sealer_bob();
+
+        // p - h is not an element of the subgroup of order q
+        let r = BigUint::parse_bytes(b"1701411834604692317316873", 10).unwrap();
+        let proof = KeyGenerationProof::generate(&params, &sk.x, &pk.h, &r, &sealer_id);
+        let pk_share = PublicKeyShare {
+            proof: proof.into(),
+            pk: (&params.p - &pk.h).to_bytes_be(),
+        };
+        assert_err!(
+            OffchainModule::store_public_key_share(who, vote_id.clone(), pk_share),
+            Error::<TestRuntime>::PublicKeyShareNotInGroup
+        );
+        assert_eq!(
+            OffchainModule::key_share_by_sealer((vote_id, account_id)),
+            None
+        );
+    });
+}
+
 #[test]
 fn test_combine_public_key_shares_not_voting_authority() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -1739,11 +2603,52 @@ fn test_submit_decrypted_share() {
         assert_ok!(OffchainModule::submit_decrypted_shares(
             bob.clone(),
             vote_id,
-            topic_id,
-            bob_shares,
-            bob_proof.into(),
+            topic_id.clone(),
+            bob_shares.clone(),
+            bob_proof.clone().into(),
             NR_OF_SHUFFLES
         ));
+
+        // check that the proof is stored for observers
+        let (_, bob_account, _) = get_sealer_bob();
+        let payloads = OffchainModule::decryption_proofs(&topic_id, bob_account);
+        assert_eq!(payloads.len(), 1);
+        assert_eq!(payloads[0].nr_of_shuffles, NR_OF_SHUFFLES);
+        assert_eq!(payloads[0].proof, bob_proof.into());
+        assert_eq!(
+            OffchainModule::decrypted_shares(&topic_id, bob_account),
+            bob_shares
+        );
+    });
+}
+
+#[test]
+fn test_submit_decrypted_share_not_in_group() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, sk, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        let (bob, _, bob_sealer_id) = get_sealer_bob();
+        setup_sealer(&params, &sk, &pk, bob.clone(), &vote_id, &bob_sealer_id);
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+
+        // p - 1 has order 2
+        let shares = vec![(&params.p - BigUint::one()).to_bytes_be()];
+        let proof = DecryptionProof {
+            challenge: BigUint::one(),
+            response: BigUint::one(),
+        };
+        assert_err!(
+            OffchainModule::submit_decrypted_shares(
+                bob,
+                vote_id,
+                topic_id,
+                shares,
+                proof.into(),
+                NR_OF_SHUFFLES
+            ),
+            Error::<TestRuntime>::DecryptedShareNotInGroup
+        );
     });
 }
 
@@ -2174,6 +3079,47 @@ fn test_submit_shuffled_votes_and_proof() {
     });
 }
 
+#[test]
+fn test_submit_shuffled_votes_and_proof_cipher_not_in_group() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.clone().into());
+        setup_public_key(vote_id.clone(), pk.clone().into());
+        setup_ciphers(&vote_id, &topic_id, &pk, false);
+        set_vote_phase(vote_id.clone(), VotePhase::Tallying);
+        let (bob, _, _) = get_sealer_bob();
+
+        // shuffle the votes + create proof
+        let shuffle_state: ShuffleState = ShuffleStateStore::get((&vote_id, &topic_id))
+            .expect("shuffle state should exist for all existing votes & topics!");
+        let mut payload: ShufflePayload = OffchainModule::offchain_shuffle_and_proof(
+            &topic_id,
+            shuffle_state.iteration,
+            &pk,
+            shuffle_state.start_position,
+            shuffle_state.batch_size,
+        )
+        .unwrap();
+
+        // replace a of the first shuffled cipher by p - a
+        let a = BigUint::from_bytes_be(&payload.ciphers[0].a);
+        payload.ciphers[0].a = (&params.p - a).to_bytes_be();
+        assert_err!(
+            OffchainModule::submit_shuffled_votes_and_proof(
+                bob,
+                vote_id,
+                topic_id.clone(),
+                payload
+            ),
+            Error::<TestRuntime>::ShuffledCipherNotInGroup
+        );
+        assert!(
+            OffchainModule::ciphers(&topic_id, shuffle_state.iteration + 1).is_empty()
+        );
+    });
+}
+
 #[test]
 fn test_setup_ciphers_nr_of_shuffles_not_correct() {
     let (mut t, _, _) = ExternalityBuilder::build();
@@ -2193,3 +3139,72 @@ fn test_setup_ciphers_nr_of_shuffles_not_correct() {
         assert!(from_chain.is_empty());
     });
 }
+
+#[test]
+fn test_store_answers() {
+    let (mut t, _, _) = ExternalityBuilder::build();
+    t.execute_with(|| {
+        let (params, _, _) = Helper::setup_sm_system();
+        let (vote_id, topic_id) = setup_vote(params.into());
+        let answers: Vec<TopicAnswer> = vec![(0, b"no".to_vec()), (1, b"yes".to_vec())];
+
+        // the answers can only be set before the voting phase
+        assert_err!(
+            OffchainModule::store_answers(
+                get_voting_authority(),
+                vote_id.clone(),
+                topic_id.clone(),
+                answers.clone()
+            ),
+            Error::<TestRuntime>::WrongVotePhase
+        );
+
+        set_vote_phase(vote_id.clone(), VotePhase::KeyGeneration);
+        let (sealer, _, _) = get_sealer_bob();
+        assert_err!(
+            OffchainModule::store_answers(
+                sealer,
+                vote_id.clone(),
+                topic_id.clone(),
+                answers.clone()
+            ),
+            Error::<TestRuntime>::NotAVotingAuthority
+        );
+        assert_err!(
+            OffchainModule::store_answers(
+                get_voting_authority(),
+                vote_id.clone(),
+                b"unknown".to_vec(),
+                answers.clone()
+            ),
+            Error::<TestRuntime>::TopicDoesNotExist
+        );
+
+        // each value and label can only be used once
+        let invalid: Vec<Vec<TopicAnswer>> = vec![
+            vec![],
+            vec![(0, b"no".to_vec()), (0, b"yes".to_vec())],
+            vec![(0, b"no".to_vec()), (1, b"no".to_vec())],
+            vec![(0, Vec::new())],
+        ];
+        for answers in invalid {
+            assert_err!(
+                OffchainModule::store_answers(
+                    get_voting_authority(),
+                    vote_id.clone(),
+                    topic_id.clone(),
+                    answers
+                ),
+                Error::<TestRuntime>::TopicAnswersInvalid
+            );
+        }
+
+        assert_ok!(OffchainModule::store_answers(
+            get_voting_authority(),
+            vote_id.clone(),
+            topic_id.clone(),
+            answers.clone()
+        ));
+        assert_eq!(OffchainModule::answers(&topic_id), answers);
+    });
+}
@@ -1,10 +1,14 @@
 use alloc::str::FromStr;
 use codec::{Decode, Encode};
+use crypto::blind::BlindPublicKey;
+use crypto::groups::NamedGroup;
 use crypto::proofs::{decryption::DecryptionProof, keygen::KeyGenerationProof};
 use crypto::types::{Cipher as BigCipher, ElGamalParams, PublicKey as ElGamalPK};
+pub use crypto::{proofs::shuffle::ShuffleProof, types::BigS};
 use frame_system::offchain::{SignedPayload, SigningTypes};
 use num_bigint::BigUint;
 use num_traits::One;
+use sp_core::sr25519;
 use sp_runtime::RuntimeDebug;
 use sp_std::{collections::btree_map::BTreeMap, vec::Vec};
 use serde::{Deserialize, Serialize};
@@ -92,6 +96,8 @@ pub struct PublicParameters {
     pub g: Vec<u8>,
     // 2. public generator h
     pub h: Vec<u8>,
+    // the name of a standardized group (crypto::groups), p, g and h are resolved from it
+    pub group: Option<GroupName>,
 }
 
 /// defines the function q = (p - 1) / 2 with return type BigUint.
@@ -128,6 +134,17 @@ impl Into<PublicParameters> for ElGamalParams {
             p: self.p.to_bytes_be(),
             g: self.g.to_bytes_be(),
             h: self.h.to_bytes_be(),
+            group: None,
+        }
+    }
+}
+
+impl From<NamedGroup> for PublicParameters {
+    fn from(group: NamedGroup) -> Self {
+        let params: PublicParameters = group.params().into();
+        PublicParameters {
+            group: Some(group.name().as_bytes().to_vec()),
+            ..params
         }
     }
 }
@@ -142,17 +159,6 @@ impl Into<ElGamalParams> for PublicParameters {
     }
 }
 
-/// Algorithm 8.47: The s value of the ShuffleProof
-#[derive(Default, Clone, PartialEq, Eq, Debug)]
-pub struct BigS {
-    pub s1: BigUint,               // s1
-    pub s2: BigUint,               // s2
-    pub s3: BigUint,               // s3
-    pub s4: BigUint,               // s4
-    pub vec_s_hat: Vec<BigUint>,   // vec_s_hat
-    pub vec_s_tilde: Vec<BigUint>, // vec_s_tilde
-}
-
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
 pub struct BigSAsBytes {
     pub s1: Vec<u8>,               // s1
@@ -205,15 +211,6 @@ impl Into<BigSAsBytes> for BigS {
     }
 }
 
-/// Algorithm 8.47: The ShuffleProof
-#[derive(Default, Clone, PartialEq, Eq, Debug)]
-pub struct ShuffleProof {
-    pub challenge: BigUint,                          // challenge
-    pub S: BigS,                                     // S
-    pub permutation_commitments: Vec<BigUint>,       // permutation_commitments
-    pub permutation_chain_commitments: Vec<BigUint>, // permutation_chain_commitments
-}
-
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
 pub struct ShuffleProofAsBytes {
     pub challenge: Vec<u8>,                          // challenge
@@ -282,6 +279,7 @@ pub struct ShuffleState {
 
 pub type VoteId = Vec<u8>;
 pub type Title = Vec<u8>;
+pub type GroupName = Vec<u8>;
 
 // both types are strings encoded as bytes
 pub type NrOfShuffles = u8;
@@ -296,12 +294,103 @@ pub type TopicResult = BTreeMap<Plaintext, Count>;
 // topicId and question (string as Vec<u8>)
 pub type Topic = (TopicId, TopicQuestion);
 
+// the encrypted value and the label of an answer (string as Vec<u8>)
+pub type AnswerValue = u32;
+pub type AnswerLabel = Vec<u8>;
+pub type TopicAnswer = (AnswerValue, AnswerLabel);
+
 /// A ballot is composed of all answers of a voter
 #[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
 pub struct Ballot {
     pub answers: Vec<(TopicId, Cipher)>,
 }
 
+/// the sr25519 public key of a randomizer
+pub type RandomizerKey = sr25519::Public;
+
+/// the context prefix of the message signed by a randomizer
+pub const RANDOMIZER_SIGNING_CONTEXT: &[u8] = b"provotum-randomizer";
+
+/// The randomizer's signature over a re-encrypted cipher.
+/// Binds the cipher to the vote and the voter (see: `randomizer_message`).
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct RandomizerSignature {
+    pub signer: RandomizerKey,
+    pub signature: sr25519::Signature,
+}
+
+/// Creates the message a randomizer signs for a re-encrypted cipher.
+/// Used by the randomizer to sign and by the pallet to verify.
+pub fn randomizer_message<AccountId: Encode>(
+    vote_id: &VoteId,
+    voter: &AccountId,
+    cipher: &Cipher,
+) -> Vec<u8> {
+    (RANDOMIZER_SIGNING_CONTEXT, vote_id, voter, cipher).encode()
+}
+
+/// the context prefix of the message signed by a voter whose ballot is relayed
+pub const RELAY_SIGNING_CONTEXT: &[u8] = b"provotum-relay";
+
+/// The eligibility credential of a voter whose ballot is submitted by a relayer.
+/// The voter's key identifies the voter, the signature covers the ballot (see: `relay_message`).
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+pub struct VoterCredential {
+    pub voter: sr25519::Public,
+    pub signature: sr25519::Signature,
+}
+
+/// Creates the message a voter signs to have the ballot relayed.
+/// Used by the voter to sign and by the relayer and the pallet to verify.
+pub fn relay_message(vote_id: &VoteId, ballot: &Ballot) -> Vec<u8> {
+    (RELAY_SIGNING_CONTEXT, vote_id, ballot).encode()
+}
+
+/// the BlindPublicKey from the crypto crate.
+/// the voting authority's key to blind-sign the tokens of eligible voters.
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct CredentialKey {
+    // modulus: n
+    pub n: Vec<u8>,
+    // public exponent: e
+    pub e: Vec<u8>,
+}
+
+impl Into<CredentialKey> for BlindPublicKey {
+    fn into(self) -> CredentialKey {
+        CredentialKey {
+            n: self.n.to_bytes_be(),
+            e: self.e.to_bytes_be(),
+        }
+    }
+}
+
+impl Into<BlindPublicKey> for CredentialKey {
+    fn into(self) -> BlindPublicKey {
+        BlindPublicKey {
+            n: BigUint::from_bytes_be(&self.n),
+            e: BigUint::from_bytes_be(&self.e),
+        }
+    }
+}
+
+/// the unblinded signature of the voting authority over a one-time account (big-endian)
+pub type VoterToken = Vec<u8>;
+
+/// the context prefix of the message blind-signed by the voting authority
+pub const CREDENTIAL_SIGNING_CONTEXT: &[u8] = b"provotum-credential";
+
+/// Creates the message a voter gets blind-signed: the vote and the one-time account.
+/// Used by the voter to blind and by the pallet to verify the token.
+pub fn credential_message<AccountId: Encode>(
+    vote_id: &VoteId,
+    account: &AccountId,
+) -> Vec<u8> {
+    (CREDENTIAL_SIGNING_CONTEXT, vote_id, account).encode()
+}
+
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
 pub enum VotePhase {
     KeyGeneration,
@@ -396,6 +485,14 @@ impl From<DecryptedShareProof> for DecryptionProof {
     }
 }
 
+// the proof of the decrypted shares submitted by a sealer, the shares are stored in DecryptedShares
+// stored to allow observers to verify the decryption (audit trail)
+#[derive(Encode, Decode, Default, Clone, PartialEq, Eq, Debug)]
+pub struct DecryptionPayload {
+    pub nr_of_shuffles: NrOfShuffles,
+    pub proof: DecryptedShareProof,
+}
+
 /// the type to sign and send transactions.
 #[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
 pub struct Payload<Public> {
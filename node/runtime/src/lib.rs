@@ -101,10 +101,13 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     spec_name: create_runtime_str!("provotum"),
     impl_name: create_runtime_str!("provotum"),
     authoring_version: 1,
-    spec_version: 2,
+    // 3: the storage of the mixnet pallet can't be decoded by version 2, there is no migration.
+    // A chain of version 2 must be reset, see "Runtime Upgrades" in the README.
+    spec_version: 3,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    // 2: the calls of the mixnet pallet changed, transactions signed for version 1 are invalid
+    transaction_version: 2,
 };
 
 pub const MILLISECS_PER_BLOCK: u64 = 6000;
@@ -272,6 +275,10 @@ impl pallet_sudo::Trait for Runtime {
 
 parameter_types! {
     pub const BlockDuration: BlockNumber = 1u64;
+    pub const MinModulusBits: u32 = 2048;
+    // the safe prime test of a 2048bit modulus takes ~1.6s of the ~2.6s extrinsic limit
+    pub const MaxCustomModulusBits: u32 = 2048;
+    pub const MinCredentialKeyBits: u32 = 2048;
 }
 
 impl pallet_mixnet::Trait for Runtime {
@@ -279,6 +286,9 @@ impl pallet_mixnet::Trait for Runtime {
     type Call = Call;
     type AuthorityId = pallet_mixnet::keys::TestAuthId;
     type BlockDuration = BlockDuration;
+    type MinModulusBits = MinModulusBits;
+    type MaxCustomModulusBits = MaxCustomModulusBits;
+    type MinCredentialKeyBits = MinCredentialKeyBits;
 }
 
 // Payload data to be signed when making signed transaction from off-chain workers
@@ -0,0 +1,130 @@
+use crate::{error::RandomizerError, rate_limit::peer_ip, signer::AccountId};
+use actix_web::HttpRequest;
+use pallet_mixnet::types::VoteId;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A single line of the audit log.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub endpoint: String,
+    pub ip: String,
+    pub vote_id: String,
+    pub voters: Vec<AccountId>,
+    pub nr_of_ciphers: usize,
+    pub outcome: String,
+}
+
+impl AuditEntry {
+    pub fn new<T>(
+        endpoint: &str,
+        req: &HttpRequest,
+        vote_id: &VoteId,
+        voters: Vec<AccountId>,
+        nr_of_ciphers: usize,
+        result: &Result<T, RandomizerError>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let outcome = match result {
+            Ok(_) => "ok".to_string(),
+            Err(err) => err.to_string(),
+        };
+        AuditEntry {
+            timestamp,
+            endpoint: endpoint.to_string(),
+            ip: peer_ip(req),
+            vote_id: String::from_utf8_lossy(vote_id).to_string(),
+            voters,
+            nr_of_ciphers,
+            outcome,
+        }
+    }
+}
+
+/// An append-only log of all randomization requests, one JSON object per line.
+pub struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(AuditLog {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn record(&self, entry: &AuditEntry) -> Result<(), RandomizerError> {
+        let mut line = serde_json::to_vec(entry).map_err(io::Error::from)?;
+        line.push(b'\n');
+        let mut file = self.file.lock().expect("audit log lock is poisoned!");
+        file.write_all(&line)?;
+        file.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditEntry, AuditLog};
+    use crate::error::RandomizerError;
+    use actix_web::test;
+    use sp_keyring::AccountKeyring;
+    use std::{env, fs};
+
+    #[test]
+    fn it_should_append_entries() {
+        let path = env::temp_dir().join("randomizer-audit-test.log");
+        let _ = fs::remove_file(&path);
+        let path = path.to_str().unwrap();
+        let req = test::TestRequest::default().to_http_request();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let voters = vec![AccountKeyring::Alice.to_account_id()];
+
+        // the log is appended to when it is re-opened
+        let ok: Result<(), RandomizerError> = Ok(());
+        AuditLog::open(path)
+            .unwrap()
+            .record(&AuditEntry::new(
+                "/randomize",
+                &req,
+                &vote_id,
+                voters.clone(),
+                1,
+                &ok,
+            ))
+            .unwrap();
+        let err: Result<(), RandomizerError> = Err(RandomizerError::RateLimited);
+        AuditLog::open(path)
+            .unwrap()
+            .record(&AuditEntry::new(
+                "/randomize",
+                &req,
+                &vote_id,
+                voters,
+                1,
+                &err,
+            ))
+            .unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        let entries = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap())
+            .collect::<Vec<AuditEntry>>();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].outcome, "ok");
+        assert_eq!(entries[0].vote_id, "20201212");
+        assert_eq!(entries[1].outcome, RandomizerError::RateLimited.to_string());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{error::RandomizerError, rate_limit::RateLimits, signer::AccountId};
+use actix_web::{post, web};
+use pallet_mixnet::types::VoteId;
+use provotum_sdk::auth::auth_message;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fs,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use substrate_subxt::sp_core::{sr25519, Pair as KeyPairGenerator};
+
+/// the time a voter has to sign a challenge
+const CHALLENGE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// the time a token is valid
+const TOKEN_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// the open challenge per (vote, voter) + when it expires
+type Challenges = HashMap<(VoteId, AccountId), ([u8; 32], Instant)>;
+
+struct Session {
+    vote_id: VoteId,
+    voter: AccountId,
+    expires: Instant,
+}
+
+/// Authenticates voters using a signed challenge.
+///
+/// 1. the voter requests a challenge for a vote (`/auth/challenge`)
+/// 2. the voter signs the challenge with its sr25519 account and receives a token (`/auth/token`)
+/// 3. the token is sent along with each randomization request
+///
+/// Only voters on the eligible voter list of the vote receive a challenge.
+pub struct VoterAuth {
+    eligible_voters: HashMap<VoteId, HashSet<AccountId>>,
+    challenges: Mutex<Challenges>,
+    tokens: Mutex<HashMap<String, Session>>,
+}
+
+impl VoterAuth {
+    pub fn new(eligible_voters: HashMap<VoteId, HashSet<AccountId>>) -> Self {
+        VoterAuth {
+            eligible_voters,
+            challenges: Mutex::new(HashMap::new()),
+            tokens: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads the eligible voters from a JSON file: `{ "<vote_id>": ["<ss58 account>", ...] }`
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        let voters: HashMap<String, HashSet<AccountId>> = serde_json::from_str(&content)?;
+        let eligible_voters = voters
+            .into_iter()
+            .map(|(vote_id, voters)| (vote_id.as_bytes().to_vec(), voters))
+            .collect();
+        Ok(VoterAuth::new(eligible_voters))
+    }
+
+    pub fn is_eligible(&self, vote_id: &VoteId, voter: &AccountId) -> bool {
+        self.eligible_voters
+            .get(vote_id)
+            .map(|voters| voters.contains(voter))
+            .unwrap_or(false)
+    }
+
+    /// Creates a new challenge for an eligible voter, replaces any previous challenge.
+    pub fn create_challenge(
+        &self,
+        vote_id: &VoteId,
+        voter: &AccountId,
+    ) -> Result<[u8; 32], RandomizerError> {
+        if !self.is_eligible(vote_id, voter) {
+            return Err(RandomizerError::NotEligible);
+        }
+        let challenge: [u8; 32] = rand::thread_rng().gen();
+        let mut challenges = self.challenges.lock().expect("challenge lock is poisoned!");
+        challenges.insert(
+            (vote_id.clone(), voter.clone()),
+            (challenge, Instant::now() + CHALLENGE_TTL),
+        );
+        Ok(challenge)
+    }
+
+    /// Verifies the voter's signature of the challenge and issues a token.
+    /// A challenge can only be used once, it is consumed only if the signature is valid.
+    pub fn verify_challenge(
+        &self,
+        vote_id: &VoteId,
+        voter: &AccountId,
+        signature: &sr25519::Signature,
+    ) -> Result<String, RandomizerError> {
+        let key = (vote_id.clone(), voter.clone());
+        let mut challenges = self.challenges.lock().expect("challenge lock is poisoned!");
+        let (challenge, expires) = *challenges
+            .get(&key)
+            .ok_or(RandomizerError::InvalidChallengeResponse)?;
+        if expires < Instant::now() {
+            challenges.remove(&key);
+            return Err(RandomizerError::InvalidChallengeResponse);
+        }
+
+        // the account id of a voter is its sr25519 public key
+        let public = sr25519::Public::from_raw(voter.clone().into());
+        let message = auth_message(vote_id, &challenge);
+        if !sr25519::Pair::verify(signature, &message, &public) {
+            return Err(RandomizerError::InvalidChallengeResponse);
+        }
+        challenges.remove(&key);
+        drop(challenges);
+        Ok(self.issue_token(vote_id, voter))
+    }
+
+    pub fn issue_token(&self, vote_id: &VoteId, voter: &AccountId) -> String {
+        let token = hex::encode(rand::thread_rng().gen::<[u8; 32]>());
+        let session = Session {
+            vote_id: vote_id.clone(),
+            voter: voter.clone(),
+            expires: Instant::now() + TOKEN_TTL,
+        };
+        let mut tokens = self.tokens.lock().expect("token lock is poisoned!");
+        let now = Instant::now();
+        tokens.retain(|_, session| session.expires > now);
+        tokens.insert(token.clone(), session);
+        token
+    }
+
+    /// Checks that the token was issued to the voter for the vote.
+    pub fn authenticate(
+        &self,
+        token: &str,
+        vote_id: &VoteId,
+        voter: &AccountId,
+    ) -> Result<(), RandomizerError> {
+        let tokens = self.tokens.lock().expect("token lock is poisoned!");
+        match tokens.get(token) {
+            Some(session)
+                if &session.vote_id == vote_id
+                    && &session.voter == voter
+                    && session.expires > Instant::now() =>
+            {
+                Ok(())
+            }
+            _ => Err(RandomizerError::Unauthorized),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct ChallengeRequestBody {
+    pub vote_id: VoteId,
+    pub voter: AccountId,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct ChallengeResponseBody {
+    // hex encoded
+    pub challenge: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct TokenRequestBody {
+    pub vote_id: VoteId,
+    pub voter: AccountId,
+    // hex encoded sr25519 signature of: auth_message(vote_id, challenge)
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct TokenResponseBody {
+    pub token: String,
+    pub expires_in: u64,
+}
+
+#[post("/auth/challenge")]
+pub async fn get_challenge(
+    body: web::Json<ChallengeRequestBody>,
+    auth: web::Data<VoterAuth>,
+    limits: web::Data<RateLimits>,
+) -> Result<web::Json<ChallengeResponseBody>, RandomizerError> {
+    limits.check_auth(&body.voter)?;
+    let challenge = auth.create_challenge(&body.vote_id, &body.voter)?;
+    Ok(web::Json(ChallengeResponseBody {
+        challenge: hex::encode(challenge),
+    }))
+}
+
+#[post("/auth/token")]
+pub async fn get_token(
+    body: web::Json<TokenRequestBody>,
+    auth: web::Data<VoterAuth>,
+    limits: web::Data<RateLimits>,
+) -> Result<web::Json<TokenResponseBody>, RandomizerError> {
+    limits.check_auth(&body.voter)?;
+    let signature = hex::decode(&body.signature)
+        .ok()
+        .filter(|bytes| bytes.len() == 64)
+        .map(|bytes| sr25519::Signature::from_slice(&bytes))
+        .ok_or(RandomizerError::InvalidChallengeResponse)?;
+    let token = auth.verify_challenge(&body.vote_id, &body.voter, &signature)?;
+    Ok(web::Json(TokenResponseBody {
+        token,
+        expires_in: TOKEN_TTL.as_secs(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_challenge, get_token, ChallengeRequestBody, ChallengeResponseBody, TokenRequestBody,
+        TokenResponseBody, VoterAuth,
+    };
+    use crate::{error::RandomizerError, rate_limit::RateLimits};
+    use actix_web::{http::StatusCode, test, App};
+    use provotum_sdk::auth::auth_message;
+    use sp_keyring::AccountKeyring;
+    use std::collections::{HashMap, HashSet};
+    use substrate_subxt::sp_core::Pair;
+
+    fn setup_auth() -> VoterAuth {
+        let vote_id = "20201212".as_bytes().to_vec();
+        let mut voters = HashSet::new();
+        voters.insert(AccountKeyring::Alice.to_account_id());
+        let mut eligible_voters = HashMap::new();
+        eligible_voters.insert(vote_id, voters);
+        VoterAuth::new(eligible_voters)
+    }
+
+    #[test]
+    fn it_should_authenticate_with_token_for_vote_and_voter() {
+        let auth = setup_auth();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+
+        let token = auth.issue_token(&vote_id, &alice);
+        assert!(auth.authenticate(&token, &vote_id, &alice).is_ok());
+        assert!(auth.authenticate(&token, &vote_id, &bob).is_err());
+        assert!(auth
+            .authenticate(&token, &"20201213".as_bytes().to_vec(), &alice)
+            .is_err());
+        assert!(auth.authenticate("not-a-token", &vote_id, &alice).is_err());
+    }
+
+    #[test]
+    fn it_should_only_issue_challenges_to_eligible_voters() {
+        let auth = setup_auth();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let bob = AccountKeyring::Bob.to_account_id();
+        match auth.create_challenge(&vote_id, &bob) {
+            Err(RandomizerError::NotEligible) => (),
+            other => panic!("expected NotEligible, got: {:?}", other),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_post_challenge_and_token() {
+        let app = App::new()
+            .data(setup_auth())
+            .data(RateLimits::new(10, 10, 10))
+            .service(get_challenge)
+            .service(get_token);
+        let mut test_app = test::init_service(app).await;
+        let vote_id = "20201212".as_bytes().to_vec();
+        let alice = AccountKeyring::Alice;
+
+        // 1. request a challenge
+        let req = test::TestRequest::post()
+            .uri("/auth/challenge")
+            .set_json(&ChallengeRequestBody {
+                vote_id: vote_id.clone(),
+                voter: alice.to_account_id(),
+            })
+            .to_request();
+        let resp: ChallengeResponseBody = test::read_response_json(&mut test_app, req).await;
+        let challenge = hex::decode(resp.challenge).unwrap();
+
+        // 2. an invalid signature doesn't consume the challenge
+        let signature = AccountKeyring::Bob
+            .pair()
+            .sign(&auth_message(&vote_id, &challenge));
+        let req = test::TestRequest::post()
+            .uri("/auth/token")
+            .set_json(&TokenRequestBody {
+                vote_id: vote_id.clone(),
+                voter: alice.to_account_id(),
+                signature: hex::encode(signature.0),
+            })
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // 3. sign the challenge and request a token
+        let signature = alice.pair().sign(&auth_message(&vote_id, &challenge));
+        let body = TokenRequestBody {
+            vote_id: vote_id.clone(),
+            voter: alice.to_account_id(),
+            signature: hex::encode(signature.0),
+        };
+        let req = test::TestRequest::post()
+            .uri("/auth/token")
+            .set_json(&body)
+            .to_request();
+        let resp: TokenResponseBody = test::read_response_json(&mut test_app, req).await;
+        assert!(!resp.token.is_empty());
+
+        // 4. the challenge cannot be used twice
+        let req = test::TestRequest::post()
+            .uri("/auth/token")
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_rt::test]
+    async fn test_post_challenge_not_eligible() {
+        let app = App::new()
+            .data(setup_auth())
+            .data(RateLimits::new(10, 10, 10))
+            .service(get_challenge);
+        let mut test_app = test::init_service(app).await;
+        let req = test::TestRequest::post()
+            .uri("/auth/challenge")
+            .set_json(&ChallengeRequestBody {
+                vote_id: "20201212".as_bytes().to_vec(),
+                voter: AccountKeyring::Bob.to_account_id(),
+            })
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+    }
+
+    fn challenge_request(voter: AccountKeyring) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/auth/challenge")
+            .set_json(&ChallengeRequestBody {
+                vote_id: "20201212".as_bytes().to_vec(),
+                voter: voter.to_account_id(),
+            })
+    }
+
+    #[actix_rt::test]
+    async fn test_post_challenge_rate_limited_per_voter() {
+        let app = App::new()
+            .data(setup_auth())
+            .data(RateLimits::new(10, 10, 1))
+            .service(get_challenge);
+        let mut test_app = test::init_service(app).await;
+        let req = challenge_request(AccountKeyring::Alice).to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let req = challenge_request(AccountKeyring::Alice).to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[actix_rt::test]
+    async fn test_post_challenge_not_limited_per_ip() {
+        // many voters can authenticate from the same IP
+        let mut auth = setup_auth();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let voters = auth.eligible_voters.get_mut(&vote_id).unwrap();
+        voters.insert(AccountKeyring::Bob.to_account_id());
+        let app = App::new()
+            .data(auth)
+            .data(RateLimits::new(10, 1, 1))
+            .service(get_challenge);
+        let mut test_app = test::init_service(app).await;
+        for voter in [AccountKeyring::Alice, AccountKeyring::Bob].iter() {
+            let req = challenge_request(*voter).to_request();
+            let resp = test::call_service(&mut test_app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+        }
+    }
+}
@@ -0,0 +1,305 @@
+use crate::{
+    audit::{AuditEntry, AuditLog},
+    auth::VoterAuth,
+    error::RandomizerError,
+    keys::PublicKeyCache,
+    randomizer::{is_valid_verifier_pk, randomize_cipher, ResponseBody},
+    rate_limit::RateLimits,
+    signer::{AccountId, RandomizerSigner},
+};
+use actix_web::{web, web::Bytes, HttpRequest, HttpResponse};
+use crypto::types::{Cipher, PublicKey};
+use futures::stream::{self, StreamExt};
+use num_bigint::BigUint;
+use pallet_mixnet::types::VoteId;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// the maximum number of ciphers per batch request
+pub const MAX_BATCH_SIZE: usize = 10_000;
+
+/// the maximum size of a JSON request body (bytes), batches exceed the default limit of 32kb
+pub const JSON_PAYLOAD_LIMIT: usize = 64 * 1024 * 1024;
+
+/// the number of ciphers which are re-encrypted (in parallel) before results are streamed
+const CHUNK_SIZE: usize = 64;
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchEntry {
+    pub cipher: Cipher,
+    pub verifier_pk: BigUint,
+    pub voter: AccountId,
+    // the token issued to the voter for the vote (see: /auth/token)
+    pub token: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchRequestBody {
+    pub vote_id: VoteId,
+    pub entries: Vec<BatchEntry>,
+}
+
+/// A single line of the streamed response, index refers to the position in the request.
+#[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
+pub struct BatchResponseEntry {
+    pub index: usize,
+    #[serde(flatten)]
+    pub response: ResponseBody,
+}
+
+/// Registers the batch endpoint, the larger JSON payload limit only applies to this route.
+pub fn batch_service(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/randomize/batch")
+            .app_data(web::JsonConfig::default().limit(JSON_PAYLOAD_LIMIT))
+            .route(web::post().to(randomize_batch)),
+    );
+}
+
+/// Re-encrypts all ciphers of a vote.
+/// The results are streamed as newline delimited JSON, one `BatchResponseEntry` per line.
+pub async fn randomize_batch(
+    req: HttpRequest,
+    body: web::Json<BatchRequestBody>,
+    keys: web::Data<PublicKeyCache>,
+    signer: web::Data<RandomizerSigner>,
+    auth: web::Data<VoterAuth>,
+    limits: web::Data<RateLimits>,
+    audit: web::Data<AuditLog>,
+) -> Result<HttpResponse, RandomizerError> {
+    let body = body.into_inner();
+    let result = validate_batch(&req, &body, &keys, &auth, &limits).await;
+
+    // record every request in the audit log, also the rejected ones
+    let mut voters = body
+        .entries
+        .iter()
+        .map(|entry| entry.voter.clone())
+        .collect::<Vec<AccountId>>();
+    voters.sort();
+    voters.dedup();
+    let nr_of_ciphers = body.entries.len();
+    let entry = AuditEntry::new(
+        "/randomize/batch",
+        &req,
+        &body.vote_id,
+        voters,
+        nr_of_ciphers,
+        &result,
+    );
+    audit.record(&entry)?;
+    let pk = result?;
+
+    let vote_id = Arc::new(body.vote_id);
+    let pk = Arc::new(pk);
+    let signer = signer.into_inner();
+    let chunks = body
+        .entries
+        .into_iter()
+        .enumerate()
+        .collect::<Vec<_>>()
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| chunk.to_vec())
+        .collect::<Vec<_>>();
+
+    // re-encrypt chunk by chunk on the thread pool, stream each chunk once it is done
+    let results = stream::iter(chunks).then(move |chunk| {
+        let vote_id = vote_id.clone();
+        let pk = pk.clone();
+        let signer = signer.clone();
+        web::block(move || randomize_chunk(&vote_id, chunk, &pk, &signer))
+    });
+    let stream = results.map(|result| result.map(Bytes::from));
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(Box::pin(stream)))
+}
+
+/// Validates all entries before starting to stream, returns the vote's public key.
+async fn validate_batch(
+    req: &HttpRequest,
+    body: &BatchRequestBody,
+    keys: &PublicKeyCache,
+    auth: &VoterAuth,
+    limits: &RateLimits,
+) -> Result<PublicKey, RandomizerError> {
+    limits.check_ip(req)?;
+    if body.entries.len() > MAX_BATCH_SIZE {
+        return Err(RandomizerError::BatchTooLarge(body.entries.len()));
+    }
+
+    // authenticate each voter + enforce the per-voter rate limit
+    for entry in body.entries.iter() {
+        auth.authenticate(&entry.token, &body.vote_id, &entry.voter)?;
+        limits.check_voter(&entry.voter, 1)?;
+    }
+
+    // resolve the vote's public key from chain, never trust the client
+    let pk = keys.get_public_key(&body.vote_id).await?;
+
+    // the verifier's public keys must be elements of the group
+    if let Some(index) = body
+        .entries
+        .iter()
+        .position(|entry| !is_valid_verifier_pk(&entry.verifier_pk, &pk))
+    {
+        return Err(RandomizerError::InvalidBatchEntry(index));
+    }
+    Ok(pk)
+}
+
+/// Re-encrypts the chunk in parallel and serializes each result as a single line of JSON.
+fn randomize_chunk(
+    vote_id: &VoteId,
+    chunk: Vec<(usize, BatchEntry)>,
+    pk: &PublicKey,
+    signer: &RandomizerSigner,
+) -> Result<Vec<u8>, serde_json::Error> {
+    let entries = chunk
+        .into_par_iter()
+        .map(|(index, entry)| BatchResponseEntry {
+            index,
+            response: randomize_cipher(
+                vote_id,
+                &entry.cipher,
+                &entry.verifier_pk,
+                &entry.voter,
+                pk,
+                signer,
+            ),
+        })
+        .collect::<Vec<BatchResponseEntry>>();
+
+    let mut lines = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut lines, &entry)?;
+        lines.push(b'\n');
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{batch_service, randomize_chunk, BatchEntry, BatchRequestBody, BatchResponseEntry};
+    use crate::{
+        audit::AuditLog, auth::VoterAuth, keys::PublicKeyCache, rate_limit::RateLimits,
+        signer::RandomizerSigner,
+    };
+    use actix_web::{http::StatusCode, test, App};
+    use crypto::{
+        encryption::ElGamal, helper::Helper, proofs::re_encryption::ReEncryptionProof,
+        random::Random,
+    };
+    use num_bigint::BigUint;
+    use sp_keyring::AccountKeyring;
+    use std::{collections::HashMap, env, fs};
+
+    fn setup_entries(nr_of_entries: u32) -> (crypto::types::PublicKey, Vec<BatchEntry>) {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let entries = (0..nr_of_entries)
+            .map(|vote| {
+                let r = Random::get_random_less_than(q);
+                let x_v = Random::get_random_less_than(q);
+                BatchEntry {
+                    cipher: ElGamal::encrypt_encode(&BigUint::from(vote), &r, &pk),
+                    verifier_pk: params.g.modpow(&x_v, &params.p),
+                    voter: AccountKeyring::Alice.to_account_id(),
+                    token: "token".to_string(),
+                }
+            })
+            .collect();
+        (pk, entries)
+    }
+
+    #[test]
+    fn test_randomize_chunk() {
+        let (pk, entries) = setup_entries(5);
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let chunk = entries.clone().into_iter().enumerate().collect();
+
+        let lines = randomize_chunk(&vote_id, chunk, &pk, &signer).unwrap();
+        let results = String::from_utf8(lines)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str::<BatchResponseEntry>(line).unwrap())
+            .collect::<Vec<BatchResponseEntry>>();
+        assert_eq!(results.len(), entries.len());
+
+        // each result has a valid proof for the entry with the same index
+        for (index, result) in results.into_iter().enumerate() {
+            assert_eq!(result.index, index);
+            let entry = &entries[index];
+            assert!(ReEncryptionProof::verify_designated(
+                &pk,
+                &entry.verifier_pk,
+                &result.response.proof,
+                &entry.cipher,
+                &result.response.cipher,
+            ));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_post_randomize_batch_exceeds_default_json_limit() {
+        let keys = PublicKeyCache::new("ws://127.0.0.1:1");
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let path = env::temp_dir().join("randomizer-test-batch-json-limit.log");
+        let audit = AuditLog::open(path.to_str().unwrap()).unwrap();
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(VoterAuth::new(HashMap::new()))
+            .data(RateLimits::new(10, 10, 10))
+            .data(audit)
+            .configure(batch_service);
+        let mut test_app = test::init_service(app).await;
+
+        // the body exceeds the default limit of 32kb but is parsed, the tokens are rejected
+        let (_, entries) = setup_entries(1);
+        let request_body = BatchRequestBody {
+            vote_id: "20201212".as_bytes().to_vec(),
+            entries: vec![entries[0].clone(); 1_000],
+        };
+        assert!(serde_json::to_vec(&request_body).unwrap().len() > 32 * 1024);
+        let req = test::TestRequest::post()
+            .uri("/randomize/batch")
+            .set_json(&request_body)
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_post_randomize_batch_too_large() {
+        let keys = PublicKeyCache::new("ws://127.0.0.1:1");
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let path = env::temp_dir().join("randomizer-test-batch-too-large.log");
+        let audit = AuditLog::open(path.to_str().unwrap()).unwrap();
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(VoterAuth::new(HashMap::new()))
+            .data(RateLimits::new(10, 10, 10))
+            .data(audit)
+            .configure(batch_service);
+        let mut test_app = test::init_service(app).await;
+
+        let (_, entries) = setup_entries(1);
+        let request_body = BatchRequestBody {
+            vote_id: "20201212".as_bytes().to_vec(),
+            entries: vec![entries[0].clone(); super::MAX_BATCH_SIZE + 1],
+        };
+        let req = test::TestRequest::post()
+            .uri("/randomize/batch")
+            .set_json(&request_body)
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        fs::remove_file(path).unwrap();
+    }
+}
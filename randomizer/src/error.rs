@@ -0,0 +1,106 @@
+use actix_web::{http::StatusCode, ResponseError};
+use pallet_mixnet::types::VotePhase;
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum RandomizerError {
+    /// the node could not be reached or the storage query failed
+    Chain(substrate_subxt::Error),
+
+    /// the requested vote does not exist on chain
+    VoteDoesNotExist,
+
+    /// the vote exists but no public key has been created yet
+    PublicKeyDoesNotExist,
+
+    /// ballots can only be randomized while the vote is in phase: Voting
+    WrongVotePhase(VotePhase),
+
+    /// the verifier's public key is not an element of the group
+    InvalidVerifierKey,
+
+    /// the batch contains more ciphers than allowed
+    BatchTooLarge(usize),
+
+    /// the verifier's public key of the batch entry (index) is not an element of the group
+    InvalidBatchEntry(usize),
+
+    /// the token is missing, expired or was not issued for the vote and voter
+    Unauthorized,
+
+    /// the voter is not on the eligible voter list of the vote
+    NotEligible,
+
+    /// the challenge doesn't exist, expired or the signature doesn't verify
+    InvalidChallengeResponse,
+
+    /// the per-voter or per-IP rate limit is exceeded
+    RateLimited,
+
+    /// the request could not be written to the audit log
+    AuditLog(io::Error),
+}
+
+impl fmt::Display for RandomizerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RandomizerError::Chain(err) => write!(f, "failed to query the chain: {}", err),
+            RandomizerError::VoteDoesNotExist => write!(f, "vote does not exist!"),
+            RandomizerError::PublicKeyDoesNotExist => {
+                write!(f, "public key for vote does not exist!")
+            }
+            RandomizerError::WrongVotePhase(phase) => {
+                write!(f, "vote is not in phase Voting but: {:?}", phase)
+            }
+            RandomizerError::InvalidVerifierKey => {
+                write!(f, "verifier public key is not a group element!")
+            }
+            RandomizerError::BatchTooLarge(size) => {
+                write!(f, "batch of size: {} exceeds the maximum size!", size)
+            }
+            RandomizerError::InvalidBatchEntry(index) => write!(
+                f,
+                "verifier public key of entry: {} is not a group element!",
+                index
+            ),
+            RandomizerError::Unauthorized => write!(f, "invalid or missing token!"),
+            RandomizerError::NotEligible => write!(f, "voter is not eligible for the vote!"),
+            RandomizerError::InvalidChallengeResponse => {
+                write!(f, "challenge response is invalid!")
+            }
+            RandomizerError::RateLimited => write!(f, "rate limit exceeded!"),
+            RandomizerError::AuditLog(err) => write!(f, "failed to write audit log: {}", err),
+        }
+    }
+}
+
+impl From<substrate_subxt::Error> for RandomizerError {
+    fn from(err: substrate_subxt::Error) -> Self {
+        RandomizerError::Chain(err)
+    }
+}
+
+impl From<io::Error> for RandomizerError {
+    fn from(err: io::Error) -> Self {
+        RandomizerError::AuditLog(err)
+    }
+}
+
+impl ResponseError for RandomizerError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            RandomizerError::Chain(_) => StatusCode::BAD_GATEWAY,
+            RandomizerError::VoteDoesNotExist => StatusCode::NOT_FOUND,
+            RandomizerError::PublicKeyDoesNotExist => StatusCode::NOT_FOUND,
+            RandomizerError::WrongVotePhase(_) => StatusCode::CONFLICT,
+            RandomizerError::InvalidVerifierKey => StatusCode::BAD_REQUEST,
+            RandomizerError::BatchTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            RandomizerError::InvalidBatchEntry(_) => StatusCode::BAD_REQUEST,
+            RandomizerError::Unauthorized => StatusCode::UNAUTHORIZED,
+            RandomizerError::NotEligible => StatusCode::FORBIDDEN,
+            RandomizerError::InvalidChallengeResponse => StatusCode::UNAUTHORIZED,
+            RandomizerError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            RandomizerError::AuditLog(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::error::RandomizerError;
+use crypto::types::PublicKey as ElGamalPK;
+use futures::future::{FutureExt, LocalBoxFuture};
+use pallet_mixnet::types::{VoteId, VotePhase};
+use provotum_sdk::rpc::{get_public_key, get_vote};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, RwLock},
+};
+use substrate_subxt::{Client, ClientBuilder, NodeTemplateRuntime};
+
+/// The source of the vote phase and the public key of a vote.
+pub trait VoteSource: Send + Sync {
+    /// Returns the current phase of the vote.
+    fn get_phase<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<VotePhase, RandomizerError>>;
+
+    /// Returns the public key of the vote.
+    fn get_public_key<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<ElGamalPK, RandomizerError>>;
+}
+
+/// Reads the vote phase and the public key from the node's storage.
+/// All requests share a single connection to the node.
+pub struct NodeSource {
+    url: String,
+    client: Mutex<Option<Client<NodeTemplateRuntime>>>,
+}
+
+impl NodeSource {
+    pub fn new(url: &str) -> Self {
+        NodeSource {
+            url: url.to_string(),
+            client: Mutex::new(None),
+        }
+    }
+
+    /// Returns the shared client, connects to the node on first use.
+    async fn client(&self) -> Result<Client<NodeTemplateRuntime>, RandomizerError> {
+        if let Some(client) = self
+            .client
+            .lock()
+            .expect("client lock is poisoned!")
+            .as_ref()
+        {
+            return Ok(client.clone());
+        }
+        let client = ClientBuilder::<NodeTemplateRuntime>::new()
+            .set_url(&self.url)
+            .build()
+            .await?;
+        *self.client.lock().expect("client lock is poisoned!") = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Drops the shared client if the query failed, the next request reconnects to the node.
+    fn reset_on_error<T>(&self, result: Result<T, RandomizerError>) -> Result<T, RandomizerError> {
+        if let Err(RandomizerError::Chain(_)) = result {
+            // the connection might be broken, reconnect on the next request
+            *self.client.lock().expect("client lock is poisoned!") = None;
+        }
+        result
+    }
+}
+
+impl VoteSource for NodeSource {
+    fn get_phase<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<VotePhase, RandomizerError>> {
+        async move {
+            let client = self.client().await?;
+            let result = get_vote(&client, vote_id.clone())
+                .await
+                .map_err(RandomizerError::from);
+            let vote = self
+                .reset_on_error(result)?
+                .ok_or(RandomizerError::VoteDoesNotExist)?;
+            Ok(vote.phase)
+        }
+        .boxed_local()
+    }
+
+    fn get_public_key<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<ElGamalPK, RandomizerError>> {
+        async move {
+            let client = self.client().await?;
+            let result = get_public_key(&client, vote_id.clone())
+                .await
+                .map_err(RandomizerError::from);
+            let pk = self
+                .reset_on_error(result)?
+                .ok_or(RandomizerError::PublicKeyDoesNotExist)?;
+            Ok(pk.into())
+        }
+        .boxed_local()
+    }
+}
+
+/// Resolves the public key of a vote from the vote source (by default: the node).
+/// Once fetched, the key is cached per vote since it doesn't change anymore.
+pub struct PublicKeyCache {
+    source: Box<dyn VoteSource>,
+    keys: RwLock<HashMap<VoteId, ElGamalPK>>,
+}
+
+impl PublicKeyCache {
+    pub fn new(url: &str) -> Self {
+        PublicKeyCache::with_source(NodeSource::new(url))
+    }
+
+    pub fn with_source<S: VoteSource + 'static>(source: S) -> Self {
+        PublicKeyCache {
+            source: Box::new(source),
+            keys: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_cached(&self, vote_id: &VoteId) -> Option<ElGamalPK> {
+        let keys = self
+            .keys
+            .read()
+            .expect("public key cache lock is poisoned!");
+        keys.get(vote_id).cloned()
+    }
+
+    pub fn insert(&self, vote_id: VoteId, pk: ElGamalPK) {
+        let mut keys = self
+            .keys
+            .write()
+            .expect("public key cache lock is poisoned!");
+        keys.insert(vote_id, pk);
+    }
+
+    /// Returns the public key of the vote, if the vote is in phase: Voting.
+    /// The vote phase is always checked at the source, only the key is cached.
+    pub async fn get_public_key(&self, vote_id: &VoteId) -> Result<ElGamalPK, RandomizerError> {
+        // refuse to randomize ballots of votes which are not open
+        let phase = self.source.get_phase(vote_id).await?;
+        if phase != VotePhase::Voting {
+            return Err(RandomizerError::WrongVotePhase(phase));
+        }
+
+        if let Some(pk) = self.get_cached(vote_id) {
+            return Ok(pk);
+        }
+
+        // fetch the public key from the source and cache it
+        let pk = self.source.get_public_key(vote_id).await?;
+        self.insert(vote_id.clone(), pk.clone());
+        Ok(pk)
+    }
+}
+
+/// A fixed set of votes, used to test the endpoints without a node.
+#[cfg(test)]
+pub struct StaticSource {
+    pub votes: HashMap<VoteId, (VotePhase, ElGamalPK)>,
+}
+
+#[cfg(test)]
+impl VoteSource for StaticSource {
+    fn get_phase<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<VotePhase, RandomizerError>> {
+        let result = self
+            .votes
+            .get(vote_id)
+            .map(|(phase, _)| phase.clone())
+            .ok_or(RandomizerError::VoteDoesNotExist);
+        futures::future::ready(result).boxed_local()
+    }
+
+    fn get_public_key<'a>(
+        &'a self,
+        vote_id: &'a VoteId,
+    ) -> LocalBoxFuture<'a, Result<ElGamalPK, RandomizerError>> {
+        let result = self
+            .votes
+            .get(vote_id)
+            .map(|(_, pk)| pk.clone())
+            .ok_or(RandomizerError::PublicKeyDoesNotExist);
+        futures::future::ready(result).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PublicKeyCache, StaticSource};
+    use crate::error::RandomizerError;
+    use crypto::helper::Helper;
+    use pallet_mixnet::types::VotePhase;
+    use std::collections::HashMap;
+
+    #[test]
+    fn it_should_cache_public_key_per_vote() {
+        let cache = PublicKeyCache::new("ws://127.0.0.1:9944");
+        let (_, _, pk) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let other_vote_id = "20201213".as_bytes().to_vec();
+
+        assert_eq!(cache.get_cached(&vote_id), None);
+        cache.insert(vote_id.clone(), pk.clone());
+        assert_eq!(cache.get_cached(&vote_id), Some(pk));
+        assert_eq!(cache.get_cached(&other_vote_id), None);
+    }
+
+    #[actix_rt::test]
+    async fn it_should_check_the_vote_phase_at_the_source() {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let closed_vote_id = "20201213".as_bytes().to_vec();
+        let mut votes = HashMap::new();
+        votes.insert(vote_id.clone(), (VotePhase::Voting, pk.clone()));
+        votes.insert(closed_vote_id.clone(), (VotePhase::Tallying, pk.clone()));
+        let cache = PublicKeyCache::with_source(StaticSource { votes });
+
+        assert_eq!(cache.get_public_key(&vote_id).await.unwrap(), pk);
+        assert_eq!(cache.get_cached(&vote_id), Some(pk));
+        assert!(matches!(
+            cache.get_public_key(&closed_vote_id).await,
+            Err(RandomizerError::WrongVotePhase(VotePhase::Tallying))
+        ));
+        assert!(matches!(
+            cache.get_public_key(&"unknown".as_bytes().to_vec()).await,
+            Err(RandomizerError::VoteDoesNotExist)
+        ));
+    }
+}
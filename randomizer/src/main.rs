@@ -1,21 +1,89 @@
+mod audit;
+mod auth;
+mod batch;
+mod error;
 mod health;
 mod index;
+mod keys;
 mod randomizer;
+mod rate_limit;
+mod signer;
 
-// use actix_web::{App, HttpServer};
-// use health::get_health;
-// use index::get_index;
-// use randomizer::randomize_ballot;
-
-// #[actix_web::main]
-// async fn main() -> std::io::Result<()> {
-//     HttpServer::new(|| {
-//         App::new()
-//             .service(get_index)
-//             .service(get_health)
-//             .service(randomize_ballot)
-//     })
-//     .bind(("0.0.0.0", 8081))?
-//     .run()
-//     .await
-// }
+use actix_web::{web, App, HttpServer};
+use audit::AuditLog;
+use auth::{get_challenge, get_token, VoterAuth};
+use batch::batch_service;
+use health::get_health;
+use index::get_index;
+use keys::PublicKeyCache;
+use randomizer::randomize_ballot;
+use rate_limit::RateLimits;
+use signer::RandomizerSigner;
+use std::{collections::HashMap, env, process};
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
+    // the node used to resolve the public key of a vote
+    let url = env::var("NODE_URL").unwrap_or_else(|_| "ws://127.0.0.1:9944".to_string());
+    let keys = web::Data::new(PublicKeyCache::new(&url));
+
+    // the key used to sign re-encrypted ciphers, needs to be registered on chain
+    // there is no default, a well-known seed would allow anyone to forge signatures
+    let seed = env::var("RANDOMIZER_SEED").unwrap_or_else(|_| {
+        eprintln!("RANDOMIZER_SEED is not set!");
+        process::exit(1);
+    });
+    let signer = RandomizerSigner::from_seed(&seed).unwrap_or_else(|_| {
+        eprintln!("invalid RANDOMIZER_SEED!");
+        process::exit(1);
+    });
+    println!("randomizer key: {}", signer.public());
+    let signer = web::Data::new(signer);
+
+    // the eligible voters per vote, voters authenticate with a signed challenge
+    let path = env::var("ELIGIBLE_VOTERS").unwrap_or_else(|_| "eligible_voters.json".to_string());
+    let auth = VoterAuth::from_file(&path).unwrap_or_else(|err| {
+        println!(
+            "failed to load eligible voters: {}, no voter is eligible!",
+            err
+        );
+        VoterAuth::new(HashMap::new())
+    });
+    let auth = web::Data::new(auth);
+
+    // per-voter: # of ciphers per minute, per-IP: # of requests per minute,
+    // per-voter: # of authentication requests per minute
+    let per_voter = env_u32("RATE_LIMIT_VOTER", 10);
+    let per_ip = env_u32("RATE_LIMIT_IP", 120);
+    let per_auth = env_u32("RATE_LIMIT_AUTH", 10);
+    let limits = web::Data::new(RateLimits::new(per_voter, per_ip, per_auth));
+
+    // the append-only log of all randomization requests
+    let path = env::var("AUDIT_LOG").unwrap_or_else(|_| "audit.log".to_string());
+    let audit = web::Data::new(AuditLog::open(&path)?);
+
+    HttpServer::new(move || {
+        App::new()
+            .app_data(keys.clone())
+            .app_data(signer.clone())
+            .app_data(auth.clone())
+            .app_data(limits.clone())
+            .app_data(audit.clone())
+            .service(get_index)
+            .service(get_health)
+            .service(get_challenge)
+            .service(get_token)
+            .service(randomize_ballot)
+            .configure(batch_service)
+    })
+    .bind(("0.0.0.0", 8080))?
+    .run()
+    .await
+}
+
+fn env_u32(name: &str, default: u32) -> u32 {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
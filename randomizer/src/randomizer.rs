@@ -1,4 +1,12 @@
-use actix_web::{post, web, Responder};
+use crate::{
+    audit::{AuditEntry, AuditLog},
+    auth::VoterAuth,
+    error::RandomizerError,
+    keys::PublicKeyCache,
+    rate_limit::RateLimits,
+    signer::{AccountId, RandomizerSigner},
+};
+use actix_web::{post, web, HttpRequest};
 use crypto::{
     encryption::ElGamal,
     proofs::re_encryption::ReEncryptionProof,
@@ -7,58 +15,159 @@ use crypto::{
 };
 use num_bigint::BigUint;
 use num_traits::One;
+use pallet_mixnet::types::{RandomizerSignature, VoteId};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct RequestBody {
-    pub pk: PublicKey,
+    pub vote_id: VoteId,
     pub cipher: Cipher,
+    // the voter's public key: g^x_v mod p, the proof is designated to the voter
+    pub verifier_pk: BigUint,
+    // the account which submits the ballot, part of the randomizer's signature
+    pub voter: AccountId,
+    // the token issued to the voter for the vote (see: /auth/token)
+    pub token: String,
 }
 
 #[derive(Deserialize, Serialize, Debug, Eq, PartialEq, Clone)]
 pub struct ResponseBody {
     pub proof: ReEncryptionProof,
     pub cipher: Cipher,
+    pub signature: RandomizerSignature,
 }
 
 #[post("/randomize")]
-pub async fn randomize_ballot(body: web::Json<RequestBody>) -> impl Responder {
+pub async fn randomize_ballot(
+    req: HttpRequest,
+    body: web::Json<RequestBody>,
+    keys: web::Data<PublicKeyCache>,
+    signer: web::Data<RandomizerSigner>,
+    auth: web::Data<VoterAuth>,
+    limits: web::Data<RateLimits>,
+    audit: web::Data<AuditLog>,
+) -> Result<web::Json<ResponseBody>, RandomizerError> {
+    let result = randomize(&req, &body, &keys, &signer, &auth, &limits).await;
+
+    // record every request in the audit log, also the rejected ones
+    let voters = vec![body.voter.clone()];
+    let entry = AuditEntry::new("/randomize", &req, &body.vote_id, voters, 1, &result);
+    audit.record(&entry)?;
+    result.map(web::Json)
+}
+
+async fn randomize(
+    req: &HttpRequest,
+    body: &RequestBody,
+    keys: &PublicKeyCache,
+    signer: &RandomizerSigner,
+    auth: &VoterAuth,
+    limits: &RateLimits,
+) -> Result<ResponseBody, RandomizerError> {
+    // authenticate the voter + enforce the rate limits
+    limits.check_ip(req)?;
+    auth.authenticate(&body.token, &body.vote_id, &body.voter)?;
+    limits.check_voter(&body.voter, 1)?;
+
+    // resolve the vote's public key from chain, never trust the client
+    let pk = keys.get_public_key(&body.vote_id).await?;
+
+    // the verifier's public key must be an element of the group
+    if !is_valid_verifier_pk(&body.verifier_pk, &pk) {
+        return Err(RandomizerError::InvalidVerifierKey);
+    }
+
+    // return the re-encrypted cipher
+    Ok(randomize_cipher(
+        &body.vote_id,
+        &body.cipher,
+        &body.verifier_pk,
+        &body.voter,
+        &pk,
+        signer,
+    ))
+}
+
+/// Re-encrypts the cipher and signs the re-encrypted cipher for the vote and the voter.
+pub fn randomize_cipher(
+    vote_id: &VoteId,
+    cipher: &Cipher,
+    verifier_pk: &BigUint,
+    voter: &AccountId,
+    pk: &PublicKey,
+    signer: &RandomizerSigner,
+) -> ResponseBody {
+    // re-encrypt the cipher
+    let (cipher, proof) = re_encrypt_ballot(cipher, pk, verifier_pk);
+
+    // sign the re-encrypted cipher for the vote and the voter
+    let signature = signer.sign(vote_id, voter, &cipher.clone().into());
+
+    ResponseBody {
+        proof,
+        cipher,
+        signature,
+    }
+}
+
+/// Checks that the verifier's public key is a non-trivial element of the subgroup of order q.
+pub fn is_valid_verifier_pk(verifier_pk: &BigUint, pk: &PublicKey) -> bool {
+    let p = &pk.params.p;
+    let q = &pk.params.q();
+    let one = BigUint::one();
+    verifier_pk > &one && verifier_pk < p && verifier_pk.modpow(q, p) == one
+}
+
+/// Re-encrypts the cipher and generates a proof that the re-encryption is valid.
+/// The proof is designated to the voter, i.e. it only convinces the holder of the verifier's secret key.
+pub fn re_encrypt_ballot(
+    cipher: &Cipher,
+    pk: &PublicKey,
+    verifier_pk: &BigUint,
+) -> (Cipher, ReEncryptionProof) {
     // common values
-    let cipher = body.cipher.clone();
-    let pk = body.pk.clone();
     let q = &pk.params.q();
 
     // 1. re-encrypt the cipher
     let r1 = Random::get_random_less_than(q);
-    let re_encrypted_cipher = ElGamal::re_encrypt(&cipher, &r1, &pk);
+    let re_encrypted_cipher = ElGamal::re_encrypt(cipher, &r1, pk);
 
     // 2. generate a proof to show that the re-encryption is valid/not something else
     // 2.1 generate c_one -> the encryption of 1 using the re-encryption random r1
     let one = BigUint::one();
-    let c_one = ElGamal::encrypt(&one, &r1, &pk);
+    let c_one = ElGamal::encrypt(&one, &r1, pk);
 
     // 2.2 generate the proof
     let r2 = Random::get_random_less_than(q);
     let h2 = Random::get_random_less_than(q);
     let s2 = Random::get_random_less_than(q);
-    let proof = ReEncryptionProof::generate(&r1, &r2, &h2, &s2, &c_one, &pk);
+    let proof = ReEncryptionProof::generate_designated(&r1, &r2, &h2, &s2, &c_one, pk, verifier_pk);
 
-    // return the re-encrypted cipher
-    web::Json(ResponseBody {
-        cipher: re_encrypted_cipher,
-        proof,
-    })
+    (re_encrypted_cipher, proof)
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{randomize_ballot, RequestBody, ResponseBody};
-    use actix_web::{test, App};
+    use super::{
+        is_valid_verifier_pk, randomize_ballot, re_encrypt_ballot, RequestBody, ResponseBody,
+    };
+    use crate::{
+        audit::{AuditEntry, AuditLog},
+        auth::VoterAuth,
+        error::RandomizerError,
+        keys::{PublicKeyCache, StaticSource},
+        rate_limit::RateLimits,
+        signer::RandomizerSigner,
+    };
+    use actix_web::{http::StatusCode, test, App};
     use crypto::{
         encryption::ElGamal, helper::Helper, proofs::re_encryption::ReEncryptionProof,
         random::Random,
     };
     use num_bigint::BigUint;
+    use pallet_mixnet::types::VotePhase;
+    use sp_keyring::AccountKeyring;
+    use std::{collections::HashMap, env, fs, path::PathBuf};
 
     #[actix_rt::test]
     async fn test_get_randomize_ballot() {
@@ -69,19 +178,60 @@ mod tests {
         assert!(resp.status().is_client_error());
     }
 
+    fn setup_request_body(token: &str) -> RequestBody {
+        let (_, _, pk) = Helper::setup_sm_system();
+        let q = &pk.params.q();
+        let r = Random::get_random_less_than(q);
+        let cipher = ElGamal::encrypt(&BigUint::from(13u32), &r, &pk);
+        let x_v = Random::get_random_less_than(q);
+        RequestBody {
+            vote_id: "20201212".as_bytes().to_vec(),
+            cipher,
+            verifier_pk: pk.params.g.modpow(&x_v, &pk.params.p),
+            voter: AccountKeyring::Alice.to_account_id(),
+            token: token.to_string(),
+        }
+    }
+
+    fn setup_audit_log(name: &str) -> (AuditLog, PathBuf) {
+        let path = env::temp_dir().join(name);
+        let _ = fs::remove_file(&path);
+        (AuditLog::open(path.to_str().unwrap()).unwrap(), path)
+    }
+
     #[actix_rt::test]
     async fn test_post_randomize_ballot() {
-        let app = App::new().service(randomize_ballot);
+        let (_, sk, pk) = Helper::setup_sm_system();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let mut votes = HashMap::new();
+        votes.insert(vote_id.clone(), (VotePhase::Voting, pk.clone()));
+        let keys = PublicKeyCache::with_source(StaticSource { votes });
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let auth = VoterAuth::new(HashMap::new());
+        let token = auth.issue_token(&vote_id, &AccountKeyring::Alice.to_account_id());
+        let (audit, path) = setup_audit_log("randomizer-test-randomize.log");
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(auth)
+            .data(RateLimits::new(10, 10, 10))
+            .data(audit)
+            .service(randomize_ballot);
         let mut test_app = test::init_service(app).await;
 
-        let (_, sk, pk) = Helper::setup_sm_system();
+        // the voter encrypts the vote and keeps the secret key of the verifier's public key
         let q = &pk.params.q();
         let vote = &BigUint::from(13u32);
         let r = Random::get_random_less_than(q);
         let cipher = ElGamal::encrypt(vote, &r, &pk);
+        let x_v = Random::get_random_less_than(q);
+        let verifier_pk = pk.params.g.modpow(&x_v, &pk.params.p);
         let request_body = RequestBody {
-            pk: pk.clone(),
+            vote_id: vote_id.clone(),
             cipher: cipher.clone(),
+            verifier_pk: verifier_pk.clone(),
+            voter: AccountKeyring::Alice.to_account_id(),
+            token,
         };
 
         // send post request to re-encrypt ballot
@@ -97,13 +247,175 @@ mod tests {
         // ensure that the encrypted vote and re-encrypted vote are not the same
         assert_ne!(&re_encrypted_cipher, &cipher);
 
-        // verify the re-encryption proof
-        let proof_is_valid =
-            ReEncryptionProof::verify(&pk, &resp.proof, &cipher, &re_encrypted_cipher);
+        // verify the designated re-encryption proof
+        let proof_is_valid = ReEncryptionProof::verify_designated(
+            &pk,
+            &verifier_pk,
+            &resp.proof,
+            &cipher,
+            &re_encrypted_cipher,
+        );
+        assert!(proof_is_valid);
+
+        // ensure that the decrypted re-encrypted vote is still 13
+        let decrypted = ElGamal::decrypt(&re_encrypted_cipher, &sk);
+        assert_eq!(&decrypted, vote);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_post_randomize_ballot_node_unavailable() {
+        // no node is running on this port
+        let keys = PublicKeyCache::new("ws://127.0.0.1:1");
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let auth = VoterAuth::new(HashMap::new());
+        let token = auth.issue_token(
+            &"20201212".as_bytes().to_vec(),
+            &AccountKeyring::Alice.to_account_id(),
+        );
+        let (audit, path) = setup_audit_log("randomizer-test-node-unavailable.log");
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(auth)
+            .data(RateLimits::new(10, 10, 10))
+            .data(audit)
+            .service(randomize_ballot);
+        let mut test_app = test::init_service(app).await;
+
+        // send post request to re-encrypt ballot
+        let req = test::TestRequest::post()
+            .uri("/randomize")
+            .set_json(&setup_request_body(&token))
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_post_randomize_ballot_unauthorized() {
+        let keys = PublicKeyCache::new("ws://127.0.0.1:1");
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let (audit, path) = setup_audit_log("randomizer-test-unauthorized.log");
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(VoterAuth::new(HashMap::new()))
+            .data(RateLimits::new(10, 10, 10))
+            .data(audit)
+            .service(randomize_ballot);
+        let mut test_app = test::init_service(app).await;
+
+        let req = test::TestRequest::post()
+            .uri("/randomize")
+            .set_json(&setup_request_body("not-a-token"))
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+        // the rejected request is recorded in the audit log
+        let content = fs::read_to_string(&path).unwrap();
+        let entries = content
+            .lines()
+            .map(|line| serde_json::from_str::<AuditEntry>(line).unwrap())
+            .collect::<Vec<AuditEntry>>();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].endpoint, "/randomize");
+        assert_eq!(
+            entries[0].voters,
+            vec![AccountKeyring::Alice.to_account_id()]
+        );
+        assert_eq!(
+            entries[0].outcome,
+            RandomizerError::Unauthorized.to_string()
+        );
+        fs::remove_file(path).unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_post_randomize_ballot_rate_limited_per_voter() {
+        let keys = PublicKeyCache::new("ws://127.0.0.1:1");
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let auth = VoterAuth::new(HashMap::new());
+        let token = auth.issue_token(
+            &"20201212".as_bytes().to_vec(),
+            &AccountKeyring::Alice.to_account_id(),
+        );
+        let (audit, path) = setup_audit_log("randomizer-test-rate-limited.log");
+        let app = App::new()
+            .data(keys)
+            .data(signer)
+            .data(auth)
+            .data(RateLimits::new(1, 10, 10))
+            .data(audit)
+            .service(randomize_ballot);
+        let mut test_app = test::init_service(app).await;
+
+        // the first request passes the rate limit (but there is no node)
+        let req = test::TestRequest::post()
+            .uri("/randomize")
+            .set_json(&setup_request_body(&token))
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+
+        // the second request of the same voter is rejected
+        let req = test::TestRequest::post()
+            .uri("/randomize")
+            .set_json(&setup_request_body(&token))
+            .to_request();
+        let resp = test::call_service(&mut test_app, req).await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_re_encrypt_ballot() {
+        let (_, sk, pk) = Helper::setup_sm_system();
+        let q = &pk.params.q();
+        let vote = &BigUint::from(13u32);
+        let r = Random::get_random_less_than(q);
+        let cipher = ElGamal::encrypt(vote, &r, &pk);
+
+        let x_v = Random::get_random_less_than(q);
+        let verifier_pk = pk.params.g.modpow(&x_v, &pk.params.p);
+
+        let (re_encrypted_cipher, proof) = re_encrypt_ballot(&cipher, &pk, &verifier_pk);
+
+        // ensure that the encrypted vote and re-encrypted vote are not the same
+        assert_ne!(&re_encrypted_cipher, &cipher);
+
+        // verify the designated re-encryption proof
+        let proof_is_valid = ReEncryptionProof::verify_designated(
+            &pk,
+            &verifier_pk,
+            &proof,
+            &cipher,
+            &re_encrypted_cipher,
+        );
         assert!(proof_is_valid);
 
+        // the proof is not transferable to anyone else
+        let proof_is_valid = ReEncryptionProof::verify(&pk, &proof, &cipher, &re_encrypted_cipher);
+        assert!(!proof_is_valid);
+
         // ensure that the decrypted re-encrypted vote is still 13
         let decrypted = ElGamal::decrypt(&re_encrypted_cipher, &sk);
         assert_eq!(&decrypted, vote);
     }
+
+    #[test]
+    fn test_is_valid_verifier_pk() {
+        let (params, _, pk) = Helper::setup_sm_system();
+        let q = &params.q();
+        let x_v = Random::get_random_less_than(q);
+        let verifier_pk = params.g.modpow(&x_v, &params.p);
+        assert!(is_valid_verifier_pk(&verifier_pk, &pk));
+
+        // the identity and values outside of the group are rejected
+        assert!(!is_valid_verifier_pk(&BigUint::from(1u32), &pk));
+        assert!(!is_valid_verifier_pk(&BigUint::from(0u32), &pk));
+        assert!(!is_valid_verifier_pk(&params.p, &pk));
+    }
 }
@@ -0,0 +1,129 @@
+use crate::{error::RandomizerError, signer::AccountId};
+use actix_web::HttpRequest;
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// the window in which the requests are counted
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// A fixed window rate limiter: at most `limit` requests per key and window.
+pub struct RateLimiter<K> {
+    limit: u32,
+    window: Duration,
+    counters: Mutex<HashMap<K, (Instant, u32)>>,
+}
+
+impl<K: Eq + Hash> RateLimiter<K> {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        RateLimiter {
+            limit,
+            window,
+            counters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts `n` requests for the key, returns false if the limit is exceeded.
+    pub fn check(&self, key: K, n: u32) -> bool {
+        let now = Instant::now();
+        let mut counters = self
+            .counters
+            .lock()
+            .expect("rate limiter lock is poisoned!");
+
+        // drop counters of expired windows, such that the map doesn't grow unbounded
+        let window = self.window;
+        counters.retain(|_, (start, _)| now.duration_since(*start) < window);
+
+        let (_, count) = counters.entry(key).or_insert((now, 0));
+        if *count + n > self.limit {
+            return false;
+        }
+        *count += n;
+        true
+    }
+}
+
+/// The per-voter and per-IP rate limits of the randomizer.
+///
+/// The authentication routes are limited per voter instead of per IP,
+/// such that many voters can authenticate from the same IP (e.g. a bulk client).
+pub struct RateLimits {
+    per_voter: RateLimiter<AccountId>,
+    per_ip: RateLimiter<String>,
+    per_auth: RateLimiter<AccountId>,
+}
+
+impl RateLimits {
+    /// `per_voter`: # of ciphers per voter and minute, `per_ip`: # of requests per IP and minute,
+    /// `per_auth`: # of authentication requests per voter and minute
+    pub fn new(per_voter: u32, per_ip: u32, per_auth: u32) -> Self {
+        RateLimits {
+            per_voter: RateLimiter::new(per_voter, WINDOW),
+            per_ip: RateLimiter::new(per_ip, WINDOW),
+            per_auth: RateLimiter::new(per_auth, WINDOW),
+        }
+    }
+
+    pub fn check_ip(&self, req: &HttpRequest) -> Result<(), RandomizerError> {
+        match self.per_ip.check(peer_ip(req), 1) {
+            true => Ok(()),
+            false => Err(RandomizerError::RateLimited),
+        }
+    }
+
+    pub fn check_auth(&self, voter: &AccountId) -> Result<(), RandomizerError> {
+        match self.per_auth.check(voter.clone(), 1) {
+            true => Ok(()),
+            false => Err(RandomizerError::RateLimited),
+        }
+    }
+
+    pub fn check_voter(
+        &self,
+        voter: &AccountId,
+        nr_of_ciphers: u32,
+    ) -> Result<(), RandomizerError> {
+        match self.per_voter.check(voter.clone(), nr_of_ciphers) {
+            true => Ok(()),
+            false => Err(RandomizerError::RateLimited),
+        }
+    }
+}
+
+/// The IP of the peer, headers such as `X-Forwarded-For` are not trusted.
+pub fn peer_ip(req: &HttpRequest) -> String {
+    req.peer_addr()
+        .map(|addr| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RateLimiter;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn it_should_limit_requests_per_key() {
+        let limiter = RateLimiter::new(3, Duration::from_secs(60));
+        assert!(limiter.check("a", 2));
+        assert!(limiter.check("a", 1));
+        assert!(!limiter.check("a", 1));
+
+        // other keys are not affected
+        assert!(limiter.check("b", 3));
+        assert!(!limiter.check("b", 1));
+    }
+
+    #[test]
+    fn it_should_reset_the_limit_after_the_window() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(10));
+        assert!(limiter.check("a", 1));
+        assert!(!limiter.check("a", 1));
+        thread::sleep(Duration::from_millis(20));
+        assert!(limiter.check("a", 1));
+    }
+}
@@ -0,0 +1,79 @@
+use pallet_mixnet::types::{
+    randomizer_message, Cipher as SubstrateCipher, RandomizerKey, RandomizerSignature, VoteId,
+};
+use substrate_subxt::{
+    sp_core::{crypto::SecretStringError, sr25519::Pair, Pair as KeyPairGenerator},
+    system::System,
+    NodeTemplateRuntime,
+};
+
+pub type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+/// Signs re-encrypted ciphers with the randomizer's sr25519 key.
+/// The public key needs to be registered on chain (`store_randomizer_key`),
+/// such that votes requiring a randomizer signature accept the ballots.
+pub struct RandomizerSigner {
+    pair: Pair,
+}
+
+impl RandomizerSigner {
+    pub fn from_seed(seed: &str) -> Result<Self, SecretStringError> {
+        let pair = Pair::from_string(seed, None)?;
+        Ok(RandomizerSigner { pair })
+    }
+
+    pub fn public(&self) -> RandomizerKey {
+        self.pair.public()
+    }
+
+    /// Signs the re-encrypted cipher together with the vote and the voter.
+    pub fn sign(
+        &self,
+        vote_id: &VoteId,
+        voter: &AccountId,
+        cipher: &SubstrateCipher,
+    ) -> RandomizerSignature {
+        let message = randomizer_message(vote_id, voter, cipher);
+        RandomizerSignature {
+            signer: self.public(),
+            signature: self.pair.sign(&message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RandomizerSigner;
+    use pallet_mixnet::types::{randomizer_message, Cipher};
+    use sp_keyring::AccountKeyring;
+    use substrate_subxt::sp_core::{sr25519::Pair, Pair as KeyPairGenerator};
+
+    #[test]
+    fn it_should_sign_cipher_for_vote_and_voter() {
+        let signer = RandomizerSigner::from_seed("//Randomizer").unwrap();
+        let vote_id = "20201212".as_bytes().to_vec();
+        let voter = AccountKeyring::Alice.to_account_id();
+        let cipher = Cipher {
+            a: vec![1u8],
+            b: vec![2u8],
+        };
+
+        let signature = signer.sign(&vote_id, &voter, &cipher);
+        assert_eq!(signature.signer, signer.public());
+
+        // the signature is bound to the voter
+        let message = randomizer_message(&vote_id, &voter, &cipher);
+        assert!(Pair::verify(
+            &signature.signature,
+            &message,
+            &signature.signer
+        ));
+        let other_voter = AccountKeyring::Bob.to_account_id();
+        let message = randomizer_message(&vote_id, &other_voter, &cipher);
+        assert!(!Pair::verify(
+            &signature.signature,
+            &message,
+            &signature.signer
+        ));
+    }
+}
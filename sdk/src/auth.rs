@@ -0,0 +1,18 @@
+use crate::types::VoteId;
+use codec::Encode;
+
+/// the context prefix of the message signed by a voter to authenticate at the randomizer
+pub const AUTH_SIGNING_CONTEXT: &[u8] = b"provotum-randomizer-auth";
+
+/// Creates the message a voter signs to prove control over its account at the randomizer.
+pub fn auth_message(vote_id: &VoteId, challenge: &[u8]) -> Vec<u8> {
+    (AUTH_SIGNING_CONTEXT, vote_id, challenge).encode()
+}
+
+/// the context prefix of the message signed by a voter to request a credential
+pub const CREDENTIAL_REQUEST_CONTEXT: &[u8] = b"provotum-credential-request";
+
+/// Creates the message a voter signs with its identity to request a blind signature.
+pub fn credential_request_message(vote_id: &VoteId, blinded: &[u8]) -> Vec<u8> {
+    (CREDENTIAL_REQUEST_CONTEXT, vote_id, blinded).encode()
+}
@@ -0,0 +1,307 @@
+//! The extrinsics of the mixnet pallet.
+
+use crate::events::register_type_sizes;
+use codec::Encode;
+use pallet_mixnet::types::{
+    Ballot, CredentialKey, DecryptedShare, DecryptedShareProof, NrOfShuffles,
+    PublicKey as SubstratePK, PublicKeyShare, PublicParameters, RandomizerKey, RandomizerSignature,
+    ShufflePayload, Title, Topic, TopicAnswer, TopicId, VoteId, VotePhase, VoterCredential,
+    VoterToken,
+};
+use substrate_subxt::{system::System, Call, EventsDecoder, NodeTemplateRuntime};
+
+/// Calls `set_vote_phase`, changes the phase of a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct SetVotePhase {
+    pub vote_id: VoteId,
+    pub vote_phase: VotePhase,
+}
+
+impl Call<NodeTemplateRuntime> for SetVotePhase {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "set_vote_phase";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_public_key`, stores the public key of a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct StorePublicKey {
+    pub vote_id: VoteId,
+    pub pk: SubstratePK,
+}
+
+impl Call<NodeTemplateRuntime> for StorePublicKey {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_public_key";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_public_key_share`, submits the public key share of a sealer including its proof.
+#[derive(Clone, Debug, Encode)]
+pub struct StorePublicKeyShare {
+    pub vote_id: VoteId,
+    pub pk_share: PublicKeyShare,
+}
+
+impl Call<NodeTemplateRuntime> for StorePublicKeyShare {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_public_key_share";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `combine_public_key_shares`, combines the public key shares of a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct CombinePublicKeyShares {
+    pub vote_id: VoteId,
+}
+
+impl Call<NodeTemplateRuntime> for CombinePublicKeyShares {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "combine_public_key_shares";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `create_vote`, creates a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct CreateVote {
+    pub vote_id: VoteId,
+    pub title: Title,
+    pub params: PublicParameters,
+    pub topics: Vec<Topic>,
+    pub batch_size: u64,
+}
+
+impl Call<NodeTemplateRuntime> for CreateVote {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "create_vote";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_question`, adds a question to a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct StoreQuestion {
+    pub vote_id: VoteId,
+    pub topic: Topic,
+    pub batch_size: u64,
+}
+
+impl Call<NodeTemplateRuntime> for StoreQuestion {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_question";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_answers`, stores the allowed answers of a question.
+#[derive(Clone, Debug, Encode)]
+pub struct StoreAnswers {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+    pub answers: Vec<TopicAnswer>,
+}
+
+impl Call<NodeTemplateRuntime> for StoreAnswers {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_answers";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_randomizer_key`, registers the key of a randomizer.
+#[derive(Clone, Debug, Encode)]
+pub struct StoreRandomizerKey {
+    pub key: RandomizerKey,
+}
+
+impl Call<NodeTemplateRuntime> for StoreRandomizerKey {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_randomizer_key";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `remove_randomizer_key`, removes the key of a randomizer.
+#[derive(Clone, Debug, Encode)]
+pub struct RemoveRandomizerKey {
+    pub key: RandomizerKey,
+}
+
+impl Call<NodeTemplateRuntime> for RemoveRandomizerKey {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "remove_randomizer_key";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `set_randomizer_signature_required`, sets whether the ballots of a vote require a randomizer signature.
+#[derive(Clone, Debug, Encode)]
+pub struct SetRandomizerSignatureRequired {
+    pub vote_id: VoteId,
+    pub required: bool,
+}
+
+impl Call<NodeTemplateRuntime> for SetRandomizerSignatureRequired {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "set_randomizer_signature_required";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `cast_ballot`, casts the ballot of a voter.
+#[derive(Clone, Debug, Encode)]
+pub struct CastBallot {
+    pub vote_id: VoteId,
+    pub ballot: Ballot,
+    pub signatures: Vec<RandomizerSignature>,
+}
+
+impl Call<NodeTemplateRuntime> for CastBallot {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "cast_ballot";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `cast_anonymous_ballot`, casts a ballot authorized by a voter token.
+#[derive(Clone, Debug, Encode)]
+pub struct CastAnonymousBallot {
+    pub vote_id: VoteId,
+    pub ballot: Ballot,
+    pub signatures: Vec<RandomizerSignature>,
+    pub token: VoterToken,
+}
+
+impl Call<NodeTemplateRuntime> for CastAnonymousBallot {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "cast_anonymous_ballot";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_credential_key`, stores the key blind-signing the voter tokens of a vote.
+#[derive(Clone, Debug, Encode)]
+pub struct StoreCredentialKey {
+    pub vote_id: VoteId,
+    pub key: CredentialKey,
+}
+
+impl Call<NodeTemplateRuntime> for StoreCredentialKey {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_credential_key";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `relay_ballot`, relays the ballot of a voter.
+#[derive(Clone, Debug, Encode)]
+pub struct RelayBallot {
+    pub vote_id: VoteId,
+    pub ballot: Ballot,
+    pub signatures: Vec<RandomizerSignature>,
+    pub credential: VoterCredential,
+}
+
+impl Call<NodeTemplateRuntime> for RelayBallot {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "relay_ballot";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `store_relayer`, registers a relayer.
+#[derive(Clone, Debug, Encode)]
+pub struct StoreRelayer {
+    pub relayer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Call<NodeTemplateRuntime> for StoreRelayer {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "store_relayer";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `remove_relayer`, removes a relayer.
+#[derive(Clone, Debug, Encode)]
+pub struct RemoveRelayer {
+    pub relayer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Call<NodeTemplateRuntime> for RemoveRelayer {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "remove_relayer";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `submit_shuffled_votes_and_proof`, submits a shuffle of the ciphers of a question including its proof.
+#[derive(Clone, Debug, Encode)]
+pub struct SubmitShuffledVotesAndProof {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+    pub payload: ShufflePayload,
+}
+
+impl Call<NodeTemplateRuntime> for SubmitShuffledVotesAndProof {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "submit_shuffled_votes_and_proof";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `submit_decrypted_shares`, submits the partial decryptions of a sealer including their proof.
+#[derive(Clone, Debug, Encode)]
+pub struct SubmitPartialDecryption {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+    pub shares: Vec<DecryptedShare>,
+    pub proof: DecryptedShareProof,
+    pub nr_of_shuffles: NrOfShuffles,
+}
+
+impl Call<NodeTemplateRuntime> for SubmitPartialDecryption {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "submit_decrypted_shares";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
+
+/// Calls `combine_decrypted_shares`, combines the partial decryptions of a question and tallies it.
+#[derive(Clone, Debug, Encode)]
+pub struct CombineDecryptedShares {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+    pub encoded: bool,
+    pub nr_of_shuffles: NrOfShuffles,
+}
+
+impl Call<NodeTemplateRuntime> for CombineDecryptedShares {
+    const MODULE: &'static str = "PalletMixnet";
+    const FUNCTION: &'static str = "combine_decrypted_shares";
+    fn events_decoder(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+        register_type_sizes(decoder);
+    }
+}
@@ -0,0 +1,140 @@
+//! The events of the mixnet pallet.
+
+pub mod decoder;
+
+use codec::{Decode, Encode};
+use frame_support::event::DecodeDifferent;
+use pallet_mixnet::types::{
+    Ballot, CredentialKey, PublicKey as SubstratePK, PublicKeyShare, PublicParameters,
+    RandomizerKey, Title, Topic, TopicId, TopicResult, VoteId, VotePhase,
+};
+use substrate_subxt::{system::System, EventsDecoder, NodeTemplateRuntime, RawEvent};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+
+/// the name of the mixnet pallet in the runtime
+pub const MODULE: &str = "PalletMixnet";
+
+/// An event of the mixnet pallet, the pallet's own event type.
+pub type MixnetEvent = pallet_mixnet::RawEvent<AccountId>;
+
+/// The index of the variant, the events are SCALE encoded in the order of the pallet's metadata.
+fn variant_index(variant: &str) -> Option<u8> {
+    MixnetEvent::metadata()
+        .iter()
+        .position(|event| matches!(event.name, DecodeDifferent::Encode(name) if name == variant))
+        .map(|index| index as u8)
+}
+
+/// Decodes the data of an event, the events of other modules are ignored.
+pub fn decode(
+    module: &str,
+    variant: &str,
+    data: &[u8],
+) -> Result<Option<MixnetEvent>, codec::Error> {
+    if module != MODULE {
+        return Ok(None);
+    }
+    let index = match variant_index(variant) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    // the data only contains the arguments, the variant is encoded as its index
+    let mut encoded = Vec::with_capacity(data.len() + 1);
+    encoded.push(index);
+    encoded.extend_from_slice(data);
+    let event = MixnetEvent::decode(&mut &encoded[..])?;
+    Ok(Some(event))
+}
+
+/// Decodes an event received from subxt.
+pub fn from_raw(raw: &RawEvent) -> Result<Option<MixnetEvent>, codec::Error> {
+    decode(&raw.module, &raw.variant, &raw.data)
+}
+
+/// The name of the variant, as emitted by the pallet.
+pub fn variant(event: &MixnetEvent) -> &'static str {
+    let index = event.using_encoded(|encoded| encoded[0]);
+    match MixnetEvent::metadata()[index as usize].name {
+        DecodeDifferent::Encode(name) => name,
+        // the metadata of the pallet is static, it is never decoded
+        DecodeDifferent::Decoded(_) => unreachable!(),
+    }
+}
+
+/// Registers the sizes of all types used by the events of the mixnet pallet,
+/// the subxt decoder cannot skip the events otherwise.
+pub fn register_type_sizes(decoder: &mut EventsDecoder<NodeTemplateRuntime>) {
+    decoder.register_type_size::<VoteId>("VoteId");
+    decoder.register_type_size::<TopicId>("TopicId");
+    decoder.register_type_size::<Topic>("Topic");
+    decoder.register_type_size::<Ballot>("Ballot");
+    decoder.register_type_size::<VotePhase>("VotePhase");
+    decoder.register_type_size::<TopicResult>("TopicResult");
+    decoder.register_type_size::<Title>("Title");
+    decoder.register_type_size::<SubstratePK>("SubstratePK");
+    decoder.register_type_size::<PublicKeyShare>("PublicKeyShare");
+    decoder.register_type_size::<PublicParameters>("PublicParameters");
+    decoder.register_type_size::<RandomizerKey>("RandomizerKey");
+    decoder.register_type_size::<CredentialKey>("CredentialKey");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_keyring::AccountKeyring;
+
+    #[test]
+    fn it_should_decode_mixnet_events() {
+        let bob = AccountKeyring::Bob.to_account_id();
+        let vote_id = b"vote".to_vec();
+
+        let data = (&vote_id, VotePhase::Voting).encode();
+        let event = decode(MODULE, "VotePhaseChanged", &data).unwrap();
+        assert_eq!(
+            event,
+            Some(MixnetEvent::VotePhaseChanged(
+                vote_id.clone(),
+                VotePhase::Voting
+            ))
+        );
+        assert_eq!(variant(&event.unwrap()), "VotePhaseChanged");
+
+        let raw = RawEvent {
+            module: MODULE.to_string(),
+            variant: "RelayerStored".to_string(),
+            data: (&bob, &bob).encode(),
+        };
+        assert_eq!(
+            from_raw(&raw).unwrap(),
+            Some(MixnetEvent::RelayerStored(bob.clone(), bob))
+        );
+    }
+
+    #[test]
+    fn it_should_decode_the_encoded_pallet_events() {
+        let bob = AccountKeyring::Bob.to_account_id();
+        let events = vec![
+            MixnetEvent::BallotSubmitted(bob.clone(), b"vote".to_vec(), Ballot::default()),
+            MixnetEvent::TopicAnswersStored(b"vote".to_vec(), b"topic".to_vec()),
+            MixnetEvent::RelayerRemoved(bob.clone(), bob),
+        ];
+        for event in events {
+            // the raw events contain the arguments without the variant's index
+            let data = &event.encode()[1..];
+            assert_eq!(decode(MODULE, variant(&event), data).unwrap(), Some(event));
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_other_events() {
+        let data = (b"vote".to_vec(), true).encode();
+        assert_eq!(decode("Balances", "Transfer", &data).unwrap(), None);
+        assert_eq!(decode(MODULE, "Unknown", &data).unwrap(), None);
+    }
+
+    #[test]
+    fn it_should_fail_on_invalid_data() {
+        assert!(decode(MODULE, "TopicTallied", &[1, 2]).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+use codec::{Compact, Decode};
+use frame_metadata::{
+    DecodeDifferent, EventMetadata, RuntimeMetadata, RuntimeMetadataPrefixed, META_RESERVED,
+};
+use frame_support::{traits::BalanceStatus, weights::DispatchInfo};
+use pallet_mixnet::types::{
+    Ballot, CredentialKey, PublicKey as SubstratePK, PublicKeyShare, PublicParameters,
+    RandomizerKey, Topic, TopicResult, VotePhase,
+};
+use std::{collections::HashMap, fmt};
+use substrate_subxt::sp_runtime::{DispatchError, DispatchResult};
+
+#[derive(Debug)]
+pub enum DecodeError {
+    /// the runtime metadata is not V12 or not fully decoded
+    Metadata(&'static str),
+    Codec(codec::Error),
+    UnknownModule(u8),
+    UnknownEvent(String, u8),
+    /// the size of an event argument type is unknown
+    UnknownType(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Metadata(reason) => write!(f, "invalid metadata: {}", reason),
+            DecodeError::Codec(err) => write!(f, "failed to decode events: {}", err),
+            DecodeError::UnknownModule(index) => write!(f, "unknown module: {}", index),
+            DecodeError::UnknownEvent(module, index) => {
+                write!(f, "unknown event: {} of module: {}", index, module)
+            }
+            DecodeError::UnknownType(name) => write!(f, "unknown event argument type: {}", name),
+        }
+    }
+}
+
+impl From<codec::Error> for DecodeError {
+    fn from(err: codec::Error) -> Self {
+        DecodeError::Codec(err)
+    }
+}
+
+/// The type of an event argument as declared in the metadata, e.g. `Vec<AccountId>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Arg {
+    Primitive(String),
+    Vec(Box<Arg>),
+    Option(Box<Arg>),
+    Tuple(Vec<Arg>),
+}
+
+impl Arg {
+    fn parse(arg: &str) -> Arg {
+        let arg = arg.trim();
+        if arg.starts_with("Vec<") && arg.ends_with('>') {
+            Arg::Vec(Box::new(Arg::parse(&arg[4..arg.len() - 1])))
+        } else if arg.starts_with("Option<") && arg.ends_with('>') {
+            Arg::Option(Box::new(Arg::parse(&arg[7..arg.len() - 1])))
+        } else if arg.starts_with('(') && arg.ends_with(')') {
+            Arg::Tuple(arg[1..arg.len() - 1].split(',').map(Arg::parse).collect())
+        } else {
+            Arg::Primitive(arg.to_string())
+        }
+    }
+}
+
+struct ModuleEvents {
+    name: String,
+    // (event name, arguments) indexed by the event's variant
+    events: Vec<(String, Vec<Arg>)>,
+}
+
+/// The undecoded data of an event, all arguments SCALE encoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEvent {
+    // the position of the event in the block
+    pub index: u32,
+    pub module: String,
+    pub variant: String,
+    pub data: Vec<u8>,
+}
+
+/// Splits the encoded events of a block (`System::Events`) into single events using the metadata.
+pub struct EventDecoder {
+    modules: HashMap<u8, ModuleEvents>,
+}
+
+impl EventDecoder {
+    pub fn new(metadata: RuntimeMetadataPrefixed) -> Result<Self, DecodeError> {
+        if metadata.0 != META_RESERVED {
+            return Err(DecodeError::Metadata("invalid prefix"));
+        }
+        let metadata = match metadata.1 {
+            RuntimeMetadata::V12(metadata) => metadata,
+            _ => return Err(DecodeError::Metadata("unsupported version")),
+        };
+
+        let mut modules = HashMap::new();
+        for module in decoded(metadata.modules)? {
+            if let Some(events) = module.event {
+                let events = decoded(events)?
+                    .into_iter()
+                    .map(event_arguments)
+                    .collect::<Result<Vec<_>, _>>()?;
+                let name = decoded(module.name)?;
+                modules.insert(module.index, ModuleEvents { name, events });
+            }
+        }
+        Ok(EventDecoder { modules })
+    }
+
+    pub fn decode_events(&self, input: &mut &[u8]) -> Result<Vec<RawEvent>, DecodeError> {
+        let len = <Compact<u32>>::decode(input)?.0;
+        let mut events = Vec::with_capacity(len as usize);
+        for index in 0..len {
+            skip_phase(input)?;
+            let module_index = u8::decode(input)?;
+            let module = self
+                .modules
+                .get(&module_index)
+                .ok_or(DecodeError::UnknownModule(module_index))?;
+            let event_index = u8::decode(input)?;
+            let (variant, arguments) = module
+                .events
+                .get(event_index as usize)
+                .ok_or_else(|| DecodeError::UnknownEvent(module.name.clone(), event_index))?;
+
+            // skip over all arguments, the consumed bytes are the event's data
+            let start = *input;
+            for arg in arguments.iter() {
+                skip_arg(arg, input)?;
+            }
+            let data = start[..start.len() - input.len()].to_vec();
+
+            // the topics come after the event data
+            let _topics = Vec::<[u8; 32]>::decode(input)?;
+            events.push(RawEvent {
+                index,
+                module: module.name.clone(),
+                variant: variant.clone(),
+                data,
+            });
+        }
+        Ok(events)
+    }
+}
+
+fn decoded<B, O>(value: DecodeDifferent<B, O>) -> Result<O, DecodeError> {
+    match value {
+        DecodeDifferent::Decoded(value) => Ok(value),
+        DecodeDifferent::Encode(_) => Err(DecodeError::Metadata("expected decoded metadata")),
+    }
+}
+
+fn event_arguments(event: EventMetadata) -> Result<(String, Vec<Arg>), DecodeError> {
+    let name = decoded(event.name)?;
+    let arguments = decoded(event.arguments)?;
+    Ok((name, arguments.iter().map(|arg| Arg::parse(arg)).collect()))
+}
+
+fn skip_arg(arg: &Arg, input: &mut &[u8]) -> Result<(), DecodeError> {
+    match arg {
+        Arg::Primitive(name) => skip_primitive(name, input),
+        Arg::Vec(arg) => {
+            let len = <Compact<u32>>::decode(input)?.0;
+            for _ in 0..len {
+                skip_arg(arg, input)?;
+            }
+            Ok(())
+        }
+        Arg::Option(arg) => match u8::decode(input)? {
+            0 => Ok(()),
+            1 => skip_arg(arg, input),
+            _ => Err(codec::Error::from("invalid Option").into()),
+        },
+        Arg::Tuple(args) => args.iter().try_for_each(|arg| skip_arg(arg, input)),
+    }
+}
+
+/// Skips the phase of the event, only `ApplyExtrinsic` carries the extrinsic's index.
+fn skip_phase(input: &mut &[u8]) -> Result<(), DecodeError> {
+    match u8::decode(input)? {
+        0 => skip::<u32>(input),
+        1 | 2 => Ok(()),
+        _ => Err(codec::Error::from("invalid Phase").into()),
+    }
+}
+
+fn skip<T: Decode>(input: &mut &[u8]) -> Result<(), DecodeError> {
+    T::decode(input)?;
+    Ok(())
+}
+
+/// Skips an argument of all types used in the events of the provotum runtime.
+fn skip_primitive(name: &str, input: &mut &[u8]) -> Result<(), DecodeError> {
+    match name {
+        "bool" => skip::<bool>(input),
+        "u8" => skip::<u8>(input),
+        "u32" => skip::<u32>(input),
+        "u64" | "AuthorityWeight" => skip::<u64>(input),
+        "u128" | "Balance" => skip::<u128>(input),
+        "AccountId" | "AuthorityId" | "Hash" => skip::<[u8; 32]>(input),
+        "AuthorityList" => skip::<Vec<([u8; 32], u64)>>(input),
+        "Status" => skip::<BalanceStatus>(input),
+        "DispatchInfo" => skip::<DispatchInfo>(input),
+        "DispatchError" => skip::<DispatchError>(input),
+        "DispatchResult" => skip::<DispatchResult>(input),
+        // the types of the mixnet pallet
+        "VoteId" | "TopicId" | "Title" => skip::<Vec<u8>>(input),
+        "Ballot" => skip::<Ballot>(input),
+        "SubstratePK" => skip::<SubstratePK>(input),
+        "PublicParameters" => skip::<PublicParameters>(input),
+        "Topic" => skip::<Topic>(input),
+        "VotePhase" => skip::<VotePhase>(input),
+        "PublicKeyShare" => skip::<PublicKeyShare>(input),
+        "TopicResult" => skip::<TopicResult>(input),
+        "RandomizerKey" => skip::<RandomizerKey>(input),
+        "CredentialKey" => skip::<CredentialKey>(input),
+        _ => Err(DecodeError::UnknownType(name.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+    use frame_metadata::{ExtrinsicMetadata, ModuleMetadata, RuntimeMetadataV12};
+
+    fn event(name: &'static str, arguments: Vec<&'static str>) -> EventMetadata {
+        EventMetadata {
+            name: DecodeDifferent::Decoded(name.to_string()),
+            arguments: DecodeDifferent::Decoded(arguments.into_iter().map(String::from).collect()),
+            documentation: DecodeDifferent::Decoded(vec![]),
+        }
+    }
+
+    fn module(name: &'static str, index: u8, events: Vec<EventMetadata>) -> ModuleMetadata {
+        ModuleMetadata {
+            name: DecodeDifferent::Decoded(name.to_string()),
+            storage: None,
+            calls: None,
+            event: Some(DecodeDifferent::Decoded(events)),
+            constants: DecodeDifferent::Decoded(vec![]),
+            errors: DecodeDifferent::Decoded(vec![]),
+            index,
+        }
+    }
+
+    fn setup_decoder() -> EventDecoder {
+        let modules = vec![
+            module(
+                "System",
+                0,
+                vec![event("ExtrinsicSuccess", vec!["DispatchInfo"])],
+            ),
+            module(
+                "PalletMixnet",
+                8,
+                vec![
+                    event("BallotSubmitted", vec!["AccountId", "VoteId", "Ballot"]),
+                    event("VotePhaseChanged", vec!["VoteId", "VotePhase"]),
+                ],
+            ),
+        ];
+        let metadata = RuntimeMetadataV12 {
+            modules: DecodeDifferent::Decoded(modules),
+            extrinsic: ExtrinsicMetadata {
+                version: 4,
+                signed_extensions: vec![],
+            },
+        };
+        EventDecoder::new(RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V12(metadata),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn it_should_parse_event_arguments() {
+        assert_eq!(
+            Arg::parse("Vec<(AuthorityId, AuthorityWeight)>"),
+            Arg::Vec(Box::new(Arg::Tuple(vec![
+                Arg::Primitive("AuthorityId".to_string()),
+                Arg::Primitive("AuthorityWeight".to_string())
+            ])))
+        );
+        assert_eq!(
+            Arg::parse("Option<VoteId>"),
+            Arg::Option(Box::new(Arg::Primitive("VoteId".to_string())))
+        );
+    }
+
+    #[test]
+    fn it_should_decode_events() {
+        let decoder = setup_decoder();
+        let vote_id = b"vote".to_vec();
+        let phase_changed = (vote_id.clone(), VotePhase::Tallying).encode();
+
+        // two event records: (phase, module, variant, data, topics)
+        let mut encoded = Compact(2u32).encode();
+        encoded.extend((0u8, 0u32, 0u8, 0u8).encode());
+        encoded.extend(DispatchInfo::default().encode());
+        encoded.extend(Vec::<[u8; 32]>::new().encode());
+        encoded.extend((0u8, 0u32, 8u8, 1u8).encode());
+        encoded.extend(phase_changed.clone());
+        encoded.extend(Vec::<[u8; 32]>::new().encode());
+
+        let events = decoder.decode_events(&mut &encoded[..]).unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].module, "System");
+        assert_eq!(events[1].index, 1);
+        assert_eq!(events[1].module, "PalletMixnet");
+        assert_eq!(events[1].variant, "VotePhaseChanged");
+        assert_eq!(events[1].data, phase_changed);
+    }
+
+    #[test]
+    fn it_should_refuse_unknown_modules() {
+        let decoder = setup_decoder();
+        let mut encoded = Compact(1u32).encode();
+        encoded.extend((0u8, 0u32, 3u8, 0u8).encode());
+        let result = decoder.decode_events(&mut &encoded[..]);
+        assert!(matches!(result, Err(DecodeError::UnknownModule(3))));
+    }
+}
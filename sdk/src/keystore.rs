@@ -0,0 +1,310 @@
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::Rng;
+use scrypt::{scrypt, Params};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt, fs, io, path::Path};
+use substrate_subxt::sp_core::{crypto::Ss58Codec, sr25519::Pair, Pair as KeyPairGenerator};
+
+/// the default scrypt cost parameter (N = 2^15)
+pub const DEFAULT_LOG_N: u8 = 15;
+
+/// the file extension of keystore entries
+const EXTENSION: &str = "json";
+
+#[derive(Debug)]
+pub enum KeystoreError {
+    Io(io::Error),
+    InvalidFile(String),
+    InvalidName(String),
+    InvalidPassword(String),
+    InvalidSecret(String),
+    PublicKeyMismatch(String),
+    DuplicateSealer(String),
+    UnknownSealer(String),
+}
+
+impl fmt::Display for KeystoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeystoreError::Io(err) => write!(f, "keystore io error: {}", err),
+            KeystoreError::InvalidFile(file) => write!(f, "invalid keystore file: {}", file),
+            KeystoreError::InvalidName(name) => write!(f, "invalid sealer name: {}", name),
+            KeystoreError::InvalidPassword(name) => {
+                write!(f, "failed to decrypt the key of: {}", name)
+            }
+            KeystoreError::InvalidSecret(name) => write!(f, "invalid secret of: {}", name),
+            KeystoreError::PublicKeyMismatch(name) => {
+                write!(f, "the public key does not match the secret of: {}", name)
+            }
+            KeystoreError::DuplicateSealer(name) => write!(f, "duplicate sealer: {}", name),
+            KeystoreError::UnknownSealer(name) => write!(f, "unknown sealer: {}", name),
+        }
+    }
+}
+
+impl From<io::Error> for KeystoreError {
+    fn from(err: io::Error) -> Self {
+        KeystoreError::Io(err)
+    }
+}
+
+impl From<KeystoreError> for substrate_subxt::Error {
+    fn from(err: KeystoreError) -> Self {
+        substrate_subxt::Error::Other(err.to_string())
+    }
+}
+
+/// A password encrypted key, either a single file or stored as `<name>.json` in a keystore directory.
+///
+/// The secret (a secret URI, e.g. a mnemonic) is encrypted using ChaCha20Poly1305,
+/// the key is derived from the password using scrypt.
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct KeystoreEntry {
+    pub name: String,
+    // the public key (ss58)
+    pub public: String,
+    pub log_n: u8,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+impl KeystoreEntry {
+    /// Encrypts the secret uri (or mnemonic) of an account.
+    pub fn encrypt(
+        name: &str,
+        secret: &str,
+        password: &str,
+        log_n: u8,
+    ) -> Result<Self, KeystoreError> {
+        if !is_valid_name(name) {
+            return Err(KeystoreError::InvalidName(name.to_string()));
+        }
+        let pair = Pair::from_string(secret, None)
+            .map_err(|_| KeystoreError::InvalidSecret(name.to_string()))?;
+        let public = pair.public().to_ss58check();
+        Self::encrypt_secret(name, &public, secret, password, log_n)
+    }
+
+    /// Encrypts an arbitrary secret, the public part is stored in plain.
+    pub fn encrypt_secret(
+        name: &str,
+        public: &str,
+        secret: &str,
+        password: &str,
+        log_n: u8,
+    ) -> Result<Self, KeystoreError> {
+        let mut rng = rand::thread_rng();
+        let salt: [u8; 32] = rng.gen();
+        let nonce: [u8; 12] = rng.gen();
+        let key = derive_key(password, &salt, log_n)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| KeystoreError::InvalidSecret(name.to_string()))?;
+
+        Ok(KeystoreEntry {
+            name: name.to_string(),
+            public: public.to_string(),
+            log_n,
+            salt: hex::encode(salt),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+        })
+    }
+
+    /// Decrypts the secret uri (or mnemonic) of an account.
+    pub fn decrypt(&self, password: &str) -> Result<Pair, KeystoreError> {
+        let secret = self.decrypt_secret(password)?;
+        let pair = Pair::from_string(&secret, None)
+            .map_err(|_| KeystoreError::InvalidSecret(self.name.clone()))?;
+
+        // the public key is stored in plain, it must match the secret
+        if pair.public().to_ss58check() != self.public {
+            return Err(KeystoreError::PublicKeyMismatch(self.name.clone()));
+        }
+        Ok(pair)
+    }
+
+    /// Decrypts the secret, the caller must check that it matches the public part.
+    pub fn decrypt_secret(&self, password: &str) -> Result<String, KeystoreError> {
+        let invalid_file = || KeystoreError::InvalidFile(self.name.clone());
+        let salt = hex::decode(&self.salt).map_err(|_| invalid_file())?;
+        let nonce = hex::decode(&self.nonce).map_err(|_| invalid_file())?;
+        let ciphertext = hex::decode(&self.ciphertext).map_err(|_| invalid_file())?;
+        if nonce.len() != 12 {
+            return Err(invalid_file());
+        }
+
+        let key = derive_key(password, &salt, self.log_n)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let secret = cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+            .map_err(|_| KeystoreError::InvalidPassword(self.name.clone()))?;
+        String::from_utf8(secret).map_err(|_| KeystoreError::InvalidSecret(self.name.clone()))
+    }
+
+    /// Reads a single entry from the file.
+    pub fn read(path: &Path) -> Result<Self, KeystoreError> {
+        let content = fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map_err(|_| KeystoreError::InvalidFile(path.display().to_string()))
+    }
+
+    /// Writes the entry to the file, existing files are never overwritten.
+    pub fn write_file(&self, path: &Path) -> Result<(), KeystoreError> {
+        if path.exists() {
+            return Err(KeystoreError::DuplicateSealer(path.display().to_string()));
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|_| KeystoreError::InvalidFile(self.name.clone()))?;
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// Writes the entry to `<dir>/<name>.json`, existing entries are never overwritten.
+    pub fn write(&self, dir: &Path) -> Result<(), KeystoreError> {
+        if !is_valid_name(&self.name) {
+            return Err(KeystoreError::InvalidName(self.name.clone()));
+        }
+        fs::create_dir_all(dir)?;
+        let path = dir.join(&self.name).with_extension(EXTENSION);
+        if path.exists() {
+            return Err(KeystoreError::DuplicateSealer(self.name.clone()));
+        }
+        self.write_file(&path)
+    }
+}
+
+/// The keys of all sealers managed by this instance.
+pub struct Keystore {
+    sealers: HashMap<String, Pair>,
+}
+
+impl Keystore {
+    /// Loads and decrypts all entries (`*.json`) in the directory.
+    pub fn load(dir: &Path, password: &str) -> Result<Self, KeystoreError> {
+        let mut sealers = HashMap::new();
+        for file in fs::read_dir(dir)? {
+            let path = file?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some(EXTENSION) {
+                continue;
+            }
+            let entry = KeystoreEntry::read(&path)?;
+            if !is_valid_name(&entry.name) {
+                return Err(KeystoreError::InvalidName(entry.name));
+            }
+            let pair = entry.decrypt(password)?;
+            if sealers.insert(entry.name.clone(), pair).is_some() {
+                return Err(KeystoreError::DuplicateSealer(entry.name));
+            }
+        }
+        Ok(Keystore { sealers })
+    }
+
+    /// Creates a keystore of already decrypted keys.
+    pub fn from_pairs(pairs: Vec<(String, Pair)>) -> Self {
+        Keystore {
+            sealers: pairs.into_iter().collect(),
+        }
+    }
+
+    /// Returns the key of the sealer, unknown sealers are refused.
+    pub fn get_sealer(&self, name: &str) -> Result<Pair, KeystoreError> {
+        self.sealers
+            .get(name)
+            .cloned()
+            .ok_or_else(|| KeystoreError::UnknownSealer(name.to_string()))
+    }
+
+    pub fn sealers(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.sealers.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
+
+/// sealer names are used as file names and path parameters
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn derive_key(password: &str, salt: &[u8], log_n: u8) -> Result<[u8; 32], KeystoreError> {
+    let params =
+        Params::new(log_n, 8, 1).map_err(|_| KeystoreError::InvalidFile("scrypt".to_string()))?;
+    let mut key = [0u8; 32];
+    scrypt(password.as_bytes(), salt, &params, &mut key)
+        .map_err(|_| KeystoreError::InvalidFile("scrypt".to_string()))?;
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sp_keyring::AccountKeyring;
+    use std::env;
+
+    // keep the tests fast, never use this in production
+    const TEST_LOG_N: u8 = 4;
+
+    #[test]
+    fn it_should_encrypt_and_decrypt_an_entry() {
+        let entry = KeystoreEntry::encrypt("bob", "//Bob", "password", TEST_LOG_N).unwrap();
+        assert_eq!(entry.public, AccountKeyring::Bob.public().to_ss58check());
+
+        let pair = entry.decrypt("password").unwrap();
+        assert_eq!(pair.public(), AccountKeyring::Bob.public());
+
+        let result = entry.decrypt("wrong");
+        assert!(matches!(result, Err(KeystoreError::InvalidPassword(_))));
+    }
+
+    #[test]
+    fn it_should_refuse_invalid_names() {
+        let result = KeystoreEntry::encrypt("../bob", "//Bob", "password", TEST_LOG_N);
+        assert!(matches!(result, Err(KeystoreError::InvalidName(_))));
+    }
+
+    #[test]
+    fn it_should_never_overwrite_an_entry() {
+        let path = env::temp_dir().join("provotum-sdk-test-keystore.json");
+        let _ = fs::remove_file(&path);
+
+        let entry = KeystoreEntry::encrypt("dave", "//Dave", "password", TEST_LOG_N).unwrap();
+        entry.write_file(&path).unwrap();
+        let other = KeystoreEntry::encrypt("eve", "//Eve", "password", TEST_LOG_N).unwrap();
+        assert!(other.write_file(&path).is_err());
+
+        let read = KeystoreEntry::read(&path).unwrap();
+        assert_eq!(read, entry);
+    }
+
+    #[test]
+    fn it_should_load_multiple_sealers() {
+        let dir = env::temp_dir().join("provotum-sdk-test-keystore");
+        let _ = fs::remove_dir_all(&dir);
+
+        for (name, secret) in &[("bob", "//Bob"), ("charlie", "//Charlie")] {
+            let entry = KeystoreEntry::encrypt(name, secret, "password", TEST_LOG_N).unwrap();
+            entry.write(&dir).unwrap();
+        }
+        let entry = KeystoreEntry::encrypt("bob", "//Dave", "password", TEST_LOG_N).unwrap();
+        let result = entry.write(&dir);
+        assert!(matches!(result, Err(KeystoreError::DuplicateSealer(_))));
+
+        let keystore = Keystore::load(&dir, "password").unwrap();
+        assert_eq!(keystore.sealers(), vec!["bob", "charlie"]);
+        let charlie = keystore.get_sealer("charlie").unwrap();
+        assert_eq!(charlie.public(), AccountKeyring::Charlie.public());
+        let result = keystore.get_sealer("dave");
+        assert!(matches!(result, Err(KeystoreError::UnknownSealer(_))));
+
+        let result = Keystore::load(&dir, "wrong");
+        assert!(matches!(result, Err(KeystoreError::InvalidPassword(_))));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,20 @@
+//! A client library for the mixnet pallet of the Provotum node.
+//!
+//! The storage items (`stores`), the extrinsics (`calls`) and the events (`events`) of the pallet
+//! are defined once using the pallet's own `types`, `rpc` wraps them in typed async functions.
+//! The messages signed to authenticate at the off-chain services are defined in `auth`,
+//! the password encrypted keys of voters and sealers are read and written by `keystore`.
+
+pub mod auth;
+pub mod calls;
+pub mod events;
+pub mod keystore;
+pub mod rpc;
+pub mod stores;
+
+pub use pallet_mixnet::types;
+
+use types::NrOfShuffles;
+
+/// the number of shuffles of each question before it is decrypted, see `Module::NR_OF_SHUFFLES` of the pallet
+pub const NR_OF_SHUFFLES: NrOfShuffles = 3;
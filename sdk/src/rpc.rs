@@ -0,0 +1,542 @@
+//! Typed async functions to read the storage of the mixnet pallet and to submit its extrinsics.
+
+use crate::calls::{
+    CastAnonymousBallot, CastBallot, CombineDecryptedShares, CombinePublicKeyShares, CreateVote,
+    RelayBallot, RemoveRandomizerKey, RemoveRelayer, SetRandomizerSignatureRequired, SetVotePhase,
+    StoreAnswers, StoreCredentialKey, StorePublicKey, StorePublicKeyShare, StoreQuestion,
+    StoreRandomizerKey, StoreRelayer, SubmitPartialDecryption, SubmitShuffledVotesAndProof,
+};
+use crate::stores::{
+    AnswersStore, BallotStore, CiphersStore, CountBySealerStore, CountStore, CredentialKeyStore,
+    DecryptedSharesStore, DecryptionProofsStore, PublicKeyShareBySealerStore, PublicKeySharesStore,
+    PublicKeyStore, RandomizerKeysStore, RandomizerSignatureRequiredStore, RelayersStore,
+    SealersStore, ShuffleProofsStore, ShuffleStateStore, SpentTokensStore, TallyStore, TopicsStore,
+    VoteStore, VotersStore, VotesStore, VotingAuthoritiesStore,
+};
+use codec::Encode;
+use pallet_mixnet::types::{
+    Ballot, Cipher, CredentialKey, DecryptedShare, DecryptedShareProof, DecryptionPayload,
+    NrOfShuffles, PublicKey as SubstratePK, PublicKeyShare, PublicParameters, RandomizerKey,
+    RandomizerSignature, ShufflePayload, ShuffleState, Title, Topic, TopicAnswer, TopicId,
+    TopicResult, Vote, VoteId, VotePhase, VoterCredential, VoterToken,
+};
+use substrate_subxt::{
+    sp_core::{hashing::blake2_128, storage::StorageKey},
+    system::System,
+    Call, Client, Error, ExtrinsicSuccess, NodeTemplateRuntime, Signer, Store,
+};
+
+type AccountId = <NodeTemplateRuntime as System>::AccountId;
+type Hash = <NodeTemplateRuntime as System>::Hash;
+
+/// The signer of an extrinsic, e.g. a `PairSigner`.
+pub type MixnetSigner = dyn Signer<NodeTemplateRuntime> + Send + Sync;
+
+/// the number of storage keys fetched per request
+const PAGE_SIZE: u32 = 1000;
+
+pub async fn get_voting_authorities(
+    client: &Client<NodeTemplateRuntime>,
+) -> Result<Vec<AccountId>, Error> {
+    let store = VotingAuthoritiesStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_sealers(client: &Client<NodeTemplateRuntime>) -> Result<Vec<AccountId>, Error> {
+    let store = SealersStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_voters(client: &Client<NodeTemplateRuntime>) -> Result<Vec<AccountId>, Error> {
+    let store = VotersStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+/// Returns the number of offchain worker transactions.
+pub async fn get_count(client: &Client<NodeTemplateRuntime>) -> Result<u32, Error> {
+    let store = CountStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_count_by_sealer(
+    client: &Client<NodeTemplateRuntime>,
+    sealer: AccountId,
+) -> Result<u32, Error> {
+    let store = CountBySealerStore { sealer };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_vote_ids(client: &Client<NodeTemplateRuntime>) -> Result<Vec<VoteId>, Error> {
+    let store = VotesStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_vote(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<Option<Vote<AccountId>>, Error> {
+    let store = VoteStore { vote_id };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_topics(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<Vec<Topic>, Error> {
+    let store = TopicsStore { vote_id };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_answers(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+) -> Result<Vec<TopicAnswer>, Error> {
+    let store = AnswersStore { topic_id };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_ballot(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    voter: AccountId,
+) -> Result<Option<Ballot>, Error> {
+    let store = BallotStore { vote_id, voter };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_ciphers(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+    nr_of_shuffles: NrOfShuffles,
+) -> Result<Vec<Cipher>, Error> {
+    let store = CiphersStore {
+        topic_id,
+        nr_of_shuffles,
+    };
+    let value = client
+        .fetch(&store, None)
+        .await?
+        .ok_or("failed to fetch ciphers!")?;
+    Ok(value)
+}
+
+pub async fn get_shuffle_proofs(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    topic_id: TopicId,
+) -> Result<Vec<ShufflePayload>, Error> {
+    let store = ShuffleProofsStore { vote_id, topic_id };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_shuffle_state(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    topic_id: TopicId,
+) -> Result<Option<ShuffleState>, Error> {
+    let store = ShuffleStateStore { vote_id, topic_id };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_tally(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+) -> Result<Option<TopicResult>, Error> {
+    let store = TallyStore { topic_id };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_decrypted_shares(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+    sealer: AccountId,
+) -> Result<Vec<DecryptedShare>, Error> {
+    let store = DecryptedSharesStore { topic_id, sealer };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_decryption_proofs(
+    client: &Client<NodeTemplateRuntime>,
+    topic_id: TopicId,
+    sealer: AccountId,
+) -> Result<Vec<DecryptionPayload>, Error> {
+    let store = DecryptionProofsStore { topic_id, sealer };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_public_key_shares(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<Vec<PublicKeyShare>, Error> {
+    let store = PublicKeySharesStore { vote_id };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_public_key_share(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    sealer: AccountId,
+) -> Result<Option<PublicKeyShare>, Error> {
+    let store = PublicKeyShareBySealerStore { vote_id, sealer };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_public_key(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<Option<SubstratePK>, Error> {
+    let store = PublicKeyStore { vote_id };
+    client.fetch(&store, None).await
+}
+
+pub async fn get_vote_public_key(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<SubstratePK, Error> {
+    let store = PublicKeyStore { vote_id };
+    let value = client
+        .fetch(&store, None)
+        .await?
+        .ok_or("failed to fetch public key!")?;
+    Ok(value)
+}
+
+pub async fn get_randomizer_keys(
+    client: &Client<NodeTemplateRuntime>,
+) -> Result<Vec<RandomizerKey>, Error> {
+    let store = RandomizerKeysStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn is_randomizer_signature_required(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<bool, Error> {
+    let store = RandomizerSignatureRequiredStore { vote_id };
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_relayers(client: &Client<NodeTemplateRuntime>) -> Result<Vec<AccountId>, Error> {
+    let store = RelayersStore {};
+    client.fetch_or_default(&store, None).await
+}
+
+pub async fn get_credential_key(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<Option<CredentialKey>, Error> {
+    let store = CredentialKeyStore { vote_id };
+    client.fetch(&store, None).await
+}
+
+pub async fn is_token_spent(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+    token: VoterToken,
+) -> Result<bool, Error> {
+    let store = SpentTokensStore { vote_id, token };
+    client.fetch_or_default(&store, None).await
+}
+
+/// Counts the ballots of a vote by iterating over the storage keys: (VoteId, AccountId).
+pub async fn count_ballots(
+    client: &Client<NodeTemplateRuntime>,
+    vote_id: VoteId,
+) -> Result<usize, Error> {
+    // all keys of the vote start with: prefix ++ blake2_128_concat(vote_id)
+    let mut vote_prefix = BallotStore::prefix(client.metadata())?.0;
+    let encoded_vote_id = vote_id.encode();
+    vote_prefix.extend(blake2_128(&encoded_vote_id).iter());
+    vote_prefix.extend(encoded_vote_id);
+
+    // the keys are sorted, start right at the vote's keys
+    let mut start_key = Some(StorageKey(vote_prefix.clone()));
+    let mut count = 0;
+    loop {
+        let keys = client
+            .fetch_keys::<BallotStore>(PAGE_SIZE, start_key.take(), None)
+            .await?;
+        let nr_of_keys = keys.len();
+        let ballots = keys
+            .iter()
+            .take_while(|key| key.0.starts_with(&vote_prefix))
+            .count();
+        count += ballots;
+        if ballots < nr_of_keys || nr_of_keys < PAGE_SIZE as usize {
+            return Ok(count);
+        }
+        start_key = keys.last().cloned();
+    }
+}
+
+pub async fn set_vote_phase(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    vote_phase: VotePhase,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = SetVotePhase {
+        vote_id,
+        vote_phase,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn create_vote(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    title: Title,
+    params: PublicParameters,
+    topics: Vec<Topic>,
+    batch_size: u64,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = CreateVote {
+        vote_id,
+        title,
+        params,
+        topics,
+        batch_size,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn store_question(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    topic: Topic,
+    batch_size: u64,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StoreQuestion {
+        vote_id,
+        topic,
+        batch_size,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn store_answers(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    topic_id: TopicId,
+    answers: Vec<TopicAnswer>,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StoreAnswers {
+        vote_id,
+        topic_id,
+        answers,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn store_public_key(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    pk: SubstratePK,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StorePublicKey { vote_id, pk };
+    client.watch(call, signer).await
+}
+
+pub async fn store_public_key_share(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    pk_share: PublicKeyShare,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StorePublicKeyShare { vote_id, pk_share };
+    client.watch(call, signer).await
+}
+
+pub async fn combine_pk_shares(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = CombinePublicKeyShares { vote_id };
+    client.watch(call, signer).await
+}
+
+pub async fn store_randomizer_key(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    key: RandomizerKey,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StoreRandomizerKey { key };
+    client.watch(call, signer).await
+}
+
+pub async fn remove_randomizer_key(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    key: RandomizerKey,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = RemoveRandomizerKey { key };
+    client.watch(call, signer).await
+}
+
+pub async fn set_randomizer_signature_required(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    required: bool,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = SetRandomizerSignatureRequired { vote_id, required };
+    client.watch(call, signer).await
+}
+
+pub async fn store_credential_key(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    key: CredentialKey,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StoreCredentialKey { vote_id, key };
+    client.watch(call, signer).await
+}
+
+pub async fn store_relayer(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    relayer: AccountId,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = StoreRelayer { relayer };
+    client.watch(call, signer).await
+}
+
+pub async fn remove_relayer(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    relayer: AccountId,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = RemoveRelayer { relayer };
+    client.watch(call, signer).await
+}
+
+/// Submits a ballot without waiting for its inclusion, returns the hash of the extrinsic.
+pub async fn submit_ballot(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    ballot: Ballot,
+    signatures: Vec<RandomizerSignature>,
+) -> Result<Hash, Error> {
+    let call = CastBallot {
+        vote_id,
+        ballot,
+        signatures,
+    };
+    client.submit(call, signer).await
+}
+
+pub async fn cast_ballot(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    ballot: Ballot,
+    signatures: Vec<RandomizerSignature>,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = CastBallot {
+        vote_id,
+        ballot,
+        signatures,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn cast_anonymous_ballot(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    ballot: Ballot,
+    signatures: Vec<RandomizerSignature>,
+    token: VoterToken,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = CastAnonymousBallot {
+        vote_id,
+        ballot,
+        signatures,
+        token,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn relay_ballot(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    ballot: Ballot,
+    signatures: Vec<RandomizerSignature>,
+    credential: VoterCredential,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = RelayBallot {
+        vote_id,
+        ballot,
+        signatures,
+        credential,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn submit_shuffled_votes_and_proof(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    topic_id: TopicId,
+    payload: ShufflePayload,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = SubmitShuffledVotesAndProof {
+        vote_id,
+        topic_id,
+        payload,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn submit_partial_decryptions(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    topic_id: TopicId,
+    shares: Vec<DecryptedShare>,
+    proof: DecryptedShareProof,
+    nr_of_shuffles: NrOfShuffles,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = SubmitPartialDecryption {
+        vote_id,
+        topic_id,
+        shares,
+        proof,
+        nr_of_shuffles,
+    };
+    client.watch(call, signer).await
+}
+
+pub async fn combine_decrypted_shares(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    vote_id: VoteId,
+    topic_id: TopicId,
+    encoded: bool,
+    nr_of_shuffles: NrOfShuffles,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    let call = CombineDecryptedShares {
+        vote_id,
+        topic_id,
+        encoded,
+        nr_of_shuffles,
+    };
+    client.watch(call, signer).await
+}
+
+/// Submits any call of the mixnet pallet and waits for its inclusion.
+pub async fn watch<C: Call<NodeTemplateRuntime> + Send + Sync>(
+    client: &Client<NodeTemplateRuntime>,
+    signer: &MixnetSigner,
+    call: C,
+) -> Result<ExtrinsicSuccess<NodeTemplateRuntime>, Error> {
+    client.watch(call, signer).await
+}
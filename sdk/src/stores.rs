@@ -0,0 +1,860 @@
+//! The storage items of the mixnet pallet.
+
+use codec::Encode;
+use pallet_mixnet::types::{
+    Ballot, Cipher, CredentialKey, DecryptedShare, DecryptionPayload, NrOfShuffles,
+    PublicKey as SubstratePK, PublicKeyShare, RandomizerKey, ShufflePayload, ShuffleState, Topic,
+    TopicAnswer, TopicId, TopicResult, Vote, VoteId, VoterToken,
+};
+use substrate_subxt::{
+    sp_core::storage::StorageKey, system::System, Metadata, MetadataError, NodeTemplateRuntime,
+    Store,
+};
+
+/// The accounts of the voting authorities.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct VotingAuthoritiesStore {}
+
+impl Store<NodeTemplateRuntime> for VotingAuthoritiesStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "VotingAuthorities";
+    /// Return type.
+    type Returns = Vec<<NodeTemplateRuntime as System>::AccountId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The accounts of the sealers.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SealersStore {}
+
+impl Store<NodeTemplateRuntime> for SealersStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Sealers";
+    /// Return type.
+    type Returns = Vec<<NodeTemplateRuntime as System>::AccountId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The number of offchain worker transactions per sealer.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CountBySealerStore {
+    pub sealer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Store<NodeTemplateRuntime> for CountBySealerStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "CountsBySealer";
+    /// Return type.
+    type Returns = u32;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.sealer))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The number of offchain worker transactions.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CountStore {}
+
+impl Store<NodeTemplateRuntime> for CountStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Counts";
+    /// Return type.
+    type Returns = u32;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The accounts of the voters.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct VotersStore {}
+
+impl Store<NodeTemplateRuntime> for VotersStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Voters";
+    /// Return type.
+    type Returns = Vec<<NodeTemplateRuntime as System>::AccountId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The ids of all votes.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct VotesStore {}
+
+impl Store<NodeTemplateRuntime> for VotesStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "VoteIds";
+    /// Return type.
+    type Returns = Vec<VoteId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// A vote.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct VoteStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for VoteStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Votes";
+    /// Return type.
+    type Returns = Vote<<NodeTemplateRuntime as System>::AccountId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The questions of a vote.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct TopicsStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for TopicsStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Topics";
+    /// Return type.
+    type Returns = Vec<Topic>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The allowed answers of a question.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct AnswersStore {
+    pub topic_id: TopicId,
+}
+
+impl Store<NodeTemplateRuntime> for AnswersStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Answers";
+    /// Return type.
+    type Returns = Vec<TopicAnswer>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.topic_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The ballot of a voter.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct BallotStore {
+    pub vote_id: VoteId,
+    pub voter: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Store<NodeTemplateRuntime> for BallotStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Ballots";
+    /// Return type.
+    type Returns = Ballot;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.double_map()?;
+        Ok(item.key(&self.vote_id, &self.voter))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The ciphers of a question after a number of shuffles.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CiphersStore {
+    pub topic_id: TopicId,
+    pub nr_of_shuffles: NrOfShuffles,
+}
+
+impl Store<NodeTemplateRuntime> for CiphersStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Ciphers";
+    /// Return type.
+    type Returns = Vec<Cipher>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.double_map()?;
+        Ok(item.key(&self.topic_id, &self.nr_of_shuffles))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The shuffles of a question including their proofs.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct ShuffleProofsStore {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+}
+
+impl Store<NodeTemplateRuntime> for ShuffleProofsStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "ShuffleProofs";
+    /// Return type.
+    type Returns = Vec<ShufflePayload>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&(&self.vote_id, &self.topic_id)))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The shuffle progress of a question.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct ShuffleStateStore {
+    pub vote_id: VoteId,
+    pub topic_id: TopicId,
+}
+
+impl Store<NodeTemplateRuntime> for ShuffleStateStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "ShuffleStateStore";
+    /// Return type.
+    type Returns = ShuffleState;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&(&self.vote_id, &self.topic_id)))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The tally of a question.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct TallyStore {
+    pub topic_id: TopicId,
+}
+
+impl Store<NodeTemplateRuntime> for TallyStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Tally";
+    /// Return type.
+    type Returns = TopicResult;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.topic_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The partial decryptions of a question by a sealer.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct DecryptedSharesStore {
+    pub topic_id: TopicId,
+    pub sealer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Store<NodeTemplateRuntime> for DecryptedSharesStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "DecryptedShares";
+    /// Return type.
+    type Returns = Vec<DecryptedShare>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.double_map()?;
+        Ok(item.key(&self.topic_id, &self.sealer))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The partial decryptions of a question by a sealer including their proofs.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct DecryptionProofsStore {
+    pub topic_id: TopicId,
+    pub sealer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Store<NodeTemplateRuntime> for DecryptionProofsStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "DecryptionProofs";
+    /// Return type.
+    type Returns = Vec<DecryptionPayload>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.double_map()?;
+        Ok(item.key(&self.topic_id, &self.sealer))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The public key shares of a vote.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct PublicKeySharesStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for PublicKeySharesStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "PublicKeyShares";
+    /// Return type.
+    type Returns = Vec<PublicKeyShare>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The public key share of a sealer.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct PublicKeyShareBySealerStore {
+    pub vote_id: VoteId,
+    pub sealer: <NodeTemplateRuntime as System>::AccountId,
+}
+
+impl Store<NodeTemplateRuntime> for PublicKeyShareBySealerStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "PublicKeyShareBySealer";
+    /// Return type.
+    type Returns = PublicKeyShare;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&(&self.vote_id, &self.sealer)))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The combined public key of a vote.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct PublicKeyStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for PublicKeyStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "PublicKey";
+    /// Return type.
+    type Returns = SubstratePK;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The keys of the registered randomizers.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct RandomizerKeysStore {}
+
+impl Store<NodeTemplateRuntime> for RandomizerKeysStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "RandomizerKeys";
+    /// Return type.
+    type Returns = Vec<RandomizerKey>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// Whether the ballots of a vote require a randomizer signature.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct RandomizerSignatureRequiredStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for RandomizerSignatureRequiredStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "RandomizerSignatureRequired";
+    /// Return type.
+    type Returns = bool;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The accounts of the registered relayers.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct RelayersStore {}
+
+impl Store<NodeTemplateRuntime> for RelayersStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "Relayers";
+    /// Return type.
+    type Returns = Vec<<NodeTemplateRuntime as System>::AccountId>;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .plain()?
+            .key())
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// The key blind-signing the voter tokens of a vote.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct CredentialKeyStore {
+    pub vote_id: VoteId,
+}
+
+impl Store<NodeTemplateRuntime> for CredentialKeyStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "CredentialKeys";
+    /// Return type.
+    type Returns = CredentialKey;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.map()?;
+        Ok(item.key(&self.vote_id))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}
+
+/// Whether a voter token is spent.
+#[derive(Clone, Debug, Eq, Encode, PartialEq)]
+pub struct SpentTokensStore {
+    pub vote_id: VoteId,
+    pub token: VoterToken,
+}
+
+impl Store<NodeTemplateRuntime> for SpentTokensStore {
+    /// Module name.
+    const MODULE: &'static str = "PalletMixnet";
+    /// Field name.
+    const FIELD: &'static str = "SpentTokens";
+    /// Return type.
+    type Returns = bool;
+    /// Returns the key prefix for storage maps
+    fn prefix(metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        Ok(metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .prefix())
+    }
+    /// Returns the `StorageKey`.
+    fn key(&self, metadata: &Metadata) -> Result<StorageKey, MetadataError> {
+        let storage = metadata.module(Self::MODULE)?.storage(Self::FIELD)?;
+        let item = storage.double_map()?;
+        Ok(item.key(&self.vote_id, &self.token))
+    }
+    /// Returns the default value.
+    fn default(&self, metadata: &Metadata) -> Result<Self::Returns, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .storage(Self::FIELD)?
+            .default()
+    }
+}